@@ -1,47 +1,121 @@
-use crate::{cmn, cpu, riot, tia};
-use alloc::rc::Rc;
+use crate::{
+    audio::{AudioConfig, AudioFrame, AudioStream},
+    cmn, cpu,
+    ctrl::{
+        ConsoleSwitches, ControllerInput, ControllerKind, ControllerPort, ControllerPorts,
+        Difficulty, Port,
+    },
+    movie::FrameChecksum,
+    power_on::{PowerOnConfig, PowerOnFill},
+    rewind::{RewindBuffer, RewindConfig},
+    riot,
+    state::{SaveState, StateError, StateReader, StateWriter},
+    tia::{self, TVStandard},
+    tv_detect::TVDetector,
+};
+use alloc::{boxed::Box, rc::Rc, vec::Vec};
 use core::cell::{Cell, RefCell};
 
-pub struct NtscAtari {
+const CLOCKS_PER_CPU_CYCLE: u8 = 3;
+
+/// The console, for any TV standard. Frame geometry & colors come from the TV's [`tia::TVConfig`].
+pub struct Atari {
     cpu: Rc<RefCell<cpu::NMOS6502>>,
     mem: riot::Memory,
     tia: Rc<RefCell<dyn tia::TIA>>,
     pia: Rc<RefCell<dyn riot::PIA6532>>,
+    tv: Rc<RefCell<dyn tia::TV>>,
+    ports: Rc<RefCell<ControllerPorts>>,
+    switches: Rc<Cell<ConsoleSwitches>>,
+    power_on: PowerOnConfig,
+    rom_checksum: u32,
+    /// Color clock within the current CPU cycle, the CPU & RIOT run on 0.
+    clock_phase: u8,
+    rewind: Option<RewindBuffer>,
+    audio: Option<AudioStream>,
+    tv_detector: Option<TVDetector>,
 }
 
-impl NtscAtari {
-    pub fn new(
-        tv: Rc<RefCell<dyn tia::TV<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>>>,
-    ) -> Self {
+impl Atari {
+    pub fn new(tv: Rc<RefCell<dyn tia::TV>>) -> Self {
+        Self::new_with_power_on(tv, PowerOnConfig::default())
+    }
+
+    pub fn new_with_power_on(tv: Rc<RefCell<dyn tia::TV>>, power_on: PowerOnConfig) -> Self {
         let rdy = Rc::new(Cell::new(cmn::LineState::High));
-        let tia = Rc::new(RefCell::new(tia::NtscTIA::new(rdy.clone(), tv.clone())));
-        let pia = Rc::new(RefCell::new(riot::InMemory6532::default()));
-        let mem = riot::Memory::new_with_rom(
+        let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+        let mut tia = tia::InMemoryTIA::new_with_ports(rdy.clone(), tv.clone(), ports.clone());
+        tia.power_on(power_on.tia_positions);
+        let tia = Rc::new(RefCell::new(tia));
+        let switches = Rc::new(Cell::new(ConsoleSwitches::default()));
+        let mut pia = riot::InMemory6532::new(ports.clone(), switches.clone());
+        pia.power_on(power_on.riot_timer);
+        let pia = Rc::new(RefCell::new(pia));
+        let mut mem = riot::Memory::new_with_rom(
             &[],
             cmn::LoHi(0x00, 0x00),
             riot::mm_6507,
             Some(tia.clone()),
             Some(pia.clone()),
-            true,
+            PowerOnFill::Zero,
         );
-        let cpu = Rc::new(RefCell::new(cpu::NMOS6502::new(rdy.clone(), &mem)));
+        mem.fill_ram(power_on.riot_ram);
+        let mut cpu = cpu::NMOS6502::new(rdy.clone(), &mem);
+        cpu.power_on(power_on.cpu_registers);
+        let cpu = Rc::new(RefCell::new(cpu));
 
-        Self { cpu, mem, tia, pia }
+        let mut atari = Self {
+            cpu,
+            mem,
+            tia,
+            pia,
+            tv,
+            ports,
+            switches,
+            power_on,
+            rom_checksum: cmn::FNV1A_INIT,
+            clock_phase: 0,
+            rewind: None,
+            audio: None,
+            tv_detector: None,
+        };
+        atari.sync_cpu_hz();
+        atari
     }
 
     pub fn load_rom(&mut self, addr: u16, data: &[u8]) {
+        self.rom_checksum = cmn::fnv1a(self.rom_checksum, &addr.to_le_bytes());
+        self.rom_checksum = cmn::fnv1a(self.rom_checksum, data);
         self.mem.load(data, addr.into());
         self.cpu.borrow_mut().reset_pc(&self.mem);
     }
 
+    #[inline]
+    pub fn power_on_config(&self) -> PowerOnConfig {
+        self.power_on
+    }
+
+    /// Identifies the cartridge: FNV-1a of every ROM loaded, with its address, in order.
+    #[inline]
+    pub fn rom_checksum(&self) -> u32 {
+        self.rom_checksum
+    }
+
+    /// Runs `cycles` color clocks. The CPU & RIOT run on every third one, counted across calls, so
+    /// ticking a clock at a time ends where a single long call does.
     pub fn tick(&mut self, cycles: usize) {
-        for i in 0..cycles {
-            if i % 3 == 0 {
+        for _ in 0..cycles {
+            if self.clock_phase == 0 {
                 self.pia.borrow_mut().tick(1);
                 self.cpu.borrow_mut().tick(&mut self.mem);
             }
             self.tia.borrow_mut().tick(1);
+            self.clock_phase = (self.clock_phase + 1) % CLOCKS_PER_CPU_CYCLE;
+            self.pump_audio();
+            self.detect_tv_standard();
         }
+
+        self.capture_rewind_snapshot();
     }
 
     pub fn run_for(&mut self, instructions: u64) {
@@ -53,7 +127,327 @@ impl NtscAtari {
         }
     }
 
+    /// A frame that long means the program has stopped generating VSYNC.
+    fn max_frame_color_clocks(&self) -> usize {
+        let tv = self.tv.borrow();
+        4 * tv.config().scanlines() * tv.config().pixels_per_scanline()
+    }
+
+    /// Runs till the start of the next frame. Returns `false` if the program did not start one in time.
+    pub fn run_frame(&mut self) -> bool {
+        let frame = self.frame_counter();
+        for _ in 0..self.max_frame_color_clocks() {
+            self.tick(1);
+            if self.frame_counter() != frame {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// RAM checksum as of now and framebuffer checksum of the last complete frame.
+    pub fn frame_checksum(&self) -> FrameChecksum {
+        let ram = (riot::RAM_MIN_ADDRESS..=riot::RAM_MAX_ADDRESS)
+            .map(|addr| self.mem.get((addr as u16).into(), 0))
+            .fold(cmn::FNV1A_INIT, |h, x| cmn::fnv1a(h, &[x]));
+
+        FrameChecksum {
+            ram,
+            video: self.tia.borrow().frame_checksum(),
+        }
+    }
+
+    /// Plugs a fresh device of `kind` into `port`.
+    pub fn plug(&mut self, port: Port, kind: ControllerKind) {
+        self.ports.borrow_mut().plug(port, kind);
+    }
+
+    /// Plugs `device` into `port`, e.g. a [`crate::ctrl::SaveKey`] with host storage.
+    pub fn plug_device(&mut self, port: Port, device: Box<dyn ControllerPort>) {
+        self.ports.borrow_mut().plug_device(port, device);
+    }
+
+    /// Audio generated by devices like the AtariVox since the last call, at
+    /// [`Atari::audio_sample_rate`]. Empty while [`Atari::enable_audio`] mixes it in.
+    pub fn drain_device_audio(&mut self) -> Vec<f32> {
+        self.ports.borrow_mut().drain_audio()
+    }
+
+    /// AUDV0 & AUDV1 levels output by the TIA since the last call, at [`Atari::audio_sample_rate`].
+    /// See [`tia::TIA::drain_audio`]. Empty while [`Atari::enable_audio`] mixes them in.
+    pub fn drain_tia_audio(&mut self) -> Vec<[u8; 2]> {
+        self.tia.borrow_mut().drain_audio()
+    }
+
+    /// Input the host last set for the device in `port`, see [`ControllerPort::input`].
+    pub fn input(&self, port: Port) -> Option<ControllerInput> {
+        self.ports.borrow().get(port).input()
+    }
+
+    /// Input for the controller plugged into `port`. A device of the matching kind is plugged in first if needed.
+    pub fn set_input(&mut self, port: Port, input: ControllerInput) {
+        self.ports.borrow_mut().set_input(port, input);
+    }
+
+    pub fn switches(&self) -> ConsoleSwitches {
+        self.switches.get()
+    }
+
+    /// Flips the console switches. The TV follows the TV type switch.
+    pub fn set_switches(&mut self, switches: ConsoleSwitches) {
+        self.switches.set(switches);
+        self.tv.borrow_mut().set_black_and_white(!switches.color);
+    }
+
+    pub fn set_reset(&mut self, pressed: bool) {
+        self.set_switches(ConsoleSwitches {
+            reset: pressed,
+            ..self.switches()
+        });
+    }
+
+    pub fn set_select(&mut self, pressed: bool) {
+        self.set_switches(ConsoleSwitches {
+            select: pressed,
+            ..self.switches()
+        });
+    }
+
+    pub fn set_color(&mut self, color: bool) {
+        self.set_switches(ConsoleSwitches {
+            color,
+            ..self.switches()
+        });
+    }
+
+    /// Difficulty switch of the player using `port`.
+    pub fn set_difficulty(&mut self, port: Port, difficulty: Difficulty) {
+        let mut switches = self.switches();
+        match port {
+            Port::Left => switches.left_difficulty = difficulty,
+            Port::Right => switches.right_difficulty = difficulty,
+        }
+        self.set_switches(switches);
+    }
+
     pub fn cpu_state(&self) -> cpu::NMOS6502 {
         self.cpu.borrow().clone()
     }
+
+    pub fn frame_counter(&self) -> u64 {
+        self.tv.borrow().frame_counter()
+    }
+
+    /// Start producing audio at the host rate, see [`Atari::drain_audio_frames`]. Audio
+    /// generated before is dropped.
+    pub fn enable_audio(&mut self, cfg: AudioConfig) {
+        self.tia.borrow_mut().drain_audio();
+        self.ports.borrow_mut().drain_audio();
+        self.audio = Some(AudioStream::new_with_cpu_hz(
+            cfg,
+            self.cpu_hz(),
+            self.frame_counter(),
+        ));
+    }
+
+    pub fn disable_audio(&mut self) {
+        self.audio = None;
+    }
+
+    pub fn audio_config(&self) -> Option<&AudioConfig> {
+        self.audio.as_ref().map(|x| x.config())
+    }
+
+    /// Audio of each frame completed since the last call, oldest first. The samples of a frame
+    /// are complete once [`Atari::frame_counter`] has moved past it.
+    pub fn drain_audio_frames(&mut self) -> Vec<AudioFrame> {
+        self.audio
+            .as_mut()
+            .map(|x| x.drain_frames())
+            .unwrap_or_default()
+    }
+
+    /// Moves TIA & device audio into the stream, closing its frame as soon as the TV starts the
+    /// next one.
+    fn pump_audio(&mut self) {
+        let Some(audio) = self.audio.as_mut() else {
+            return;
+        };
+
+        let frame = self.tv.borrow().frame_counter();
+        let tia = self.tia.borrow_mut().drain_audio();
+        if tia.is_empty() && frame == audio.frame() {
+            return;
+        }
+
+        audio.push(&tia, &self.ports.borrow_mut().drain_audio());
+        if frame != audio.frame() {
+            audio.end_frame(frame);
+        }
+    }
+
+    /// Start watching the frame timing, switching the TV to the standard it matches once decided.
+    /// See [`crate::tv_detect`].
+    pub fn enable_tv_detection(&mut self) {
+        let tv = self.tv.borrow();
+        self.tv_detector = Some(TVDetector::new(
+            tv.frame_counter(),
+            tv.config().pixels_per_scanline(),
+        ));
+    }
+
+    /// Detection progress & outcome, if it was enabled and not overridden since.
+    pub fn tv_detector(&self) -> Option<&TVDetector> {
+        self.tv_detector.as_ref()
+    }
+
+    /// Standard the TV is set up for, `None` for a custom [`tia::TVConfig`].
+    pub fn tv_standard(&self) -> Option<TVStandard> {
+        TVStandard::of(self.tv.borrow().config())
+    }
+
+    /// Switch the TV to `standard`, e.g. for a program that was detected wrongly. Stops any detection.
+    pub fn set_tv_standard(&mut self, standard: TVStandard) {
+        self.tv_detector = None;
+        self.tv.borrow_mut().set_config(standard.tv_config());
+        self.sync_cpu_hz();
+    }
+
+    /// CPU clock of the console made for the TV standard, NTSC for a custom [`tia::TVConfig`].
+    pub fn cpu_hz(&self) -> u32 {
+        self.tv_standard()
+            .map_or(tia::NTSC_CPU_HZ, TVStandard::cpu_hz)
+    }
+
+    /// In Hz, of the audio from [`Atari::drain_device_audio`] & [`Atari::drain_tia_audio`]. It
+    /// follows the CPU clock.
+    pub fn audio_sample_rate(&self) -> u32 {
+        self.cpu_hz() / tia::CPU_CYCLES_PER_AUDIO_SAMPLE
+    }
+
+    /// Hands the CPU clock to what counts time in CPU cycles: the devices & the audio stream.
+    fn sync_cpu_hz(&mut self) {
+        let cpu_hz = self.cpu_hz();
+        self.ports.borrow_mut().set_cpu_hz(cpu_hz);
+        if let Some(audio) = self.audio.as_mut() {
+            audio.set_cpu_hz(cpu_hz);
+        }
+    }
+
+    fn detect_tv_standard(&mut self) {
+        let Some(detector) = self.tv_detector.as_mut() else {
+            return;
+        };
+
+        let frame = self.tv.borrow().frame_counter();
+        if let Some(standard) = detector.tick(frame) {
+            self.tv.borrow_mut().set_config(standard.tv_config());
+            self.sync_cpu_hz();
+        }
+    }
+
+    /// Start capturing snapshots for [`Atari::rewind`]. Replaces any earlier rewind history.
+    pub fn enable_rewind(&mut self, cfg: RewindConfig) {
+        self.rewind = Some(RewindBuffer::new(cfg));
+        self.capture_rewind_snapshot();
+    }
+
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+    }
+
+    pub fn rewind_buffer(&mut self) -> Option<&mut RewindBuffer> {
+        self.rewind.as_mut()
+    }
+
+    /// Go back to the latest snapshot taken at least `frames` frames ago, or the oldest one there is.
+    /// Returns the frame that was restored, `None` if rewind is not enabled. Snapshots newer than
+    /// that are discarded.
+    pub fn rewind(&mut self, frames: u64) -> Result<Option<u64>, StateError> {
+        let target = self.frame_counter().saturating_sub(frames);
+        let Some(rewind) = self.rewind.as_mut() else {
+            return Ok(None);
+        };
+        let Some((frame, state)) = rewind.rewind_to(target)? else {
+            return Ok(None);
+        };
+        self.load_state(&state)?;
+
+        Ok(Some(frame))
+    }
+
+    fn capture_rewind_snapshot(&mut self) {
+        let frame = self.frame_counter();
+        if self.rewind.as_ref().is_some_and(|r| r.is_due(frame)) {
+            let state = self.save_state();
+            if let Some(r) = self.rewind.as_mut() {
+                r.capture(frame, state);
+            }
+        }
+    }
+
+    /// Versioned snapshot of the whole machine. See [`crate::state`] for the layout.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut w = StateWriter::new();
+        w.section(b"CPU ", |w| self.cpu.borrow().save_state(w));
+        w.section(b"MEM ", |w| self.mem.save_state(w));
+        w.section(b"CTRL", |w| self.ports.borrow().save_state(w));
+        w.section(b"RIOT", |w| self.pia.borrow().save_state(w));
+        w.section(b"TIA ", |w| self.tia.borrow().save_state(w));
+        w.section(b"TV  ", |w| self.tv.borrow().save_state(w));
+        w.section(b"CLK ", |w| w.put_u8(self.clock_phase));
+        w.section(b"SWCH", |w| self.switches().save_state(w));
+
+        w.into_bytes()
+    }
+
+    /// Restores a snapshot taken by [`Atari::save_state`], possibly by an older version.
+    ///
+    /// NOTE: The machine is left in an undefined state if an error is returned.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let mut r = StateReader::new(data)?;
+        r.section(b"CPU ", |r| self.cpu.borrow_mut().load_state(r))?;
+        r.section(b"MEM ", |r| self.mem.load_state(r))?;
+        // NOTE: Ahead of the RIOT, which drives the restored devices to the levels they saw.
+        if r.version() >= 15 {
+            r.section(b"CTRL", |r| self.ports.borrow_mut().load_state(r))?;
+        }
+        r.section(b"RIOT", |r| self.pia.borrow_mut().load_state(r))?;
+        r.section(b"TIA ", |r| self.tia.borrow_mut().load_state(r))?;
+        r.section(b"TV  ", |r| self.tv.borrow_mut().load_state(r))?;
+        self.clock_phase = 0;
+        if r.version() >= 6 {
+            r.section(b"CLK ", |r| {
+                self.clock_phase = r.get_u8()?;
+                if self.clock_phase >= CLOCKS_PER_CPU_CYCLE {
+                    return Err(r.corrupt());
+                }
+                Ok(())
+            })?;
+        }
+        // NOTE: Older blobs keep the current switches.
+        if r.version() >= 15 {
+            let mut switches = self.switches();
+            r.section(b"SWCH", |r| {
+                switches = ConsoleSwitches::load_state(r)?;
+                Ok(())
+            })?;
+            self.set_switches(switches);
+        }
+        if !r.is_at_end() {
+            return Err(StateError::Corrupt(*b"HEAD"));
+        }
+        if let Some(audio) = self.audio.as_mut() {
+            self.tia.borrow_mut().drain_audio();
+            self.ports.borrow_mut().drain_audio();
+            audio.restart(self.tv.borrow().frame_counter());
+        }
+        self.sync_cpu_hz();
+        if let Some(detector) = self.tv_detector.as_mut() {
+            detector.restart(self.tv.borrow().frame_counter());
+        }
+
+        Ok(())
+    }
 }
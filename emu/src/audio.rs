@@ -0,0 +1,439 @@
+//! Host audio: TIA channel levels & device audio, mixed and resampled to the host rate.
+//!
+//! ```text
+//! TIA levels ─ mixing table ─┐
+//!                            + ─ DC blocker ─ resampler ─ frames
+//! device audio ──────────────┘
+//! ```
+//!
+//! Everything up to the resampler runs at the native rate, two samples per scanline, which
+//! follows the CPU clock of the console. See [`crate::tia::TVStandard::audio_sample_rate`].
+//! Samples are grouped by the frame they were generated in.
+
+use crate::tia::{AUDIO_SAMPLE_RATE, CPU_CYCLES_PER_AUDIO_SAMPLE, NTSC_CPU_HZ};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::f32::consts::PI;
+use num_traits::Float;
+
+/// Highest AUDV0 + AUDV1.
+const MAX_LEVEL: usize = 30;
+/// Mixed level at which the output stage is half way to saturation. Lower compresses more.
+const MIX_KNEE: f32 = 30.0;
+/// Pole of the DC blocker, a ~25 Hz cutoff at the native rate.
+const DC_BLOCKER_POLE: f32 = 0.995;
+/// Native samples either side of the one being interpolated.
+const HALF_TAPS: usize = 8;
+const TAPS: usize = 2 * HALF_TAPS;
+/// Fractional positions between two native samples the filter is tabulated for.
+const PHASES: usize = 64;
+/// Completed frames held for the host, the oldest are dropped first.
+const MAX_QUEUED_FRAMES: usize = 60;
+/// Device audio not yet mixed is capped at a second, the oldest samples are dropped first.
+const MAX_QUEUED_DEVICE_SAMPLES: usize = AUDIO_SAMPLE_RATE as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioConfig {
+    /// Host sample rate in Hz, e.g. 44100 or 48000.
+    pub sample_rate: u32,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48_000,
+        }
+    }
+}
+
+/// Audio generated while [`crate::Atari::frame_counter`] was `frame`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFrame {
+    pub frame: u64,
+    /// Mono, at [`AudioConfig::sample_rate`], ranging from -1.0 to 1.0.
+    pub samples: Vec<f32>,
+}
+
+/// Output level for AUDV0 + AUDV1.
+///
+/// NOTE: Both channels drive the same output through a resistor network, so each step in level
+/// adds less the louder the mix already is. Modelled as a saturating curve through 0 and 1.
+fn mixing_table() -> [f32; MAX_LEVEL + 1] {
+    let max = MAX_LEVEL as f32;
+    core::array::from_fn(|x| {
+        let x = x as f32;
+        x * (max + MIX_KNEE) / (max * (x + MIX_KNEE))
+    })
+}
+
+/// First order high-pass filter removing the DC offset of the always positive TIA output.
+#[derive(Debug, Default, Clone, Copy)]
+struct DcBlocker {
+    x1: f32,
+    y1: f32,
+}
+
+impl DcBlocker {
+    fn filter(&mut self, x: f32) -> f32 {
+        self.y1 = x - self.x1 + DC_BLOCKER_POLE * self.y1;
+        self.x1 = x;
+        self.y1
+    }
+}
+
+/// Windowed sinc resampler from the native rate to the host rate.
+///
+/// Time is kept in integer units of 1 / (CPU clock * host rate) seconds, in which both sample
+/// periods are exact. Output sample counts hence never drift from the emulated time.
+///
+/// Refer: https://ccrma.stanford.edu/~jos/resample/
+#[derive(Debug, Clone)]
+struct Resampler {
+    /// Filter for each phase, over the [`TAPS`] native samples around it.
+    kernels: Vec<[f32; TAPS]>,
+    /// Oldest first. Output is interpolated between `HALF_TAPS - 1` and `HALF_TAPS`.
+    history: [f32; TAPS],
+    /// Native sample period.
+    in_period: u64,
+    /// Host sample period.
+    out_period: u64,
+    /// Time of the next output past `history[HALF_TAPS - 1]`.
+    position: u64,
+}
+
+impl Resampler {
+    fn new(sample_rate: u32, cpu_hz: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let native_rate = cpu_hz as f32 / CPU_CYCLES_PER_AUDIO_SAMPLE as f32;
+        // NOTE: Cut off below half of the lower of the two rates, so nothing aliases either way.
+        let cutoff = (sample_rate as f32 / native_rate).min(1.0);
+        let kernels = (0..PHASES)
+            .map(|phase| {
+                let mut kernel: [f32; TAPS] = core::array::from_fn(|tap| {
+                    let t = tap as f32 - (HALF_TAPS - 1) as f32 - phase as f32 / PHASES as f32;
+                    cutoff * sinc(cutoff * t) * blackman(t / HALF_TAPS as f32)
+                });
+                // NOTE: Unity gain at DC for every phase, else a steady level would whine.
+                let sum: f32 = kernel.iter().sum();
+                kernel.iter_mut().for_each(|x| *x /= sum);
+                kernel
+            })
+            .collect();
+
+        Self {
+            kernels,
+            history: [0.0; TAPS],
+            in_period: CPU_CYCLES_PER_AUDIO_SAMPLE as u64 * sample_rate as u64,
+            out_period: cpu_hz as u64,
+            position: 0,
+        }
+    }
+
+    /// Takes one native sample, appending the host samples that fall before the next one.
+    fn push(&mut self, x: f32, out: &mut Vec<f32>) {
+        self.history.copy_within(1.., 0);
+        self.history[TAPS - 1] = x;
+
+        while self.position < self.in_period {
+            let phase = (self.position * PHASES as u64 / self.in_period) as usize;
+            let y = self.kernels[phase]
+                .iter()
+                .zip(self.history.iter())
+                .map(|(k, x)| k * x)
+                .sum::<f32>();
+            out.push(y.clamp(-1.0, 1.0));
+            self.position += self.out_period;
+        }
+        self.position -= self.in_period;
+    }
+}
+
+#[inline]
+fn sinc(x: f32) -> f32 {
+    if x == 0.0 {
+        1.0
+    } else {
+        Float::sin(PI * x) / (PI * x)
+    }
+}
+
+/// For `x` in -1.0..=1.0, 0.0 outside.
+#[inline]
+fn blackman(x: f32) -> f32 {
+    if Float::abs(x) > 1.0 {
+        return 0.0;
+    }
+
+    let x = PI * (x + 1.0);
+    0.42 - 0.5 * Float::cos(x) + 0.08 * Float::cos(2.0 * x)
+}
+
+/// Native samples in, frames of host samples out.
+#[derive(Debug, Clone)]
+pub struct AudioStream {
+    cfg: AudioConfig,
+    cpu_hz: u32,
+    mix: [f32; MAX_LEVEL + 1],
+    dc_blocker: DcBlocker,
+    resampler: Resampler,
+    /// Device audio waiting for the TIA samples it plays alongside.
+    device: VecDeque<f32>,
+    /// Frame whose samples are being collected.
+    frame: u64,
+    samples: Vec<f32>,
+    /// Oldest first.
+    frames: VecDeque<AudioFrame>,
+}
+
+impl AudioStream {
+    /// Starts collecting samples of a NTSC console for `frame`.
+    pub fn new(cfg: AudioConfig, frame: u64) -> Self {
+        Self::new_with_cpu_hz(cfg, NTSC_CPU_HZ, frame)
+    }
+
+    /// Starts collecting samples for `frame`, the console running at `cpu_hz`.
+    pub fn new_with_cpu_hz(cfg: AudioConfig, cpu_hz: u32, frame: u64) -> Self {
+        Self {
+            cfg,
+            cpu_hz,
+            mix: mixing_table(),
+            dc_blocker: DcBlocker::default(),
+            resampler: Resampler::new(cfg.sample_rate, cpu_hz),
+            device: VecDeque::new(),
+            frame,
+            samples: Vec::new(),
+            frames: VecDeque::new(),
+        }
+    }
+
+    #[inline]
+    pub fn config(&self) -> &AudioConfig {
+        &self.cfg
+    }
+
+    /// Frame whose samples are being collected.
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    #[inline]
+    pub fn cpu_hz(&self) -> u32 {
+        self.cpu_hz
+    }
+
+    /// Follows the console to another CPU clock, e.g. once the TV standard is detected.
+    pub fn set_cpu_hz(&mut self, cpu_hz: u32) {
+        if cpu_hz == self.cpu_hz {
+            return;
+        }
+
+        // NOTE: The history is kept, so the switch does not click.
+        let history = self.resampler.history;
+        self.resampler = Resampler::new(self.cfg.sample_rate, cpu_hz);
+        self.resampler.history = history;
+        self.cpu_hz = cpu_hz;
+    }
+
+    /// Mixes in TIA channel levels & device audio, both at the native rate.
+    ///
+    /// NOTE: Devices count samples in CPU cycles, the TIA in color clocks. Device samples are
+    /// held till there is a TIA sample for them, so the two never drift apart.
+    pub fn push(&mut self, tia: &[[u8; 2]], device: &[f32]) {
+        device.iter().for_each(|&x| {
+            if self.device.len() == MAX_QUEUED_DEVICE_SAMPLES {
+                self.device.pop_front();
+            }
+            self.device.push_back(x);
+        });
+
+        for &[a, b] in tia {
+            let level = (a + b) as usize;
+            let x = self.mix[level.min(MAX_LEVEL)] + self.device.pop_front().unwrap_or(0.0);
+            let x = self.dc_blocker.filter(x);
+            self.resampler.push(x, &mut self.samples);
+        }
+    }
+
+    /// Closes the frame being collected, the next one being `next`.
+    pub fn end_frame(&mut self, next: u64) {
+        if self.frames.len() == MAX_QUEUED_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(AudioFrame {
+            frame: self.frame,
+            samples: core::mem::take(&mut self.samples),
+        });
+        self.frame = next;
+    }
+
+    /// Drops the samples of the frame being collected and continues from `frame`, e.g. after the
+    /// machine jumped in time.
+    pub fn restart(&mut self, frame: u64) {
+        self.samples.clear();
+        self.device.clear();
+        self.frame = frame;
+    }
+
+    /// Frames completed since the last call, oldest first.
+    pub fn drain_frames(&mut self) -> Vec<AudioFrame> {
+        self.frames.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tia::PAL_CPU_HZ;
+    use alloc::vec;
+    use test_case::test_case;
+
+    /// Native samples in a NTSC frame, 262 scanlines of 2.
+    const FRAME: usize = 524;
+
+    fn rms(samples: &[f32]) -> f32 {
+        Float::sqrt(samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32)
+    }
+
+    #[test]
+    fn mixing_is_monotonic_and_compressed() {
+        let mix = mixing_table();
+
+        assert_eq!(mix[0], 0.0);
+        assert!((mix[MAX_LEVEL] - 1.0).abs() < 1e-6);
+        assert!(mix.windows(2).all(|x| x[1] > x[0]));
+        assert!(mix[MAX_LEVEL / 2] > 0.5);
+    }
+
+    #[test]
+    fn dc_blocker_settles_to_zero() {
+        let mut dc = DcBlocker::default();
+        let out: Vec<f32> = (0..5000).map(|_| dc.filter(0.8)).collect();
+
+        assert!((out[0] - 0.8).abs() < 1e-6);
+        assert!(out[4999].abs() < 1e-3);
+    }
+
+    #[test_case(44_100, NTSC_CPU_HZ)]
+    #[test_case(48_000, NTSC_CPU_HZ)]
+    #[test_case(22_050, NTSC_CPU_HZ)]
+    #[test_case(44_100, PAL_CPU_HZ; "PAL")]
+    #[test_case(48_000, PAL_CPU_HZ; "PAL 48k")]
+    fn resampled_counts_never_drift(rate: u32, cpu_hz: u32) {
+        let mut resampler = Resampler::new(rate, cpu_hz);
+        let mut out = Vec::new();
+        let seconds = 3;
+        (0..seconds * cpu_hz as usize / CPU_CYCLES_PER_AUDIO_SAMPLE as usize)
+            .for_each(|_| resampler.push(0.0, &mut out));
+
+        assert!(out.len().abs_diff(seconds * rate as usize) <= 1);
+    }
+
+    #[test]
+    fn resampler_keeps_steady_level() {
+        let mut resampler = Resampler::new(44_100, NTSC_CPU_HZ);
+        let mut out = Vec::new();
+        (0..100).for_each(|_| resampler.push(0.5, &mut out));
+
+        assert!(out[out.len() - 50..].iter().all(|x| (x - 0.5).abs() < 1e-4));
+    }
+
+    /// Alternating samples are at the native Nyquist rate, which a 22.05 kHz host can not play.
+    #[test_case(48_000, 1.0 / 8.0, true; "Audible")]
+    #[test_case(22_050, 1.0 / 8.0, true; "Audible on slow host")]
+    #[test_case(22_050, 1.0 / 2.0, false; "Above slow host Nyquist")]
+    fn resampler_band_limits(rate: u32, cycles_per_sample: f32, passed: bool) {
+        let mut resampler = Resampler::new(rate, NTSC_CPU_HZ);
+        let mut out = Vec::new();
+        (0..2000).for_each(|x| {
+            let x = Float::cos(2.0 * PI * cycles_per_sample * x as f32);
+            resampler.push(x, &mut out);
+        });
+        let level = rms(&out[out.len() / 2..]);
+
+        assert_eq!(level > 0.5, passed, "{level}");
+    }
+
+    #[test]
+    fn frames_hold_their_samples() {
+        let mut stream = AudioStream::new(AudioConfig::default(), 7);
+
+        stream.push(&[[0x0F, 0x00]; FRAME], &[]);
+        stream.end_frame(8);
+        stream.push(&[[0x0F, 0x00]; FRAME], &[]);
+        stream.end_frame(9);
+        let frames = stream.drain_frames();
+
+        assert_eq!(frames.iter().map(|x| x.frame).collect::<Vec<_>>(), [7, 8]);
+        let total: usize = frames.iter().map(|x| x.samples.len()).sum();
+        assert!(total.abs_diff(2 * FRAME * 48_000 / AUDIO_SAMPLE_RATE as usize) <= 1);
+        assert_eq!(stream.frame(), 9);
+        assert!(stream.drain_frames().is_empty());
+    }
+
+    #[test]
+    fn frames_follow_cpu_clock() {
+        let mut stream = AudioStream::new(AudioConfig::default(), 0);
+        stream.push(&[[0x0F, 0x00]; FRAME], &[]);
+        stream.end_frame(1);
+
+        stream.set_cpu_hz(PAL_CPU_HZ);
+        (1..=50).for_each(|x| {
+            stream.push(&[[0x0F, 0x00]; 624], &[]);
+            stream.end_frame(x + 1);
+        });
+        let frames = stream.drain_frames();
+
+        assert_eq!(stream.cpu_hz(), PAL_CPU_HZ);
+        let total: usize = frames[1..].iter().map(|x| x.samples.len()).sum();
+        let native_rate = PAL_CPU_HZ as usize / CPU_CYCLES_PER_AUDIO_SAMPLE as usize;
+        assert!(total.abs_diff(50 * 624 * 48_000 / native_rate) <= 1);
+    }
+
+    #[test]
+    fn silence_is_silent() {
+        let mut stream = AudioStream::new(AudioConfig::default(), 0);
+
+        stream.push(&[[0x00, 0x00]; FRAME], &[]);
+        stream.end_frame(1);
+
+        assert!(stream.drain_frames()[0].samples.iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn device_audio_waits_for_tia_samples() {
+        let mut stream = AudioStream::new(AudioConfig::default(), 0);
+
+        stream.push(&[], &vec![0.5; FRAME]);
+        stream.end_frame(1);
+        stream.push(&[[0x00, 0x00]; FRAME], &[]);
+        stream.end_frame(2);
+        let frames = stream.drain_frames();
+
+        assert!(frames[0].samples.is_empty());
+        assert!(rms(&frames[1].samples) > 0.1);
+    }
+
+    #[test]
+    fn queued_frames_are_capped() {
+        let mut stream = AudioStream::new(AudioConfig::default(), 0);
+
+        (1..=MAX_QUEUED_FRAMES as u64 + 5).for_each(|x| stream.end_frame(x));
+        let frames = stream.drain_frames();
+
+        assert_eq!(frames.len(), MAX_QUEUED_FRAMES);
+        assert_eq!(frames[0].frame, 5);
+    }
+
+    #[test]
+    fn restart_drops_partial_frame() {
+        let mut stream = AudioStream::new(AudioConfig::default(), 10);
+
+        stream.push(&[[0x0F, 0x0F]; FRAME], &[0.5; 4]);
+        stream.restart(3);
+        stream.end_frame(4);
+        let frames = stream.drain_frames();
+
+        assert_eq!(frames[0].frame, 3);
+        assert!(frames[0].samples.is_empty());
+    }
+}
@@ -78,6 +78,18 @@ pub trait RefExtensions {
 
 impl<T> RefExtensions for T {}
 
+pub const FNV1A_INIT: u32 = 0x811C_9DC5;
+
+/// 32 bit FNV-1a, continuing from `hash`. Start with [`FNV1A_INIT`].
+///
+/// Refer: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+#[inline]
+pub fn fnv1a(hash: u32, bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(hash, |h, &b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +116,13 @@ mod tests {
         let val = lohi + delta;
         assert_eq!(val, exp);
     }
+
+    #[test_case(b"", 0x811C_9DC5)]
+    #[test_case(b"a", 0xE40C_292C)]
+    #[test_case(b"foobar", 0xBF9C_F968)]
+    fn fnv1a_reference_values(data: &[u8], exp: u32) {
+        assert_eq!(fnv1a(FNV1A_INIT, data), exp);
+        let (a, b) = data.split_at(data.len() / 2);
+        assert_eq!(fnv1a(fnv1a(FNV1A_INIT, a), b), exp);
+    }
 }
@@ -343,7 +343,6 @@ pub mod immediate {
 
     #[cfg(test)]
     mod tests {
-        use super::*;
         use crate::{
             cmn::{LineState, RefExtensions},
             cpu::am::opc_step_illegal,
@@ -540,7 +539,6 @@ pub mod absolute {
 
     #[cfg(test)]
     mod tests {
-        use super::*;
         use crate::{
             cmn::{LineState, LoHi, RefExtensions},
             cpu::am::opc_step_illegal,
@@ -717,7 +715,6 @@ pub mod zero_page {
 
     #[cfg(test)]
     mod tests {
-        use super::*;
         use crate::{
             cmn::{LineState, LoHi, RefExtensions},
             cpu::am::opc_step_illegal,
@@ -978,7 +975,6 @@ pub mod indexed_absolute {
 
     #[cfg(test)]
     mod tests {
-        use super::*;
         use crate::{
             cmn::{LineState, LoHi, RefExtensions},
             cpu::am::opc_step_illegal,
@@ -1202,7 +1198,6 @@ pub mod indexed_zero_page {
 
     #[cfg(test)]
     mod tests {
-        use super::*;
         use crate::{
             cmn::{LineState, LoHi, RefExtensions},
             cpu::am::opc_step_illegal,
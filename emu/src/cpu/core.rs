@@ -2,7 +2,9 @@ use crate::{bits, timer};
 use crate::{
     cmn::*,
     cpu::{cmn, opc_impl::*},
+    power_on::PowerOnFill,
     riot::Memory,
+    state::{SaveState, StateError, StateReader, StateWriter},
 };
 use bitflags::bitflags;
 
@@ -104,6 +106,16 @@ impl NMOS6502 {
         cpu
     }
 
+    /// Registers hold garbage at power-on. The reset sequence sets I, B & bit 5 have no storage.
+    pub fn power_on(&mut self, fill: PowerOnFill) {
+        let [a, x, y, s, p] = fill.bytes();
+        self.A = a;
+        self.X = x;
+        self.Y = y;
+        self.S = s;
+        self.P = (PSR::from_bits_truncate(p) - PSR::B - PSR::__) | PSR::I;
+    }
+
     pub fn reset_pc(&mut self, mem: &Memory) {
         let pc_lo = mem.get(cmn::RST_VECTOR, 0);
         let pc_hi = mem.get(cmn::RST_VECTOR, 1);
@@ -253,6 +265,35 @@ impl NMOS6502 {
     }
 }
 
+impl SaveState for NMOS6502 {
+    /// NOTE: Profiling duration is host dependent and is not saved.
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_u8(self.A);
+        w.put_u8(self.X);
+        w.put_u8(self.Y);
+        w.put_u16(self.PC.into());
+        w.put_u8(self.S);
+        w.put_u8(self.P.bits());
+        self.execution_state.save_state(w);
+        w.put_u64(self.instructions);
+        w.put_u64(self.cycles as u64);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.A = r.get_u8()?;
+        self.X = r.get_u8()?;
+        self.Y = r.get_u8()?;
+        self.PC = r.get_u16()?.into();
+        self.S = r.get_u8()?;
+        self.P = PSR::from_bits_truncate(r.get_u8()?);
+        self.execution_state.load_state(r)?;
+        self.instructions = r.get_u64()?;
+        self.cycles = r.get_u64()? as usize;
+
+        Ok(())
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct OpcExecutionState {
     opc: usize,
@@ -284,6 +325,33 @@ impl OpcExecutionState {
     }
 }
 
+impl SaveState for OpcExecutionState {
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_u8(self.opc as u8);
+        w.put_bool(self.done);
+        w.put_u8(self.step as u8);
+        self.regs_u8.iter().for_each(|&x| w.put_u8(x));
+        self.regs_u16.iter().for_each(|&x| w.put_u16(x));
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.opc = r.get_u8()? as usize;
+        self.done = r.get_bool()?;
+        self.step = r.get_u8()? as usize;
+        if self.step > MAX_OPCODE_STEPS {
+            return Err(r.corrupt());
+        }
+        for x in self.regs_u8.iter_mut() {
+            *x = r.get_u8()?;
+        }
+        for x in self.regs_u16.iter_mut() {
+            *x = r.get_u16()?;
+        }
+
+        Ok(())
+    }
+}
+
 #[inline]
 fn set_bit(bits: &mut PSR, bit: PSR) {
     *bits |= bit;
@@ -298,6 +366,7 @@ fn clr_bit(bits: &mut PSR, bit: PSR) {
 mod tests {
     use super::*;
     use crate::bits;
+    use test_case::test_case;
 
     #[test]
     fn test_tst_bit() {
@@ -321,4 +390,45 @@ mod tests {
 
         assert!(!bits::tst_bits(bits.bits(), PSR::B.bits()));
     }
+
+    #[test_case(PowerOnFill::Zero, [0x00, 0x00, 0x00, 0x00], 0b0000_0100)]
+    #[test_case(PowerOnFill::Pattern(0x0102_03FF_F3FF_FFFF), [0x01, 0x02, 0x03, 0xFF], 0b1100_0111)]
+    fn power_on_fills_registers(fill: PowerOnFill, axys: [u8; 4], p: u8) {
+        let mem = Memory::new(false);
+        let mut cpu = NMOS6502::new(LineState::High.rc_cell(), &mem);
+        cpu.power_on(fill);
+
+        assert_eq!([cpu.a(), cpu.x(), cpu.y(), cpu.s()], axys);
+        assert_eq!(cpu.psr(), p);
+    }
+
+    #[test]
+    fn save_load_state_mid_instruction() {
+        let mut mem = Memory::new(false);
+        // LDA $1234; at 0x0400
+        mem.load(&[0xAD, 0x34, 0x12], LoHi(0x00, 0x04));
+        mem.set(LoHi(0x34, 0x12), 0, 0x5A);
+        let rdy = LineState::High.rc_cell();
+        let mut cpu = NMOS6502::new(rdy.clone(), &mem);
+        cpu.set_pc(LoHi(0x00, 0x04));
+        cpu.tick(&mut mem);
+        cpu.tick(&mut mem);
+
+        let mut w = StateWriter::new();
+        cpu.save_state(&mut w);
+        let blob = w.into_bytes();
+
+        let mut cpu2 = NMOS6502::new(rdy, &mem);
+        let mut r = StateReader::new(&blob).unwrap();
+        cpu2.load_state(&mut r).unwrap();
+        assert!(r.is_at_end());
+        assert_eq!(cpu2.execution_state().step(), 2);
+
+        while cpu2.instructions() == 0 {
+            cpu2.tick(&mut mem);
+        }
+        assert_eq!(cpu2.a(), 0x5A);
+        assert_eq!(cpu2.cycles(), 4);
+        assert_eq!(cpu2.pc(), LoHi(0x03, 0x04));
+    }
 }
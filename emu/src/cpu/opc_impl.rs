@@ -407,7 +407,7 @@ pub mod arithmetic {
 
         psr_utils::sync_pcr_n(cpu, res_u8);
         let bit8u8 = 0b1000_0000;
-        let c6 = ((n1 & !bit8u8) + (n2 & !bit8u8)) & bit8u8 == bit8u8;
+        let c6 = (n1 ^ n2 ^ res_u8) & bit8u8 == bit8u8;
         let bit8u16 = 0b0000_0001_0000_0000;
         let c7 = res & bit8u16 == bit8u16;
         if c6 != c7 {
@@ -423,8 +423,36 @@ pub mod arithmetic {
         }
     }
 
-    pub fn ADC_core_bcd(_cpu: &mut NMOS6502, _n2: u8) {
-        todo!("ADC in decimal mode is not yet implemented.")
+    /// NOTE: On the NMOS 6502 Z is set from the binary sum, while N and V are
+    /// set from the sum before the upper nibble is decimal adjusted.
+    pub fn ADC_core_bcd(cpu: &mut NMOS6502, n2: u8) {
+        let n1 = cpu.a();
+        let c = if cpu.tst_psr_bit(PSR::C) { 0x01 } else { 0x00 };
+        ADC_core_bin(cpu, n2);
+
+        let mut lo = (n1 & 0x0F) as i16 + (n2 & 0x0F) as i16 + c;
+        if lo >= 0x0A {
+            lo = ((lo + 0x06) & 0x0F) + 0x10;
+        }
+
+        let signed = (n1 & 0xF0) as i8 as i16 + (n2 & 0xF0) as i8 as i16 + lo;
+        psr_utils::sync_pcr_n(cpu, signed as u8);
+        if !(-128..=127).contains(&signed) {
+            cpu.set_psr_bit(PSR::V)
+        } else {
+            cpu.clr_psr_bit(PSR::V)
+        }
+
+        let mut res = (n1 & 0xF0) as i16 + (n2 & 0xF0) as i16 + lo;
+        if res >= 0xA0 {
+            res += 0x60;
+        }
+        cpu.set_a(res as u8);
+        if res >= 0x100 {
+            cpu.set_psr_bit(PSR::C)
+        } else {
+            cpu.clr_psr_bit(PSR::C)
+        }
     }
 
     /// Refer:
@@ -439,11 +467,24 @@ pub mod arithmetic {
     }
 
     pub fn SBC_core_bin(cpu: &mut NMOS6502, n2: u8) {
-        ADC_core(cpu, !n2);
+        ADC_core_bin(cpu, !n2);
     }
 
-    pub fn SBC_core_bcd(_cpu: &mut NMOS6502, _n2: u8) {
-        todo!("SBC in decimal mode is not yet implemented.")
+    /// NOTE: On the NMOS 6502 all flags are set as for the binary difference.
+    pub fn SBC_core_bcd(cpu: &mut NMOS6502, n2: u8) {
+        let n1 = cpu.a();
+        let c = if cpu.tst_psr_bit(PSR::C) { 0x01 } else { 0x00 };
+        SBC_core_bin(cpu, n2);
+
+        let mut lo = (n1 & 0x0F) as i16 - (n2 & 0x0F) as i16 + c - 1;
+        if lo < 0 {
+            lo = ((lo - 0x06) & 0x0F) - 0x10;
+        }
+        let mut res = (n1 & 0xF0) as i16 - (n2 & 0xF0) as i16 + lo;
+        if res < 0 {
+            res -= 0x60;
+        }
+        cpu.set_a(res as u8);
     }
 
     #[cfg(test)]
@@ -466,6 +507,7 @@ pub mod arithmetic {
         #[test_case(false, 0x64, 0xE8, 0x4C, false, false, false, true)]
         #[test_case(false, 0x40, 0x80, 0xC0, true, false, false, false)]
         #[test_case(true, 0xD0, 0x8F, 0x60, false, true, false, true)]
+        #[test_case(true, 0x70, 0x0F, 0x80, true, true, false, false)]
         // Test cases from https://www.righto.com/2012/12/the-6502-overflow-flag-explained.html
         #[test_case(false, 0x50, 0x10, 0x60, false, false, false, false)]
         #[test_case(false, 0x50, 0x50, 0xA0, true, true, false, false)]
@@ -505,7 +547,8 @@ pub mod arithmetic {
         }
 
         ///           C     n1    n2    res    N      V      Z      C
-        #[test_case(true, 0x00, 0x00, 0x00, false, true, true, true)]
+        #[test_case(true, 0x00, 0x00, 0x00, false, false, true, true)]
+        #[test_case(false, 0x80, 0x7F, 0x00, false, true, true, true)]
         // Test cases from https://www.righto.com/2012/12/the-6502-overflow-flag-explained.html
         #[test_case(true, 0x50, 0xF0, 0x60, false, false, false, false)]
         #[test_case(true, 0x50, 0xB0, 0xA0, true, true, false, false)]
@@ -543,6 +586,74 @@ pub mod arithmetic {
             assert_eq!(cpu.tst_psr_bit(PSR::Z), exp_z, "Z flag mismatch");
             assert_eq!(cpu.tst_psr_bit(PSR::C), exp_c, "C flag mismatch");
         }
+
+        ///           C     n1    n2    res    N      V      Z      C
+        #[test_case(false, 0x09, 0x01, 0x10, false, false, false, false)]
+        #[test_case(true, 0x58, 0x46, 0x05, true, true, false, true)]
+        #[test_case(false, 0x99, 0x01, 0x00, true, false, false, true)]
+        #[test_case(false, 0x12, 0x34, 0x46, false, false, false, false)]
+        #[allow(clippy::too_many_arguments)]
+        fn test_decimal_adc(
+            carry: bool,
+            v1: u8,
+            v2: u8,
+            exp: u8,
+            exp_n: bool,
+            exp_v: bool,
+            exp_z: bool,
+            exp_c: bool,
+        ) {
+            let mut cpu = NMOS6502::default();
+            cpu.set_psr_bit(PSR::D);
+            if carry {
+                cpu.set_psr_bit(PSR::C)
+            } else {
+                cpu.clr_psr_bit(PSR::C)
+            }
+            cpu.set_a(v1);
+
+            ADC_core(&mut cpu, v2);
+
+            assert_eq!(cpu.a(), exp);
+            assert_eq!(cpu.tst_psr_bit(PSR::N), exp_n, "N flag mismatch");
+            assert_eq!(cpu.tst_psr_bit(PSR::V), exp_v, "V flag mismatch");
+            assert_eq!(cpu.tst_psr_bit(PSR::Z), exp_z, "Z flag mismatch");
+            assert_eq!(cpu.tst_psr_bit(PSR::C), exp_c, "C flag mismatch");
+        }
+
+        ///           C     n1    n2    res    N      V      Z      C
+        #[test_case(true, 0x10, 0x01, 0x09, false, false, false, true)]
+        #[test_case(true, 0x00, 0x01, 0x99, true, false, false, false)]
+        #[test_case(false, 0x46, 0x12, 0x33, false, false, false, true)]
+        #[test_case(true, 0x40, 0x20, 0x20, false, false, false, true)]
+        #[allow(clippy::too_many_arguments)]
+        fn test_decimal_sbc(
+            carry: bool,
+            v1: u8,
+            v2: u8,
+            exp: u8,
+            exp_n: bool,
+            exp_v: bool,
+            exp_z: bool,
+            exp_c: bool,
+        ) {
+            let mut cpu = NMOS6502::default();
+            cpu.set_psr_bit(PSR::D);
+            if carry {
+                cpu.set_psr_bit(PSR::C)
+            } else {
+                cpu.clr_psr_bit(PSR::C)
+            }
+            cpu.set_a(v1);
+
+            SBC_core(&mut cpu, v2);
+
+            assert_eq!(cpu.a(), exp);
+            assert_eq!(cpu.tst_psr_bit(PSR::N), exp_n, "N flag mismatch");
+            assert_eq!(cpu.tst_psr_bit(PSR::V), exp_v, "V flag mismatch");
+            assert_eq!(cpu.tst_psr_bit(PSR::Z), exp_z, "Z flag mismatch");
+            assert_eq!(cpu.tst_psr_bit(PSR::C), exp_c, "C flag mismatch");
+        }
     }
 }
 
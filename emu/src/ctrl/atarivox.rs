@@ -0,0 +1,971 @@
+use super::{
+    cmn::{ControllerInput, ControllerKind, ControllerPort},
+    savekey::{EepromStorage, SaveKey},
+};
+use crate::{
+    bits,
+    state::{SaveState, StateError, StateReader, StateWriter},
+    tia::{AUDIO_SAMPLE_RATE, CPU_CYCLES_PER_AUDIO_SAMPLE, NTSC_CPU_HZ},
+};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::f32::consts::PI;
+use num_traits::Float;
+
+/// SpeakJet serial input, 8N1.
+const BAUD: u32 = 19_200;
+/// Bytes the SpeakJet input buffer holds. Anything beyond is lost.
+const BUFFER_SIZE: usize = 64;
+/// The ready line drops once the buffer is filled this far.
+const BUFFER_HALF_FULL: usize = 32;
+/// Speech queued for the host is capped at a second, the oldest samples are dropped first.
+const MAX_QUEUED_SAMPLES: usize = AUDIO_SAMPLE_RATE as usize;
+
+const DEFAULT_VOLUME: u8 = 96;
+const DEFAULT_SPEED: u8 = 114;
+const DEFAULT_PITCH: u8 = 88;
+
+/// Lengths of pauses P0-P6.
+const PAUSES_MS: [u16; 7] = [0, 100, 200, 700, 30, 60, 90];
+/// Length of each sound effect.
+const SOUND_EFFECT_MS: u16 = 100;
+
+/// UART receiver, sampling the line in the middle of each bit.
+#[derive(Debug)]
+struct SerialReceiver {
+    line: bool,
+    /// CPU cycles since the falling edge of the start bit, while a frame is coming in.
+    clocks: Option<u32>,
+    /// Bits sampled so far, the start bit being bit 0.
+    bit: u32,
+    shift: u8,
+}
+
+impl Default for SerialReceiver {
+    fn default() -> Self {
+        Self {
+            line: true,
+            clocks: None,
+            bit: 0,
+            shift: 0,
+        }
+    }
+}
+
+impl SerialReceiver {
+    fn drive(&mut self, line: bool) {
+        if self.clocks.is_none() && self.line && !line {
+            self.clocks = Some(0);
+            self.bit = 0;
+        }
+        self.line = line;
+    }
+
+    /// Returns the byte once its stop bit is in. Frames without one are dropped.
+    fn tick(&mut self, cpu_hz: u32) -> Option<u8> {
+        let clocks = self.clocks? + 1;
+        self.clocks = Some(clocks);
+        let sample_at = (2 * self.bit as u64 + 1) * cpu_hz as u64;
+        if u64::from(clocks) * 2 * u64::from(BAUD) < sample_at {
+            return None;
+        }
+
+        self.bit += 1;
+        match self.bit - 1 {
+            // NOTE: A start bit that does not last till its middle is a glitch.
+            0 if self.line => self.clocks = None,
+            0 => {}
+            1..=8 => self.shift = (self.shift >> 1) | ((self.line as u8) << 7),
+            _ => {
+                self.clocks = None;
+                return self.line.then_some(self.shift);
+            }
+        }
+
+        None
+    }
+}
+
+impl SaveState for SerialReceiver {
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_bool(self.line);
+        w.put_bool(self.clocks.is_some());
+        w.put_u32(self.clocks.unwrap_or(0));
+        w.put_u32(self.bit);
+        w.put_u8(self.shift);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.line = r.get_bool()?;
+        let receiving = r.get_bool()?;
+        let clocks = r.get_u32()?;
+        self.clocks = receiving.then_some(clocks);
+        self.bit = r.get_u32()?;
+        self.shift = r.get_u8()?;
+        if self.bit > 9 {
+            return Err(r.corrupt());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Vowel,
+    Nasal,
+    /// Noise through the vocal tract, e.g. H.
+    Aspirate,
+    Fricative,
+    VoicedFricative,
+    Plosive,
+    VoicedPlosive,
+}
+
+/// Formants glide from `from` to `to` over the length of the phoneme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Phoneme {
+    ms: u16,
+    kind: Kind,
+    /// F1 & F2 in Hz.
+    from: [u16; 2],
+    to: [u16; 2],
+    /// Centre of the frication noise in Hz.
+    noise: u16,
+}
+
+const fn ph(ms: u16, kind: Kind, from: [u16; 2], to: [u16; 2], noise: u16) -> Phoneme {
+    Phoneme {
+        ms,
+        kind,
+        from,
+        to,
+        noise,
+    }
+}
+
+const fn vowel(ms: u16, from: [u16; 2], to: [u16; 2]) -> Phoneme {
+    ph(ms, Kind::Vowel, from, to, 0)
+}
+
+const fn noise(ms: u16, kind: Kind, noise: u16) -> Phoneme {
+    ph(ms, kind, [250, 1000], [250, 1000], noise)
+}
+
+const IY: [u16; 2] = [270, 2290];
+const IH: [u16; 2] = [390, 1990];
+const EY: [u16; 2] = [480, 2200];
+const EH: [u16; 2] = [530, 1840];
+const AY: [u16; 2] = [660, 1720];
+const AX: [u16; 2] = [500, 1500];
+const UX: [u16; 2] = [520, 1190];
+const OH: [u16; 2] = [570, 840];
+const AW: [u16; 2] = [730, 1090];
+const UH: [u16; 2] = [440, 1020];
+const UW: [u16; 2] = [300, 870];
+const LL: [u16; 2] = [360, 1300];
+const WW: [u16; 2] = [290, 610];
+const RR: [u16; 2] = [420, 1300];
+
+/// Allophones 128-199, formants after Peterson & Barney. Lengths as in the SpeakJet manual.
+#[rustfmt::skip]
+static PHONEMES: [Phoneme; 72] = [
+    vowel(70, IY, IY),                              // 128 IY
+    vowel(70, IH, IH),                              // 129 IH
+    vowel(70, EY, [350, 2250]),                     // 130 EY
+    vowel(70, EH, EH),                              // 131 EH
+    vowel(70, AY, AY),                              // 132 AY
+    vowel(70, AX, AX),                              // 133 AX
+    vowel(70, UX, UX),                              // 134 UX
+    vowel(70, OH, OH),                              // 135 OH
+    vowel(70, AW, AW),                              // 136 AW
+    vowel(70, OH, [450, 900]),                      // 137 OW
+    vowel(70, UH, UH),                              // 138 UH
+    vowel(70, UW, UW),                              // 139 UW
+    ph(70, Kind::Nasal, [250, 1000], [250, 1000], 0), // 140 MM
+    ph(70, Kind::Nasal, [250, 1700], [250, 1700], 0), // 141 NE
+    ph(70, Kind::Nasal, [250, 1400], [250, 1400], 0), // 142 NO
+    ph(70, Kind::Nasal, [250, 2200], [250, 2200], 0), // 143 NGE
+    ph(70, Kind::Nasal, [250, 1500], [250, 1500], 0), // 144 NGO
+    vowel(70, LL, LL),                              // 145 LE
+    vowel(70, LL, [360, 900]),                      // 146 LO
+    vowel(70, WW, WW),                              // 147 WW
+    vowel(70, RR, RR),                              // 148 RR
+    vowel(200, IY, RR),                             // 149 IYRR
+    vowel(200, EY, RR),                             // 150 EYRR
+    vowel(190, AX, RR),                             // 151 AXRR
+    vowel(200, AW, RR),                             // 152 AWRR
+    vowel(185, OH, RR),                             // 153 OWRR
+    vowel(165, EY, IY),                             // 154 EYIY
+    vowel(200, OH, IY),                             // 155 OHIY
+    vowel(225, OH, IY),                             // 156 OWIY
+    vowel(185, OH, IH),                             // 157 OHIH
+    vowel(170, IY, EH),                             // 158 IYEH
+    vowel(140, EH, LL),                             // 159 EHLL
+    vowel(180, IY, UW),                             // 160 IYUW
+    vowel(170, AX, UW),                             // 161 AXUW
+    vowel(170, IH, WW),                             // 162 IHWW
+    vowel(200, AY, WW),                             // 163 AYWW
+    vowel(131, OH, WW),                             // 164 OWWW
+    noise(70, Kind::VoicedPlosive, 2500),           // 165 JH
+    noise(70, Kind::VoicedFricative, 4000),         // 166 VV
+    noise(70, Kind::VoicedFricative, 5000),         // 167 ZZ
+    noise(70, Kind::VoicedFricative, 2500),         // 168 ZH
+    noise(70, Kind::VoicedFricative, 4000),         // 169 DH
+    noise(45, Kind::VoicedPlosive, 1000),           // 170 BE
+    noise(45, Kind::VoicedPlosive, 800),            // 171 BO
+    noise(10, Kind::VoicedPlosive, 1000),           // 172 EB
+    noise(10, Kind::VoicedPlosive, 800),            // 173 EBO
+    noise(45, Kind::VoicedPlosive, 3500),           // 174 DE
+    noise(45, Kind::VoicedPlosive, 3000),           // 175 DO
+    noise(10, Kind::VoicedPlosive, 3500),           // 176 ED
+    noise(10, Kind::VoicedPlosive, 3000),           // 177 EDO
+    noise(55, Kind::VoicedPlosive, 2000),           // 178 GE
+    noise(55, Kind::VoicedPlosive, 1500),           // 179 GO
+    noise(55, Kind::VoicedPlosive, 2000),           // 180 EG
+    noise(55, Kind::VoicedPlosive, 1500),           // 181 EGO
+    noise(70, Kind::Plosive, 2800),                 // 182 CH
+    ph(70, Kind::Aspirate, IY, IY, 0),              // 183 HE
+    ph(70, Kind::Aspirate, OH, OH, 0),              // 184 HO
+    ph(70, Kind::Aspirate, WW, WW, 0),              // 185 WH
+    noise(70, Kind::Fricative, 6000),               // 186 FF
+    noise(40, Kind::Fricative, 5500),               // 187 SE
+    noise(40, Kind::Fricative, 5000),               // 188 SO
+    noise(50, Kind::Fricative, 2500),               // 189 SH
+    noise(40, Kind::Fricative, 6500),               // 190 TH
+    noise(50, Kind::Plosive, 4000),                 // 191 TT
+    noise(70, Kind::Plosive, 3500),                 // 192 TU
+    noise(170, Kind::Fricative, 5500),              // 193 TS
+    noise(55, Kind::Plosive, 2500),                 // 194 KE
+    noise(55, Kind::Plosive, 1500),                 // 195 KO
+    noise(55, Kind::Plosive, 2500),                 // 196 EK
+    noise(45, Kind::Plosive, 1500),                 // 197 EKO
+    noise(99, Kind::Plosive, 1000),                 // 198 PE
+    noise(99, Kind::Plosive, 800),                  // 199 PO
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Sound {
+    Silence,
+    Speech(Phoneme),
+    /// Square wave of the given frequency in Hz.
+    Tone(f32),
+}
+
+/// Klatt style two pole resonator, unity gain at 0 Hz.
+#[derive(Debug, Default, Clone, Copy)]
+struct Resonator {
+    y1: f32,
+    y2: f32,
+}
+
+impl Resonator {
+    /// `t` is the sample period in seconds.
+    fn next(&mut self, x: f32, freq: f32, bandwidth: f32, t: f32) -> f32 {
+        let r = Float::exp(-PI * bandwidth * t);
+        let b = 2.0 * r * Float::cos(2.0 * PI * freq * t);
+        let c = -r * r;
+        let y = (1.0 - b - c) * x + b * self.y1 + c * self.y2;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+impl SaveState for Resonator {
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_f32(self.y1);
+        w.put_f32(self.y2);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.y1 = r.get_f32()?;
+        self.y2 = r.get_f32()?;
+
+        Ok(())
+    }
+}
+
+/// Magnevation SpeakJet, driven through its serial input.
+///
+/// Bytes 0-127 are commands, 128-199 allophones & 200-254 sound effects. Allophones are
+/// rendered by a small cascade formant synthesiser. Sound effects are approximated by tones.
+///
+/// NOTE: Phrases in the chip's own EEPROM & the bend / port commands are not emulated,
+/// their arguments are consumed & ignored.
+///
+/// Refer: Magnevation SpeakJet User's Manual.
+pub struct SpeakJet {
+    /// Of the console, which the serial timing & the audio samples are counted in.
+    cpu_hz: u32,
+    rx: SerialReceiver,
+    buffer: VecDeque<u8>,
+    /// CPU cycles into the current audio sample.
+    sample_clocks: u32,
+    samples: VecDeque<f32>,
+
+    volume: u8,
+    speed: u8,
+    pitch: u8,
+    /// Length factor for the next allophone, set by Fast / Slow.
+    next_scale: f32,
+    /// Pitch factor for the next allophone, set by Stress / Relax.
+    next_pitch: f32,
+    repeat: u8,
+
+    sound: Sound,
+    pitch_hz: f32,
+    /// Samples into & length of the current sound.
+    position: u32,
+    length: u32,
+    /// Smoothed output level, avoids clicks between sounds.
+    level: f32,
+    glottal_phase: f32,
+    noise_lfsr: u16,
+    formants: [Resonator; 3],
+    frication: Resonator,
+}
+
+impl Default for SpeakJet {
+    fn default() -> Self {
+        Self {
+            cpu_hz: NTSC_CPU_HZ,
+            rx: SerialReceiver::default(),
+            buffer: VecDeque::new(),
+            sample_clocks: 0,
+            samples: VecDeque::new(),
+            volume: DEFAULT_VOLUME,
+            speed: DEFAULT_SPEED,
+            pitch: DEFAULT_PITCH,
+            next_scale: 1.0,
+            next_pitch: 1.0,
+            repeat: 0,
+            sound: Sound::Silence,
+            pitch_hz: DEFAULT_PITCH as f32,
+            position: 0,
+            length: 0,
+            level: 0.0,
+            glottal_phase: 0.0,
+            noise_lfsr: 0xACE1,
+            formants: Default::default(),
+            frication: Default::default(),
+        }
+    }
+}
+
+impl SpeakJet {
+    #[inline]
+    pub fn cpu_hz(&self) -> u32 {
+        self.cpu_hz
+    }
+
+    pub fn set_cpu_hz(&mut self, cpu_hz: u32) {
+        self.cpu_hz = cpu_hz;
+    }
+
+    /// Level of the serial input, `true` being high.
+    pub fn drive_serial(&mut self, line: bool) {
+        self.rx.drive(line);
+    }
+
+    /// Low once the input buffer is half full.
+    #[inline]
+    pub fn ready(&self) -> bool {
+        self.buffer.len() < BUFFER_HALF_FULL
+    }
+
+    /// Whether anything is being said or waiting to be.
+    #[inline]
+    pub fn is_speaking(&self) -> bool {
+        !self.buffer.is_empty() || !matches!(self.sound, Sound::Silence)
+    }
+
+    /// Called once per CPU cycle.
+    pub fn tick(&mut self) {
+        if let Some(byte) = self.rx.tick(self.cpu_hz) {
+            if self.buffer.len() < BUFFER_SIZE {
+                self.buffer.push_back(byte);
+            }
+        }
+
+        self.sample_clocks += 1;
+        if self.sample_clocks == CPU_CYCLES_PER_AUDIO_SAMPLE {
+            self.sample_clocks = 0;
+            let sample = self.next_sample();
+            if self.samples.len() == MAX_QUEUED_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(sample);
+        }
+    }
+
+    /// Samples at [`SpeakJet::sample_rate`] generated since the last call, ranging from -1.0 to
+    /// 1.0.
+    pub fn drain_audio(&mut self, out: &mut Vec<f32>) {
+        out.extend(self.samples.drain(..));
+    }
+
+    /// In Hz, a sample being generated every [`CPU_CYCLES_PER_AUDIO_SAMPLE`].
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.cpu_hz / CPU_CYCLES_PER_AUDIO_SAMPLE
+    }
+
+    fn reset(&mut self) {
+        *self = Self {
+            cpu_hz: self.cpu_hz,
+            rx: core::mem::take(&mut self.rx),
+            samples: core::mem::take(&mut self.samples),
+            sample_clocks: self.sample_clocks,
+            ..Default::default()
+        };
+    }
+
+    fn start(&mut self, sound: Sound, ms: u32) {
+        self.sound = sound;
+        self.position = 0;
+        self.length = ms * self.sample_rate() / 1000;
+    }
+
+    fn start_phoneme(&mut self, phoneme: Phoneme) {
+        let ms = phoneme.ms as f32 * self.next_scale * DEFAULT_SPEED as f32 / self.speed as f32;
+        self.pitch_hz = self.pitch as f32 * self.next_pitch;
+        self.next_scale = 1.0;
+        self.next_pitch = 1.0;
+        self.start(Sound::Speech(phoneme), ms as u32);
+    }
+
+    /// Runs commands off the buffer till one starts a sound.
+    fn next_sound(&mut self) {
+        if let (Sound::Speech(phoneme), 1..) = (self.sound, self.repeat) {
+            self.repeat -= 1;
+            self.start_phoneme(phoneme);
+            return;
+        }
+
+        self.sound = Sound::Silence;
+        while let Some(&code) = self.buffer.front() {
+            if matches!(code, 20..=26 | 28..=30) {
+                // NOTE: Waits for the argument, without holding the ones after it up.
+                let Some(&arg) = self.buffer.get(1) else {
+                    return;
+                };
+                self.buffer.drain(..2);
+                match code {
+                    20 => self.volume = arg.min(127),
+                    21 => self.speed = arg.clamp(1, 127),
+                    22 => self.pitch = arg,
+                    26 => self.repeat = arg,
+                    30 => return self.start(Sound::Silence, arg as u32 * 10),
+                    _ => {}
+                }
+                continue;
+            }
+
+            self.buffer.pop_front();
+            match code {
+                0..=6 if PAUSES_MS[code as usize] > 0 => {
+                    return self.start(Sound::Silence, PAUSES_MS[code as usize] as u32);
+                }
+                7 => self.next_scale = 0.5,
+                8 => self.next_scale = 1.5,
+                14 => self.next_pitch = 1.1,
+                15 => self.next_pitch = 0.9,
+                31 => self.reset(),
+                128..=199 => return self.start_phoneme(PHONEMES[code as usize - 128]),
+                200..=254 => {
+                    let freq = 200.0 + (code - 200) as f32 * 40.0;
+                    return self.start(Sound::Tone(freq), SOUND_EFFECT_MS as u32);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        if self.position >= self.length {
+            self.next_sound();
+        }
+        self.position += 1;
+
+        let (target, sample) = match self.sound {
+            Sound::Silence => (0.0, 0.0),
+            Sound::Tone(freq) => (0.25, self.tone(freq)),
+            Sound::Speech(phoneme) => (1.0, self.speech(phoneme)),
+        };
+        if target == 0.0 && self.level < 1e-4 {
+            self.level = 0.0;
+            return 0.0;
+        }
+
+        self.level += (target - self.level) * 0.01;
+        let volume = self.volume as f32 / 127.0;
+        (sample * self.level * volume).clamp(-1.0, 1.0)
+    }
+
+    fn tone(&mut self, freq: f32) -> f32 {
+        self.glottal_phase = Float::fract(self.glottal_phase + freq / self.sample_rate() as f32);
+        if self.glottal_phase < 0.5 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    /// Sawtooth glottal pulses at the current pitch.
+    fn voice(&mut self) -> f32 {
+        self.glottal_phase =
+            Float::fract(self.glottal_phase + self.pitch_hz / self.sample_rate() as f32);
+        1.0 - 2.0 * self.glottal_phase
+    }
+
+    /// 16 bit Galois LFSR.
+    fn noise(&mut self) -> f32 {
+        let lsb = self.noise_lfsr & 1;
+        self.noise_lfsr >>= 1;
+        if lsb != 0 {
+            self.noise_lfsr ^= 0xB400;
+        }
+        if lsb != 0 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    /// Voice or noise through F1-F3.
+    fn vocal_tract(&mut self, x: f32, f1: f32, f2: f32) -> f32 {
+        let t = 1.0 / self.sample_rate() as f32;
+        let y = self.formants[0].next(x, f1, 60.0, t);
+        let y = self.formants[1].next(y, f2, 90.0, t);
+        self.formants[2].next(y, 2500.0, 150.0, t)
+    }
+
+    fn frication(&mut self, freq: u16) -> f32 {
+        let x = self.noise();
+        let t = 1.0 / self.sample_rate() as f32;
+        self.frication.next(x, freq as f32, freq as f32 / 2.0, t) * 0.1
+    }
+
+    fn speech(&mut self, phoneme: Phoneme) -> f32 {
+        let t = self.position as f32 / self.length.max(1) as f32;
+        let glide = |from: u16, to: u16| from as f32 + (to as f32 - from as f32) * t;
+        let f1 = glide(phoneme.from[0], phoneme.to[0]);
+        let f2 = glide(phoneme.from[1], phoneme.to[1]);
+        // NOTE: Bursts die down over the first half of the plosive.
+        let burst = Float::powi((1.0 - 2.0 * t).max(0.0), 2);
+
+        match phoneme.kind {
+            Kind::Vowel => {
+                let x = self.voice();
+                self.vocal_tract(x, f1, f2) * 0.2
+            }
+            Kind::Nasal => {
+                let x = self.voice();
+                self.vocal_tract(x, f1, f2) * 0.1
+            }
+            Kind::Aspirate => {
+                let x = self.noise();
+                self.vocal_tract(x, f1, f2) * 0.05
+            }
+            Kind::Fricative => self.frication(phoneme.noise),
+            Kind::VoicedFricative => {
+                let x = self.voice();
+                self.vocal_tract(x, f1, f2) * 0.1 + self.frication(phoneme.noise) * 0.5
+            }
+            Kind::Plosive => self.frication(phoneme.noise) * burst * 2.0,
+            Kind::VoicedPlosive => {
+                let x = self.voice();
+                self.vocal_tract(x, f1, f2) * 0.1 + self.frication(phoneme.noise) * burst
+            }
+        }
+    }
+}
+
+/// Serial input, command buffer & synthesiser. The CPU clock is the console's, audio not drained
+/// yet is dropped.
+impl SaveState for SpeakJet {
+    fn save_state(&self, w: &mut StateWriter) {
+        self.rx.save_state(w);
+        w.put_bytes(self.buffer.as_slices().0);
+        w.put_bytes(self.buffer.as_slices().1);
+        w.put_u32(self.sample_clocks);
+        w.put_u8(self.volume);
+        w.put_u8(self.speed);
+        w.put_u8(self.pitch);
+        w.put_f32(self.next_scale);
+        w.put_f32(self.next_pitch);
+        w.put_u8(self.repeat);
+        match self.sound {
+            Sound::Silence => w.put_u8(0),
+            Sound::Speech(phoneme) => {
+                w.put_u8(1);
+                w.put_u8(PHONEMES.iter().position(|&x| x == phoneme).unwrap() as u8);
+            }
+            Sound::Tone(freq) => {
+                w.put_u8(2);
+                w.put_f32(freq);
+            }
+        }
+        w.put_f32(self.pitch_hz);
+        w.put_u32(self.position);
+        w.put_u32(self.length);
+        w.put_f32(self.level);
+        w.put_f32(self.glottal_phase);
+        w.put_u16(self.noise_lfsr);
+        self.formants.iter().for_each(|x| x.save_state(w));
+        self.frication.save_state(w);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.rx.load_state(r)?;
+        self.buffer.clear();
+        self.buffer.extend(r.get_bytes()?);
+        self.buffer.extend(r.get_bytes()?);
+        self.sample_clocks = r.get_u32()?;
+        self.volume = r.get_u8()?;
+        self.speed = r.get_u8()?;
+        self.pitch = r.get_u8()?;
+        if self.buffer.len() > BUFFER_SIZE
+            || self.sample_clocks >= CPU_CYCLES_PER_AUDIO_SAMPLE
+            || self.volume > 127
+            || !(1..=127).contains(&self.speed)
+        {
+            return Err(r.corrupt());
+        }
+        self.next_scale = r.get_f32()?;
+        self.next_pitch = r.get_f32()?;
+        self.repeat = r.get_u8()?;
+        self.sound = match r.get_u8()? {
+            0 => Sound::Silence,
+            1 => Sound::Speech(*PHONEMES.get(r.get_u8()? as usize).ok_or(r.corrupt())?),
+            2 => Sound::Tone(r.get_f32()?),
+            _ => return Err(r.corrupt()),
+        };
+        self.pitch_hz = r.get_f32()?;
+        self.position = r.get_u32()?;
+        self.length = r.get_u32()?;
+        self.level = r.get_f32()?;
+        self.glottal_phase = r.get_f32()?;
+        self.noise_lfsr = r.get_u16()?;
+        for x in self.formants.iter_mut() {
+            x.load_state(r)?;
+        }
+        self.frication.load_state(r)?;
+        self.samples.clear();
+
+        Ok(())
+    }
+}
+
+/// AtariVox, a SpeakJet with its serial input on pin 1 & ready output on pin 2, plus a SaveKey
+/// on pins 3 & 4. Like SDA, the serial input is pulled low by making it an output in SWACNT.
+#[derive(Default)]
+pub struct AtariVox {
+    speakjet: SpeakJet,
+    savekey: SaveKey,
+}
+
+impl AtariVox {
+    pub fn new(storage: Box<dyn EepromStorage>) -> Self {
+        Self {
+            speakjet: SpeakJet::default(),
+            savekey: SaveKey::new(storage),
+        }
+    }
+
+    #[inline]
+    pub fn speakjet(&self) -> &SpeakJet {
+        &self.speakjet
+    }
+
+    #[inline]
+    pub fn savekey(&self) -> &SaveKey {
+        &self.savekey
+    }
+}
+
+impl ControllerPort for AtariVox {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::AtariVox
+    }
+
+    fn pins(&self) -> u8 {
+        if self.speakjet.ready() {
+            self.savekey.pins()
+        } else {
+            self.savekey.pins() & !bits::BIT_D1
+        }
+    }
+
+    fn fire(&self) -> bool {
+        true
+    }
+
+    fn drive_pins(&mut self, out: u8, ddr: u8) {
+        let serial = !bits::tst_bits(ddr, bits::BIT_D0) || bits::tst_bits(out, bits::BIT_D0);
+        self.speakjet.drive_serial(serial);
+        self.savekey.drive_pins(out, ddr);
+    }
+
+    fn tick(&mut self) {
+        self.speakjet.tick();
+    }
+
+    fn set_cpu_hz(&mut self, cpu_hz: u32) {
+        self.speakjet.set_cpu_hz(cpu_hz);
+    }
+
+    fn drain_audio(&mut self, out: &mut Vec<f32>) {
+        self.speakjet.drain_audio(out);
+    }
+
+    fn set_input(&mut self, _input: ControllerInput) {}
+
+    fn save_state(&self, w: &mut StateWriter) {
+        self.speakjet.save_state(w);
+        ControllerPort::save_state(&self.savekey, w);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.speakjet.load_state(r)?;
+        ControllerPort::load_state(&mut self.savekey, r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ctrl::{ControllerPorts, MemoryStorage, Port},
+        riot::{InMemory6532, PIA6532},
+        tia::PAL_CPU_HZ,
+    };
+    use alloc::{rc::Rc, vec};
+    use core::cell::RefCell;
+    use test_case::test_case;
+
+    /// CPU cycles per bit, as in the usual AtariVox driver.
+    const BIT_CYCLES: usize = 62;
+
+    /// Sends bytes the way the usual driver does: the serial output latch stays low, the line
+    /// is pulled low by making it an output in SWACNT.
+    #[derive(Default)]
+    struct Host {
+        atarivox: AtariVox,
+    }
+
+    impl Host {
+        fn line(&mut self, high: bool, cycles: usize) {
+            let ddr = if high { 0x00 } else { bits::BIT_D0 };
+            self.atarivox.drive_pins(0x00, ddr);
+            (0..cycles).for_each(|_| self.atarivox.tick());
+        }
+
+        fn send_frame(&mut self, frame: &[bool]) {
+            frame.iter().for_each(|&x| self.line(x, BIT_CYCLES));
+        }
+
+        fn send(&mut self, data: &[u8]) {
+            data.iter().for_each(|&x| {
+                let mut frame = vec![false];
+                frame.extend((0..8).map(|b| (x >> b) & 1 == 1));
+                frame.push(true);
+                self.send_frame(&frame);
+            });
+        }
+
+        fn ready(&self) -> bool {
+            bits::tst_bits(self.atarivox.pins(), bits::BIT_D1)
+        }
+
+        /// Starts a long delay, so the buffer does not drain.
+        fn hold(&mut self) {
+            self.send(&[30, 255]);
+        }
+
+        fn buffer(&self) -> Vec<u8> {
+            self.atarivox.speakjet.buffer.iter().copied().collect()
+        }
+    }
+
+    #[test_case(0x00)]
+    #[test_case(0x5A)]
+    #[test_case(0xFF)]
+    fn receives_bytes_at_19200_baud(byte: u8) {
+        let mut host = Host::default();
+        host.hold();
+
+        host.send(&[byte, 0x81]);
+
+        assert_eq!(host.buffer(), [byte, 0x81]);
+    }
+
+    #[test]
+    fn frames_without_stop_bit_are_dropped() {
+        let mut host = Host::default();
+        host.hold();
+
+        host.send_frame(&[false; 10]);
+        host.line(true, BIT_CYCLES);
+        host.send(&[0x22]);
+
+        assert_eq!(host.buffer(), [0x22]);
+    }
+
+    #[test]
+    fn glitches_are_not_start_bits() {
+        let mut host = Host::default();
+
+        host.line(false, 10);
+        host.line(true, 10 * BIT_CYCLES);
+
+        assert_eq!(host.buffer(), []);
+    }
+
+    #[test]
+    fn ready_drops_once_buffer_is_half_full() {
+        let mut host = Host::default();
+        host.hold();
+        assert!(host.ready());
+
+        host.send(&[0x80; BUFFER_HALF_FULL - 1]);
+        assert!(host.ready());
+        host.send(&[0x80]);
+        assert!(!host.ready());
+
+        host.send(&[0x80; BUFFER_SIZE]);
+        assert_eq!(host.buffer().len(), BUFFER_SIZE);
+    }
+
+    #[test]
+    fn audio_stays_in_step_with_cpu_cycles() {
+        let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+        ports
+            .borrow_mut()
+            .plug_device(Port::Right, Box::new(AtariVox::default()));
+        let mut pia = InMemory6532::new(ports.clone(), Default::default());
+
+        pia.tick(CPU_CYCLES_PER_AUDIO_SAMPLE as usize * 1000);
+
+        assert_eq!(ports.borrow_mut().drain_audio(), vec![0.0; 1000]);
+        assert_eq!(ports.borrow_mut().drain_audio(), []);
+    }
+
+    /// The state is taken while speaking & halfway through a byte.
+    #[test]
+    fn state_restores_speech_and_serial_input() {
+        let mut host = Host::default();
+        host.send(&[128, 140]);
+        host.line(true, 50 * BIT_CYCLES);
+        // NOTE: 0x81, LSB first.
+        host.send_frame(&[false, true, false, false]);
+        host.atarivox.drain_audio(&mut Vec::new());
+        let mut w = StateWriter::new();
+        w.section(b"CTRL", |w| host.atarivox.save_state(w));
+        let blob = w.into_bytes();
+
+        let mut host2 = Host::default();
+        let mut r = StateReader::new(&blob).unwrap();
+        r.section(b"CTRL", |r| host2.atarivox.load_state(r))
+            .unwrap();
+        let mut out = [Vec::new(), Vec::new()];
+        for (host, out) in [&mut host, &mut host2].into_iter().zip(out.iter_mut()) {
+            host.send_frame(&[false, false, false, false, true, true]);
+            host.hold();
+            host.atarivox.drain_audio(out);
+        }
+
+        assert_eq!(host2.buffer(), host.buffer());
+        assert_eq!(host.buffer(), [140, 0x81, 30, 255]);
+        assert_eq!(out[1], out[0]);
+        assert!(rms(&out[0]) > 0.01);
+    }
+
+    fn render(codes: &[u8], ms: u32) -> Vec<f32> {
+        render_at(codes, ms, NTSC_CPU_HZ)
+    }
+
+    fn render_at(codes: &[u8], ms: u32, cpu_hz: u32) -> Vec<f32> {
+        let mut speakjet = SpeakJet::default();
+        speakjet.set_cpu_hz(cpu_hz);
+        speakjet.buffer.extend(codes);
+        (0..ms * cpu_hz / 1000).for_each(|_| speakjet.tick());
+        let mut out = Vec::new();
+        speakjet.drain_audio(&mut out);
+        out
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        Float::sqrt(samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32)
+    }
+
+    #[test_case(128; "Vowel")]
+    #[test_case(140; "Nasal")]
+    #[test_case(187; "Fricative")]
+    #[test_case(167; "Voiced fricative")]
+    #[test_case(191; "Plosive")]
+    #[test_case(170; "Voiced plosive")]
+    #[test_case(183; "Aspirate")]
+    #[test_case(200; "Sound effect")]
+    fn sounds_are_audible_and_in_range(code: u8) {
+        let out = render(&[code], 50);
+
+        assert!(rms(&out[AUDIO_SAMPLE_RATE as usize / 200..]) > 0.01);
+        assert!(out.iter().all(|x| (-1.0..=1.0).contains(x)));
+    }
+
+    #[test_case(NTSC_CPU_HZ; "NTSC")]
+    #[test_case(PAL_CPU_HZ; "PAL")]
+    fn speech_ends_in_silence(cpu_hz: u32) {
+        let out = render_at(&[128, 2, 131], 300, cpu_hz);
+        let ms = |x: u32| (x * cpu_hz / CPU_CYCLES_PER_AUDIO_SAMPLE / 1000) as usize;
+
+        assert!(rms(&out[ms(10)..ms(60)]) > 0.01);
+        assert!(rms(&out[ms(120)..ms(260)]) < 1e-3);
+        assert!(rms(&out[ms(280)..ms(300)]) > 0.01);
+    }
+
+    #[test]
+    fn volume_scales_output() {
+        let loud = render(&[20, 127, 128], 60);
+        let soft = render(&[20, 32, 128], 60);
+
+        assert!((rms(&loud) / rms(&soft) - 127.0 / 32.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn speed_scales_allophone_length() {
+        let mut speakjet = SpeakJet::default();
+        speakjet.buffer.extend([21, DEFAULT_SPEED / 2, 128]);
+
+        speakjet.next_sound();
+
+        assert_eq!(speakjet.length, 140 * AUDIO_SAMPLE_RATE / 1000);
+    }
+
+    #[test]
+    fn reset_clears_settings_and_buffer() {
+        let mut speakjet = SpeakJet::default();
+        speakjet.set_cpu_hz(PAL_CPU_HZ);
+        speakjet.buffer.extend([20, 10, 22, 200, 31, 128]);
+
+        speakjet.next_sound();
+
+        assert_eq!(
+            (speakjet.volume, speakjet.pitch),
+            (DEFAULT_VOLUME, DEFAULT_PITCH)
+        );
+        assert!(matches!(speakjet.sound, Sound::Silence));
+        assert!(speakjet.buffer.is_empty());
+        assert_eq!(speakjet.cpu_hz(), PAL_CPU_HZ);
+    }
+
+    #[test]
+    fn savekey_shares_the_port() {
+        let storage = MemoryStorage::new(vec![0x42; crate::ctrl::EEPROM_SIZE]);
+        let atarivox = AtariVox::new(Box::new(storage));
+
+        assert_eq!(atarivox.savekey().eeprom().data()[0x10], 0x42);
+        assert_eq!(atarivox.pins(), 0x0F);
+    }
+}
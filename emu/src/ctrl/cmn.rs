@@ -0,0 +1,300 @@
+use super::{
+    atarivox::AtariVox,
+    driving::{Driving, DrivingState},
+    genesis::{Genesis, GenesisState},
+    joystick::{Joystick, JoystickState},
+    keypad::{Keypad, KeypadState},
+    paddles::{Paddles, PaddlesState},
+    savekey::SaveKey,
+};
+use crate::{
+    state::{SaveState, StateError, StateReader, StateWriter},
+    tia::NTSC_CPU_HZ,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, str::FromStr};
+
+/// The two controller ports on the back of the console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Port {
+    /// Player 0. SWCHA D7-D4, INPT0/1 & INPT4.
+    Left = 0,
+    /// Player 1. SWCHA D3-D0, INPT2/3 & INPT5.
+    Right = 1,
+}
+
+/// Kinds of devices that can be plugged into a [`Port`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerKind {
+    Joystick,
+    Paddles,
+    Keypad,
+    Driving,
+    Genesis,
+    SaveKey,
+    AtariVox,
+}
+
+const KINDS: [ControllerKind; 7] = [
+    ControllerKind::Joystick,
+    ControllerKind::Paddles,
+    ControllerKind::Keypad,
+    ControllerKind::Driving,
+    ControllerKind::Genesis,
+    ControllerKind::SaveKey,
+    ControllerKind::AtariVox,
+];
+
+impl ControllerKind {
+    fn device(&self) -> Box<dyn ControllerPort> {
+        match self {
+            ControllerKind::Joystick => Box::new(Joystick::default()),
+            ControllerKind::Paddles => Box::new(Paddles::default()),
+            ControllerKind::Keypad => Box::new(Keypad::default()),
+            ControllerKind::Driving => Box::new(Driving::default()),
+            ControllerKind::Genesis => Box::new(Genesis::default()),
+            ControllerKind::SaveKey => Box::new(SaveKey::default()),
+            ControllerKind::AtariVox => Box::new(AtariVox::default()),
+        }
+    }
+}
+
+/// Host side state of whatever is plugged into a port.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControllerInput {
+    Joystick(JoystickState),
+    Paddles(PaddlesState),
+    Keypad(KeypadState),
+    Driving(DrivingState),
+    Genesis(GenesisState),
+}
+
+impl ControllerInput {
+    /// Device this input is meant for.
+    pub fn kind(&self) -> ControllerKind {
+        match self {
+            ControllerInput::Joystick(_) => ControllerKind::Joystick,
+            ControllerInput::Paddles(_) => ControllerKind::Paddles,
+            ControllerInput::Keypad(_) => ControllerKind::Keypad,
+            ControllerInput::Driving(_) => ControllerKind::Driving,
+            ControllerInput::Genesis(_) => ControllerKind::Genesis,
+        }
+    }
+}
+
+/// A device plugged into a [`Port`]. All levels are electrical, `1` / `true` being high.
+///
+/// Pins 1-4 appear on the port's SWCHA nibble as D0-D3. Pin 6 is read by the TIA through INPT4/5,
+/// the pot lines on pins 9 & 5 through INPT0/1 (left) or INPT2/3 (right).
+///
+/// Refer:
+/// - https://www.alienbill.com/2600/101/docs/stella.html#pia1.0
+/// - https://problemkaputt.de/2k6specs.htm#controllersjoysticks
+pub trait ControllerPort {
+    fn kind(&self) -> ControllerKind;
+
+    /// Levels of pins 1-4 in D0-D3, as driven by the device. Pins it does not drive float high.
+    fn pins(&self) -> u8;
+
+    /// Level of pin 6.
+    fn fire(&self) -> bool;
+
+    /// Scanlines pot line `index` (0 for pin 9, 1 for pin 5) takes to charge the TIA capacitor.
+    /// `None` if nothing is connected, the line then never charges.
+    fn pot(&self, _index: usize) -> Option<u16> {
+        None
+    }
+
+    /// The RIOT drives the pins set in `ddr` to the levels in `out`. Both are nibbles.
+    fn drive_pins(&mut self, _out: u8, _ddr: u8) {}
+
+    /// Called once per CPU cycle, for devices with timing of their own.
+    fn tick(&mut self) {}
+
+    /// CPU clock of the console in Hz, for devices with timing of their own.
+    fn set_cpu_hz(&mut self, _cpu_hz: u32) {}
+
+    /// Appends audio generated since the last call, a sample every
+    /// [`crate::tia::CPU_CYCLES_PER_AUDIO_SAMPLE`], ranging from -1.0 to 1.0.
+    fn drain_audio(&mut self, _out: &mut Vec<f32>) {}
+
+    /// Input the host last set, `None` for devices that take none.
+    fn input(&self) -> Option<ControllerInput> {
+        None
+    }
+
+    /// Input meant for other kinds of devices is ignored.
+    fn set_input(&mut self, input: ControllerInput);
+
+    /// State of devices with memory of their own. The host's input is not part of it.
+    fn save_state(&self, _w: &mut StateWriter) {}
+
+    fn load_state(&mut self, _r: &mut StateReader) -> Result<(), StateError> {
+        Ok(())
+    }
+}
+
+/// Shared by the RIOT & the TIA, as both read the ports.
+pub struct ControllerPorts {
+    ports: [Box<dyn ControllerPort>; 2],
+    /// Last `out` & `ddr` nibbles handed to each port, for devices plugged in afterwards.
+    driven: [(u8, u8); 2],
+    cpu_hz: u32,
+}
+
+impl Default for ControllerPorts {
+    fn default() -> Self {
+        Self {
+            ports: [Box::new(Joystick::default()), Box::new(Joystick::default())],
+            driven: [(0x00, 0x00); 2],
+            cpu_hz: NTSC_CPU_HZ,
+        }
+    }
+}
+
+impl ControllerPorts {
+    #[inline]
+    pub fn get(&self, port: Port) -> &dyn ControllerPort {
+        self.ports[port as usize].as_ref()
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, port: Port) -> &mut dyn ControllerPort {
+        self.ports[port as usize].as_mut()
+    }
+
+    /// Replaces the device in `port` by a fresh one of `kind`.
+    pub fn plug(&mut self, port: Port, kind: ControllerKind) {
+        self.plug_device(port, kind.device());
+    }
+
+    /// Replaces the device in `port`, e.g. by one set up with host resources.
+    pub fn plug_device(&mut self, port: Port, device: Box<dyn ControllerPort>) {
+        let (out, ddr) = self.driven[port as usize];
+        self.ports[port as usize] = device;
+        self.get_mut(port).drive_pins(out, ddr);
+        let cpu_hz = self.cpu_hz;
+        self.get_mut(port).set_cpu_hz(cpu_hz);
+    }
+
+    #[inline]
+    pub fn cpu_hz(&self) -> u32 {
+        self.cpu_hz
+    }
+
+    /// Hands the CPU clock to both devices and to any plugged in afterwards.
+    pub fn set_cpu_hz(&mut self, cpu_hz: u32) {
+        self.cpu_hz = cpu_hz;
+        self.ports.iter_mut().for_each(|x| x.set_cpu_hz(cpu_hz));
+    }
+
+    /// Forwards `input` to the device in `port`, plugging the right kind of device first if needed.
+    pub fn set_input(&mut self, port: Port, input: ControllerInput) {
+        if self.get(port).kind() != input.kind() {
+            self.plug(port, input.kind());
+        }
+        self.get_mut(port).set_input(input);
+    }
+
+    /// Value of SWCHA, as far as the devices are concerned.
+    pub fn swcha_pins(&self) -> u8 {
+        (self.get(Port::Left).pins() << 4) | (self.get(Port::Right).pins() & 0x0F)
+    }
+
+    pub fn tick(&mut self) {
+        self.ports.iter_mut().for_each(|x| x.tick());
+    }
+
+    /// Audio generated by both devices since the last call, mixed.
+    pub fn drain_audio(&mut self) -> Vec<f32> {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        self.get_mut(Port::Left).drain_audio(&mut left);
+        self.get_mut(Port::Right).drain_audio(&mut right);
+        if left.len() < right.len() {
+            core::mem::swap(&mut left, &mut right);
+        }
+        left.iter_mut().zip(right).for_each(|(l, r)| *l += r);
+        left
+    }
+
+    /// Hands the RIOT port A outputs to the devices.
+    pub fn drive_swcha(&mut self, out: u8, ddr: u8) {
+        self.driven = [(out >> 4, ddr >> 4), (out & 0x0F, ddr & 0x0F)];
+        self.get_mut(Port::Left).drive_pins(out >> 4, ddr >> 4);
+        self.get_mut(Port::Right).drive_pins(out & 0x0F, ddr & 0x0F);
+    }
+}
+
+/// Kind & state of each device. A device of another kind than saved is replaced by a fresh one,
+/// dropping any host resources it was set up with.
+impl SaveState for ControllerPorts {
+    fn save_state(&self, w: &mut StateWriter) {
+        self.ports.iter().for_each(|x| {
+            w.put_u8(x.kind() as u8);
+            x.save_state(w);
+        });
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        for port in [Port::Left, Port::Right] {
+            let kind = *KINDS.get(r.get_u8()? as usize).ok_or(r.corrupt())?;
+            if self.get(port).kind() != kind {
+                self.plug(port, kind);
+            }
+            self.get_mut(port).load_state(r)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Port {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Port::Left => write!(f, "left"),
+            Port::Right => write!(f, "right"),
+        }
+    }
+}
+
+impl FromStr for Port {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Port::Left),
+            "right" => Ok(Port::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+/// `<device> <state>`, e.g. `joystick up+left+fire`, `paddles 0.5,0.25 fire-a` or `keypad 1+#`.
+impl fmt::Display for ControllerInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControllerInput::Joystick(x) => write!(f, "joystick {x}"),
+            ControllerInput::Paddles(x) => write!(f, "paddles {x}"),
+            ControllerInput::Keypad(x) => write!(f, "keypad {x}"),
+            ControllerInput::Driving(x) => write!(f, "driving {x}"),
+            ControllerInput::Genesis(x) => write!(f, "genesis {x}"),
+        }
+    }
+}
+
+impl FromStr for ControllerInput {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (device, state) = s.split_once(' ').ok_or(())?;
+        match device {
+            "joystick" => state.parse().map(ControllerInput::Joystick),
+            "paddles" => state.parse().map(ControllerInput::Paddles),
+            "keypad" => state.parse().map(ControllerInput::Keypad),
+            "driving" => state.parse().map(ControllerInput::Driving),
+            "genesis" => state.parse().map(ControllerInput::Genesis),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::cmn::{ControllerInput, ControllerKind, ControllerPort};
+use core::{fmt, str::FromStr};
+
+/// Levels of pins 1 & 2 for each step of a clockwise turn.
+const GRAY_CODE: [u8; 4] = [0b00, 0b01, 0b11, 0b10];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DrivingState {
+    /// Steps turned clockwise, wrapping. Only its last 2 bits reach the port.
+    pub rotation: u8,
+    pub fire: bool,
+}
+
+/// CX20 driving controller. A wheel that turns endlessly, reporting a 2 bit Gray code on pins 1 & 2.
+///
+/// Refer:
+/// - https://problemkaputt.de/2k6specs.htm#controllersdriving
+#[derive(Debug, Default)]
+pub struct Driving {
+    state: DrivingState,
+}
+
+impl Driving {
+    #[inline]
+    pub fn state(&self) -> DrivingState {
+        self.state
+    }
+}
+
+impl ControllerPort for Driving {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::Driving
+    }
+
+    /// NOTE: Pins 3 & 4 are not connected and float high.
+    fn pins(&self) -> u8 {
+        0b1100 | GRAY_CODE[(self.state.rotation & 0b11) as usize]
+    }
+
+    fn fire(&self) -> bool {
+        !self.state.fire
+    }
+
+    fn input(&self) -> Option<ControllerInput> {
+        Some(ControllerInput::Driving(self.state()))
+    }
+
+    fn set_input(&mut self, input: ControllerInput) {
+        if let ControllerInput::Driving(state) = input {
+            self.state = state;
+        }
+    }
+}
+
+/// `<rotation> <fire>`, fire being `fire` or `-`.
+impl fmt::Display for DrivingState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fire = if self.fire { "fire" } else { "-" };
+        write!(f, "{} {fire}", self.rotation)
+    }
+}
+
+impl FromStr for DrivingState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rotation, fire) = s.split_once(' ').ok_or(())?;
+        let fire = match fire {
+            "fire" => true,
+            "-" => false,
+            _ => return Err(()),
+        };
+
+        Ok(DrivingState {
+            rotation: rotation.parse().map_err(|_| ())?,
+            fire,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    fn dr(rotation: u8, fire: bool) -> DrivingState {
+        DrivingState { rotation, fire }
+    }
+
+    #[test_case(dr(0, false), 0b1100, true)]
+    #[test_case(dr(1, false), 0b1101, true)]
+    #[test_case(dr(2, true), 0b1111, false)]
+    #[test_case(dr(3, false), 0b1110, true)]
+    #[test_case(dr(4, false), 0b1100, true; "Full turn")]
+    #[test_case(dr(255, false), 0b1110, true; "Counter clockwise from start")]
+    fn rotation_is_gray_coded(state: DrivingState, pins: u8, fire: bool) {
+        let mut d = Driving::default();
+        d.set_input(ControllerInput::Driving(state));
+
+        assert_eq!(d.pins(), pins);
+        assert_eq!(d.fire(), fire);
+    }
+
+    #[test]
+    fn one_pin_changes_per_step() {
+        let pins = |rotation| {
+            let mut d = Driving::default();
+            d.set_input(ControllerInput::Driving(dr(rotation, false)));
+            d.pins()
+        };
+
+        (0..=u8::MAX).for_each(|x| assert_eq!((pins(x) ^ pins(x.wrapping_add(1))).count_ones(), 1));
+    }
+
+    #[test_case(dr(0, false), "0 -")]
+    #[test_case(dr(37, true), "37 fire")]
+    fn text_roundtrip(state: DrivingState, text: &str) {
+        assert_eq!(state.to_string(), text);
+        assert_eq!(text.parse(), Ok(state));
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case("1"; "Missing fire")]
+    #[test_case("256 -"; "Rotation overflow")]
+    #[test_case("1 jump"; "Unknown button")]
+    fn text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<DrivingState>(), Err(()));
+    }
+}
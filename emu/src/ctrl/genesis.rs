@@ -0,0 +1,136 @@
+use super::{
+    cmn::{ControllerInput, ControllerKind, ControllerPort},
+    joystick::{Joystick, JoystickState},
+};
+use core::{fmt, str::FromStr};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GenesisState {
+    /// Directions & button B, wired like a joystick.
+    pub stick: JoystickState,
+    /// Button C.
+    pub c: bool,
+}
+
+/// Sega Genesis / Mega Drive pad. Works as a joystick, button C pulls pin 5 low when pressed, read
+/// through INPT1 (left) or INPT3 (right).
+#[derive(Debug, Default)]
+pub struct Genesis {
+    stick: Joystick,
+    c: bool,
+}
+
+impl Genesis {
+    #[inline]
+    pub fn state(&self) -> GenesisState {
+        GenesisState {
+            stick: self.stick.state(),
+            c: self.c,
+        }
+    }
+}
+
+impl ControllerPort for Genesis {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::Genesis
+    }
+
+    fn pins(&self) -> u8 {
+        self.stick.pins()
+    }
+
+    fn fire(&self) -> bool {
+        self.stick.fire()
+    }
+
+    /// NOTE: Pin 5 is pulled up through the pad, pin 9 is not connected.
+    fn pot(&self, index: usize) -> Option<u16> {
+        (index == 1 && !self.c).then_some(0)
+    }
+
+    fn input(&self) -> Option<ControllerInput> {
+        Some(ControllerInput::Genesis(self.state()))
+    }
+
+    fn set_input(&mut self, input: ControllerInput) {
+        if let ControllerInput::Genesis(state) = input {
+            self.stick.set_input(ControllerInput::Joystick(state.stick));
+            self.c = state.c;
+        }
+    }
+}
+
+/// Joystick text followed by `+c` when C is pressed, e.g. `up+fire+c`. Just `c` if it is the only one.
+impl fmt::Display for GenesisState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.stick == JoystickState::default(), self.c) {
+            (true, true) => write!(f, "c"),
+            (false, true) => write!(f, "{}+c", self.stick),
+            (_, false) => write!(f, "{}", self.stick),
+        }
+    }
+}
+
+impl FromStr for GenesisState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (stick, c) = match s.strip_suffix("+c") {
+            Some("-") => return Err(()),
+            Some(stick) => (stick, true),
+            None if s == "c" => ("-", true),
+            None => (s, false),
+        };
+
+        Ok(GenesisState {
+            stick: stick.parse()?,
+            c,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    fn gs(stick: &str, c: bool) -> GenesisState {
+        GenesisState {
+            stick: stick.parse().unwrap(),
+            c,
+        }
+    }
+
+    #[test_case(gs("-", false), 0b1111, true, Some(0))]
+    #[test_case(gs("up+fire", false), 0b1110, false, Some(0))]
+    #[test_case(gs("left", true), 0b1011, true, None)]
+    #[test_case(gs("fire", true), 0b1111, false, None)]
+    fn buttons_are_active_low(state: GenesisState, pins: u8, fire: bool, pin5: Option<u16>) {
+        let mut g = Genesis::default();
+        g.set_input(ControllerInput::Genesis(state));
+
+        assert_eq!(g.pins(), pins);
+        assert_eq!(g.fire(), fire);
+        assert_eq!(g.pot(1), pin5);
+        assert_eq!(g.pot(0), None);
+        assert_eq!(g.state(), state);
+    }
+
+    #[test_case(gs("-", false), "-")]
+    #[test_case(gs("-", true), "c")]
+    #[test_case(gs("down+right+fire", false), "down+right+fire")]
+    #[test_case(gs("up+fire", true), "up+fire+c")]
+    fn text_roundtrip(state: GenesisState, text: &str) {
+        assert_eq!(state.to_string(), text);
+        assert_eq!(text.parse(), Ok(state));
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case("-+c"; "Nothing before C")]
+    #[test_case("up+"; "Trailing separator")]
+    #[test_case("c+up"; "C first")]
+    fn text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<GenesisState>(), Err(()));
+    }
+}
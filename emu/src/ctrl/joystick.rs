@@ -0,0 +1,147 @@
+use super::cmn::{ControllerInput, ControllerKind, ControllerPort};
+use crate::bits;
+use core::{fmt, str::FromStr};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JoystickState {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+}
+
+/// Standard CX40 digital joystick. Every switch pulls its pin low when closed.
+#[derive(Debug, Default)]
+pub struct Joystick {
+    state: JoystickState,
+}
+
+impl Joystick {
+    #[inline]
+    pub fn state(&self) -> JoystickState {
+        self.state
+    }
+}
+
+impl ControllerPort for Joystick {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::Joystick
+    }
+
+    fn pins(&self) -> u8 {
+        [
+            (self.state.up, bits::BIT_D0),
+            (self.state.down, bits::BIT_D1),
+            (self.state.left, bits::BIT_D2),
+            (self.state.right, bits::BIT_D3),
+        ]
+        .iter()
+        .filter(|(pressed, _)| !pressed)
+        .fold(0x00, |acc, (_, bit)| acc | bit)
+    }
+
+    fn fire(&self) -> bool {
+        !self.state.fire
+    }
+
+    fn input(&self) -> Option<ControllerInput> {
+        Some(ControllerInput::Joystick(self.state()))
+    }
+
+    fn set_input(&mut self, input: ControllerInput) {
+        if let ControllerInput::Joystick(state) = input {
+            self.state = state;
+        }
+    }
+}
+
+const NAMES: [&str; 5] = ["up", "down", "left", "right", "fire"];
+
+/// Pressed switches joined with `+`, `-` when none is.
+impl fmt::Display for JoystickState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pressed = [self.up, self.down, self.left, self.right, self.fire];
+        let mut sep = "";
+        for (_, name) in pressed.iter().zip(NAMES).filter(|(&p, _)| p) {
+            write!(f, "{sep}{name}")?;
+            sep = "+";
+        }
+        if sep.is_empty() {
+            write!(f, "-")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for JoystickState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ret = JoystickState::default();
+        if s == "-" {
+            return Ok(ret);
+        }
+
+        for name in s.split('+') {
+            let switch = match name {
+                "up" => &mut ret.up,
+                "down" => &mut ret.down,
+                "left" => &mut ret.left,
+                "right" => &mut ret.right,
+                "fire" => &mut ret.fire,
+                _ => return Err(()),
+            };
+            *switch = true;
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    fn js(up: bool, down: bool, left: bool, right: bool, fire: bool) -> JoystickState {
+        JoystickState {
+            up,
+            down,
+            left,
+            right,
+            fire,
+        }
+    }
+
+    #[test_case(js(false, false, false, false, false), 0b1111, true)]
+    #[test_case(js(true, false, false, false, false), 0b1110, true)]
+    #[test_case(js(false, true, false, false, true), 0b1101, false)]
+    #[test_case(js(false, false, true, false, false), 0b1011, true)]
+    #[test_case(js(true, false, false, true, true), 0b0110, false)]
+    fn pins_are_active_low(state: JoystickState, pins: u8, fire: bool) {
+        let mut j = Joystick::default();
+        j.set_input(ControllerInput::Joystick(state));
+
+        assert_eq!(j.pins(), pins);
+        assert_eq!(j.fire(), fire);
+    }
+
+    #[test_case(js(false, false, false, false, false), "-")]
+    #[test_case(js(true, false, false, false, false), "up")]
+    #[test_case(js(true, false, true, false, true), "up+left+fire")]
+    #[test_case(js(false, true, false, true, false), "down+right")]
+    fn text_roundtrip(state: JoystickState, text: &str) {
+        assert_eq!(state.to_string(), text);
+        assert_eq!(text.parse(), Ok(state));
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case("up+"; "Trailing separator")]
+    #[test_case("jump"; "Unknown switch")]
+    fn text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<JoystickState>(), Err(()));
+    }
+}
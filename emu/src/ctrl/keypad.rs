@@ -0,0 +1,149 @@
+use super::cmn::{ControllerInput, ControllerKind, ControllerPort};
+use core::{fmt, str::FromStr};
+
+/// Key labels, row by row from the top.
+const NAMES: [&str; 12] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "*", "0", "#"];
+
+/// Pressed keys, in the order of [`NAMES`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeypadState {
+    pub pressed: [bool; 12],
+}
+
+/// CX50 keyboard controller. 4 rows of 3 keys.
+///
+/// The program selects rows by driving their pins 1-4 low through SWCHA & SWACNT. A pressed key in
+/// a selected row pulls its column low: pin 9, pin 5 & pin 6, read through INPT0/1 & INPT4 (left)
+/// or INPT2/3 & INPT5 (right). Columns float high otherwise.
+///
+/// Refer:
+/// - https://problemkaputt.de/2k6specs.htm#controllerskeyboard
+#[derive(Debug, Default)]
+pub struct Keypad {
+    state: KeypadState,
+    /// Rows driven low, D0 being the top one.
+    selected_rows: u8,
+}
+
+impl Keypad {
+    #[inline]
+    pub fn state(&self) -> KeypadState {
+        self.state
+    }
+
+    fn column_low(&self, column: usize) -> bool {
+        (0..4)
+            .any(|row| (self.selected_rows >> row) & 1 == 1 && self.state.pressed[row * 3 + column])
+    }
+}
+
+impl ControllerPort for Keypad {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::Keypad
+    }
+
+    fn pins(&self) -> u8 {
+        0x0F
+    }
+
+    fn fire(&self) -> bool {
+        !self.column_low(2)
+    }
+
+    /// NOTE: Unpressed columns are pulled up, they charge at once.
+    fn pot(&self, index: usize) -> Option<u16> {
+        (!self.column_low(index)).then_some(0)
+    }
+
+    fn drive_pins(&mut self, out: u8, ddr: u8) {
+        self.selected_rows = ddr & !out & 0x0F;
+    }
+
+    fn input(&self) -> Option<ControllerInput> {
+        Some(ControllerInput::Keypad(self.state()))
+    }
+
+    fn set_input(&mut self, input: ControllerInput) {
+        if let ControllerInput::Keypad(state) = input {
+            self.state = state;
+        }
+    }
+}
+
+/// Pressed keys joined with `+`, `-` when none is.
+impl fmt::Display for KeypadState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut sep = "";
+        for (_, name) in self.pressed.iter().zip(NAMES).filter(|(&p, _)| p) {
+            write!(f, "{sep}{name}")?;
+            sep = "+";
+        }
+        if sep.is_empty() {
+            write!(f, "-")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for KeypadState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ret = KeypadState::default();
+        if s == "-" {
+            return Ok(ret);
+        }
+
+        for name in s.split('+') {
+            let key = NAMES.iter().position(|&x| x == name).ok_or(())?;
+            ret.pressed[key] = true;
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    fn keys(text: &str) -> KeypadState {
+        text.parse().unwrap()
+    }
+
+    /// `out` & `ddr` as the program writes them to the port's SWCHA & SWACNT nibble.
+    #[test_case("-", 0b1110, 0b1111, [true, true, true]; "Nothing pressed")]
+    #[test_case("1", 0b1110, 0b1111, [false, true, true]; "Top row selected")]
+    #[test_case("1", 0b1101, 0b1111, [true, true, true]; "Other row selected")]
+    #[test_case("5", 0b1101, 0b1111, [true, false, true]; "Middle column")]
+    #[test_case("9", 0b1011, 0b1111, [true, true, false]; "Right column")]
+    #[test_case("0+#", 0b0111, 0b1111, [true, false, false]; "Bottom row")]
+    #[test_case("*", 0b0000, 0b0111, [true, true, true]; "Row pin is an input")]
+    #[test_case("2+4", 0b1100, 0b1111, [false, false, true]; "Two rows selected")]
+    fn columns_follow_selected_rows(state: &str, out: u8, ddr: u8, high: [bool; 3]) {
+        let mut k = Keypad::default();
+        k.set_input(ControllerInput::Keypad(keys(state)));
+        k.drive_pins(out, ddr);
+
+        assert_eq!([k.pot(0).is_some(), k.pot(1).is_some(), k.fire()], high);
+        assert_eq!(k.pins(), 0x0F);
+    }
+
+    #[test_case(KeypadState::default(), "-")]
+    #[test_case(keys("1+5+9"), "1+5+9")]
+    #[test_case(keys("*+0+#"), "*+0+#")]
+    fn text_roundtrip(state: KeypadState, text: &str) {
+        assert_eq!(state.to_string(), text);
+        assert_eq!(text.parse(), Ok(state));
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case("1+"; "Trailing separator")]
+    #[test_case("A"; "Unknown key")]
+    fn text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<KeypadState>(), Err(()));
+    }
+}
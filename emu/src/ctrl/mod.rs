@@ -0,0 +1,21 @@
+mod atarivox;
+mod cmn;
+mod driving;
+mod genesis;
+mod joystick;
+mod keypad;
+mod paddles;
+mod savekey;
+mod switches;
+
+pub use atarivox::{AtariVox, SpeakJet};
+pub use cmn::{ControllerInput, ControllerKind, ControllerPort, ControllerPorts, Port};
+pub use driving::{Driving, DrivingState};
+pub use genesis::{Genesis, GenesisState};
+pub use joystick::{Joystick, JoystickState};
+pub use keypad::{Keypad, KeypadState};
+pub use paddles::{Paddles, PaddlesState, POT_MAX_SCANLINES};
+#[cfg(feature = "std")]
+pub use savekey::FileStorage;
+pub use savekey::{Eeprom, EepromStorage, MemoryStorage, SaveKey, EEPROM_SIZE};
+pub use switches::{ConsoleSwitches, Difficulty};
@@ -0,0 +1,180 @@
+use super::cmn::{ControllerInput, ControllerKind, ControllerPort};
+use crate::bits;
+use core::{fmt, str::FromStr};
+
+/// Scanlines a pot at full resistance takes to charge the TIA capacitor past its threshold.
+///
+/// NOTE: 1MΩ pot, 68nF capacitor. The series resistor adds less than a scanline and is ignored.
+pub const POT_MAX_SCANLINES: u16 = 380;
+
+/// Paddle A is wired to pins 9 & 4, paddle B to pins 5 & 3.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PaddlesState {
+    /// Knob positions, 0.0 charges immediately & 1.0 takes [`POT_MAX_SCANLINES`].
+    pub position: [f32; 2],
+    pub fire: [bool; 2],
+}
+
+/// Pair of CX30 paddles sharing a port.
+///
+/// Refer:
+/// - https://problemkaputt.de/2k6specs.htm#controllerspaddles
+#[derive(Debug, Default)]
+pub struct Paddles {
+    state: PaddlesState,
+}
+
+impl Paddles {
+    #[inline]
+    pub fn state(&self) -> PaddlesState {
+        self.state
+    }
+}
+
+impl ControllerPort for Paddles {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::Paddles
+    }
+
+    fn pins(&self) -> u8 {
+        [
+            (true, bits::BIT_D0),
+            (true, bits::BIT_D1),
+            (!self.state.fire[1], bits::BIT_D2),
+            (!self.state.fire[0], bits::BIT_D3),
+        ]
+        .iter()
+        .filter(|(high, _)| *high)
+        .fold(0x00, |acc, (_, bit)| acc | bit)
+    }
+
+    fn fire(&self) -> bool {
+        true
+    }
+
+    fn pot(&self, index: usize) -> Option<u16> {
+        let position = self.state.position[index].clamp(0.0, 1.0);
+        Some((position * POT_MAX_SCANLINES as f32 + 0.5) as u16)
+    }
+
+    fn input(&self) -> Option<ControllerInput> {
+        Some(ControllerInput::Paddles(self.state()))
+    }
+
+    fn set_input(&mut self, input: ControllerInput) {
+        if let ControllerInput::Paddles(state) = input {
+            self.state = state;
+        }
+    }
+}
+
+const NAMES: [&str; 2] = ["fire-a", "fire-b"];
+
+/// `<position a>,<position b> <buttons>`, buttons joined with `+`, `-` when none is pressed.
+impl fmt::Display for PaddlesState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{} ", self.position[0], self.position[1])?;
+        let mut sep = "";
+        for (_, name) in self.fire.iter().zip(NAMES).filter(|(&p, _)| p) {
+            write!(f, "{sep}{name}")?;
+            sep = "+";
+        }
+        if sep.is_empty() {
+            write!(f, "-")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PaddlesState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (positions, buttons) = s.split_once(' ').ok_or(())?;
+        let (a, b) = positions.split_once(',').ok_or(())?;
+        let mut ret = PaddlesState {
+            position: [a.parse().map_err(|_| ())?, b.parse().map_err(|_| ())?],
+            fire: [false, false],
+        };
+        if ret.position.iter().any(|x| !(0.0..=1.0).contains(x)) {
+            return Err(());
+        }
+        if buttons == "-" {
+            return Ok(ret);
+        }
+
+        for name in buttons.split('+') {
+            match name {
+                "fire-a" => ret.fire[0] = true,
+                "fire-b" => ret.fire[1] = true,
+                _ => return Err(()),
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    fn pd(a: f32, b: f32, fire_a: bool, fire_b: bool) -> PaddlesState {
+        PaddlesState {
+            position: [a, b],
+            fire: [fire_a, fire_b],
+        }
+    }
+
+    #[test_case(pd(0.0, 0.0, false, false), 0b1111)]
+    #[test_case(pd(0.0, 0.0, true, false), 0b0111)]
+    #[test_case(pd(0.0, 0.0, false, true), 0b1011)]
+    #[test_case(pd(0.0, 0.0, true, true), 0b0011)]
+    fn fire_buttons_are_active_low(state: PaddlesState, pins: u8) {
+        let mut p = Paddles::default();
+        p.set_input(ControllerInput::Paddles(state));
+
+        assert_eq!(p.pins(), pins);
+        assert!(p.fire());
+    }
+
+    #[test_case(0.0, 0)]
+    #[test_case(0.25, 95)]
+    #[test_case(0.5, 190)]
+    #[test_case(1.0, POT_MAX_SCANLINES)]
+    #[test_case(-1.0, 0; "Clamped low")]
+    #[test_case(2.0, POT_MAX_SCANLINES; "Clamped high")]
+    fn pot_charge_time(position: f32, scanlines: u16) {
+        let mut p = Paddles::default();
+        p.set_input(ControllerInput::Paddles(pd(
+            position,
+            1.0 - position,
+            false,
+            false,
+        )));
+
+        assert_eq!(p.pot(0), Some(scanlines));
+        assert_eq!(p.pot(1), Some(POT_MAX_SCANLINES - scanlines));
+    }
+
+    #[test_case(pd(0.0, 0.0, false, false), "0,0 -")]
+    #[test_case(pd(0.5, 0.25, true, false), "0.5,0.25 fire-a")]
+    #[test_case(pd(1.0, 0.1, true, true), "1,0.1 fire-a+fire-b")]
+    fn text_roundtrip(state: PaddlesState, text: &str) {
+        assert_eq!(state.to_string(), text);
+        assert_eq!(text.parse(), Ok(state));
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case("0.5 -"; "Single position")]
+    #[test_case("0.5,x -"; "Bad position")]
+    #[test_case("0.5,1.5 -"; "Out of range")]
+    #[test_case("0.5,0.5 fire-a+"; "Trailing separator")]
+    #[test_case("0.5,0.5 fire"; "Unknown button")]
+    fn text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<PaddlesState>(), Err(()));
+    }
+}
@@ -0,0 +1,579 @@
+use super::cmn::{ControllerInput, ControllerKind, ControllerPort};
+use crate::{
+    bits,
+    state::{SaveState, StateError, StateReader, StateWriter},
+};
+use alloc::{boxed::Box, rc::Rc, vec, vec::Vec};
+use core::cell::RefCell;
+
+/// 24LC256, 32K bytes.
+pub const EEPROM_SIZE: usize = 0x8000;
+/// Writes wrap around within a page of 64 bytes.
+const PAGE_MASK: u16 = 0x003F;
+/// 1010 device code, A2-A0 tied low. R/W in D0.
+const CONTROL_CODE: u8 = 0b1010_0000;
+
+/// Where an [`Eeprom`] keeps its contents between sessions.
+pub trait EepromStorage {
+    /// Contents saved by an earlier session, if any.
+    fn load(&mut self) -> Option<Vec<u8>>;
+
+    /// Called with the whole memory after each completed write.
+    fn store(&mut self, data: &[u8]);
+}
+
+/// Byte array the host can read back, e.g. to put it in the browser's local storage.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStorage {
+    data: Rc<RefCell<Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    /// Last stored contents, shared by all clones.
+    pub fn data(&self) -> Vec<u8> {
+        self.data.borrow().clone()
+    }
+}
+
+impl EepromStorage for MemoryStorage {
+    fn load(&mut self) -> Option<Vec<u8>> {
+        let data = self.data.borrow();
+        (!data.is_empty()).then(|| data.clone())
+    }
+
+    fn store(&mut self, data: &[u8]) {
+        *self.data.borrow_mut() = data.to_vec();
+    }
+}
+
+/// File on the host, created on the first write.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    path: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FileStorage {
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl EepromStorage for FileStorage {
+    fn load(&mut self) -> Option<Vec<u8>> {
+        std::fs::read(&self.path).ok()
+    }
+
+    fn store(&mut self, data: &[u8]) {
+        if let Err(e) = std::fs::write(&self.path, data) {
+            log::error!("Unable to save EEPROM to {}: {e}", self.path.display());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Waiting for a start condition.
+    Idle,
+    Control,
+    AddressHigh,
+    AddressLow,
+    Write,
+    Read,
+}
+
+const PHASES: [Phase; 6] = [
+    Phase::Idle,
+    Phase::Control,
+    Phase::AddressHigh,
+    Phase::AddressLow,
+    Phase::Write,
+    Phase::Read,
+];
+
+/// Microchip 24LC256 I2C serial EEPROM.
+///
+/// NOTE: Writes complete at once, the chip never NAKs for being busy.
+///
+/// Refer: Microchip 24AA256/24LC256/24FC256 data sheet.
+pub struct Eeprom {
+    data: Vec<u8>,
+    storage: Option<Box<dyn EepromStorage>>,
+    phase: Phase,
+    shift: u8,
+    /// Clock pulses into the current byte. 8 once all bits are in, 9 during the acknowledge pulse.
+    bit: u8,
+    address: u16,
+    /// Page write, committed by the stop condition.
+    pending: Vec<(u16, u8)>,
+    /// Bus levels as of the last call to [`Eeprom::drive`].
+    bus_scl: bool,
+    bus_sda: bool,
+    /// Level the chip drives SDA to, `true` being released.
+    sda_out: bool,
+}
+
+impl Default for Eeprom {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Eeprom {
+    /// Erased, i.e. all 0xFF, unless `storage` has contents of the right size.
+    pub fn new(mut storage: Option<Box<dyn EepromStorage>>) -> Self {
+        let data = storage
+            .as_mut()
+            .and_then(|x| x.load())
+            .filter(|x| x.len() == EEPROM_SIZE)
+            .unwrap_or_else(|| vec![0xFF; EEPROM_SIZE]);
+        Self {
+            data,
+            storage,
+            phase: Phase::Idle,
+            shift: 0,
+            bit: 0,
+            address: 0,
+            pending: Vec::new(),
+            bus_scl: true,
+            bus_sda: true,
+            sda_out: true,
+        }
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Level of SDA as far as the chip is concerned.
+    #[inline]
+    pub fn sda(&self) -> bool {
+        self.sda_out
+    }
+
+    /// Levels the master drives the bus to, `true` being released.
+    pub fn drive(&mut self, scl: bool, sda: bool) {
+        let bus = sda && self.sda_out;
+        match (self.bus_scl, scl) {
+            (true, true) if self.bus_sda && !bus => self.start(),
+            (true, true) if !self.bus_sda && bus => self.stop(),
+            (false, true) => self.clock_rise(bus),
+            (true, false) => self.clock_fall(),
+            _ => {}
+        }
+        self.bus_scl = scl;
+        self.bus_sda = sda && self.sda_out;
+    }
+
+    /// NOTE: A repeated start aborts a page write that was not stopped.
+    fn start(&mut self) {
+        self.phase = Phase::Control;
+        self.shift = 0;
+        self.bit = 0;
+        self.pending.clear();
+        self.sda_out = true;
+    }
+
+    fn stop(&mut self) {
+        self.phase = Phase::Idle;
+        self.sda_out = true;
+        if self.pending.is_empty() {
+            return;
+        }
+
+        self.pending
+            .drain(..)
+            .for_each(|(addr, val)| self.data[addr as usize] = val);
+        if let Some(storage) = self.storage.as_mut() {
+            storage.store(&self.data);
+        }
+    }
+
+    fn clock_rise(&mut self, sda: bool) {
+        match (self.phase, self.bit) {
+            (Phase::Idle, _) => {}
+            (Phase::Read, 0..=7) => self.bit += 1,
+            (_, 0..=7) => {
+                self.shift = (self.shift << 1) | sda as u8;
+                self.bit += 1;
+            }
+            // NOTE: The master NAKs the last byte it wants.
+            (Phase::Read, 9) if sda => self.phase = Phase::Idle,
+            _ => {}
+        }
+    }
+
+    fn clock_fall(&mut self) {
+        match (self.phase, self.bit) {
+            (Phase::Idle, _) => {}
+            (Phase::Read, 9) => self.send_next(),
+            (_, 9) => {
+                self.bit = 0;
+                self.sda_out = true;
+            }
+            (Phase::Read, 8) => {
+                self.sda_out = true;
+                self.bit = 9;
+            }
+            (_, 8) => {
+                self.sda_out = !self.receive();
+                self.bit = 9;
+            }
+            (Phase::Read, x) => self.sda_out = bits::tst_bits(self.shift, 0x80 >> x),
+            _ => {}
+        }
+    }
+
+    fn send_next(&mut self) {
+        self.shift = self.data[self.address as usize];
+        self.address = (self.address + 1) % EEPROM_SIZE as u16;
+        self.bit = 0;
+        self.sda_out = bits::tst_bits(self.shift, bits::BIT_D7);
+    }
+
+    /// Handles a complete byte from the master. Returns whether to acknowledge it.
+    fn receive(&mut self) -> bool {
+        match self.phase {
+            Phase::Control if self.shift & !bits::BIT_D0 != CONTROL_CODE => {
+                self.phase = Phase::Idle;
+                return false;
+            }
+            Phase::Control if bits::tst_bits(self.shift, bits::BIT_D0) => {
+                self.phase = Phase::Read;
+            }
+            Phase::Control => self.phase = Phase::AddressHigh,
+            Phase::AddressHigh => {
+                self.address = ((self.shift & 0x7F) as u16) << 8;
+                self.phase = Phase::AddressLow;
+            }
+            Phase::AddressLow => {
+                self.address |= self.shift as u16;
+                self.phase = Phase::Write;
+            }
+            Phase::Write => {
+                self.pending.push((self.address, self.shift));
+                self.address = (self.address & !PAGE_MASK) | ((self.address + 1) & PAGE_MASK);
+            }
+            Phase::Idle | Phase::Read => {}
+        }
+
+        true
+    }
+}
+
+/// Contents & bus state, the storage is not part of the state.
+///
+/// NOTE: Restored contents reach the storage with the next completed write.
+impl SaveState for Eeprom {
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_u8(self.phase as u8);
+        w.put_u8(self.shift);
+        w.put_u8(self.bit);
+        w.put_u16(self.address);
+        w.put_u8(self.pending.len() as u8);
+        self.pending.iter().for_each(|&(addr, val)| {
+            w.put_u16(addr);
+            w.put_u8(val);
+        });
+        w.put_bool(self.bus_scl);
+        w.put_bool(self.bus_sda);
+        w.put_bool(self.sda_out);
+        w.put_bytes(&self.data);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.phase = *PHASES.get(r.get_u8()? as usize).ok_or(r.corrupt())?;
+        self.shift = r.get_u8()?;
+        self.bit = r.get_u8()?;
+        self.address = r.get_u16()?;
+        if self.bit > 9 || self.address as usize >= EEPROM_SIZE {
+            return Err(r.corrupt());
+        }
+        self.pending.clear();
+        for _ in 0..r.get_u8()? {
+            let (addr, val) = (r.get_u16()?, r.get_u8()?);
+            if addr as usize >= EEPROM_SIZE {
+                return Err(r.corrupt());
+            }
+            self.pending.push((addr, val));
+        }
+        self.bus_scl = r.get_bool()?;
+        self.bus_sda = r.get_bool()?;
+        self.sda_out = r.get_bool()?;
+        let data = r.get_bytes()?;
+        if data.len() != EEPROM_SIZE {
+            return Err(r.corrupt());
+        }
+        self.data.copy_from_slice(data);
+
+        Ok(())
+    }
+}
+
+/// SaveKey, a 24LC256 with SDA on pin 3 & SCL on pin 4. Programs bit-bang I2C by driving SCL
+/// through SWCHA and pulling SDA low by making it an output in SWACNT.
+#[derive(Default)]
+pub struct SaveKey {
+    eeprom: Eeprom,
+}
+
+impl SaveKey {
+    pub fn new(storage: Box<dyn EepromStorage>) -> Self {
+        Self {
+            eeprom: Eeprom::new(Some(storage)),
+        }
+    }
+
+    #[inline]
+    pub fn eeprom(&self) -> &Eeprom {
+        &self.eeprom
+    }
+}
+
+impl ControllerPort for SaveKey {
+    fn kind(&self) -> ControllerKind {
+        ControllerKind::SaveKey
+    }
+
+    fn pins(&self) -> u8 {
+        if self.eeprom.sda() {
+            0x0F
+        } else {
+            0x0F & !bits::BIT_D2
+        }
+    }
+
+    fn fire(&self) -> bool {
+        true
+    }
+
+    fn drive_pins(&mut self, out: u8, ddr: u8) {
+        let level = |bit| !bits::tst_bits(ddr, bit) || bits::tst_bits(out, bit);
+        self.eeprom.drive(level(bits::BIT_D3), level(bits::BIT_D2));
+    }
+
+    fn set_input(&mut self, _input: ControllerInput) {}
+
+    fn save_state(&self, w: &mut StateWriter) {
+        self.eeprom.save_state(w);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.eeprom.load_state(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ctrl::{ControllerPorts, Port},
+        riot::{regs, InMemory6532, MemorySegment},
+    };
+    use test_case::test_case;
+
+    /// Bit-bangs the right port the way the usual I2C macros do: the SDA output latch stays low,
+    /// SDA is pulled low by making it an output & released by making it an input.
+    struct Master {
+        pia: InMemory6532,
+    }
+
+    impl Master {
+        fn new(savekey: SaveKey) -> Self {
+            let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+            ports
+                .borrow_mut()
+                .plug_device(Port::Right, Box::new(savekey));
+            let mut pia = InMemory6532::new(ports, Default::default());
+            pia.write(regs::SWCHA, bits::BIT_D3);
+            pia.write(regs::SWACNT, bits::BIT_D3);
+            Self { pia }
+        }
+
+        fn scl(&mut self, high: bool) {
+            self.pia
+                .write(regs::SWCHA, if high { bits::BIT_D3 } else { 0x00 });
+        }
+
+        fn sda(&mut self, high: bool) {
+            let ddr = if high { 0x00 } else { bits::BIT_D2 };
+            self.pia.write(regs::SWACNT, bits::BIT_D3 | ddr);
+        }
+
+        fn read_sda(&self) -> bool {
+            bits::tst_bits(self.pia.read(regs::SWCHA), bits::BIT_D2)
+        }
+
+        fn start(&mut self) {
+            self.sda(true);
+            self.scl(true);
+            self.sda(false);
+            self.scl(false);
+        }
+
+        fn stop(&mut self) {
+            self.sda(false);
+            self.scl(true);
+            self.sda(true);
+        }
+
+        /// Returns whether the chip acknowledged.
+        fn write_byte(&mut self, val: u8) -> bool {
+            (0..8).rev().for_each(|x| {
+                self.sda((val >> x) & 1 == 1);
+                self.scl(true);
+                self.scl(false);
+            });
+            self.sda(true);
+            self.scl(true);
+            let ack = !self.read_sda();
+            self.scl(false);
+            ack
+        }
+
+        fn read_byte(&mut self, ack: bool) -> u8 {
+            self.sda(true);
+            let val = (0..8).fold(0, |acc, _| {
+                self.scl(true);
+                let bit = self.read_sda() as u8;
+                self.scl(false);
+                (acc << 1) | bit
+            });
+            self.sda(!ack);
+            self.scl(true);
+            self.scl(false);
+            self.sda(true);
+            val
+        }
+
+        fn write(&mut self, addr: u16, data: &[u8]) {
+            self.start();
+            assert!(self.write_byte(CONTROL_CODE));
+            assert!(self.write_byte((addr >> 8) as u8));
+            assert!(self.write_byte(addr as u8));
+            data.iter().for_each(|&x| assert!(self.write_byte(x)));
+            self.stop();
+        }
+
+        fn read(&mut self, addr: u16, len: usize) -> Vec<u8> {
+            self.start();
+            assert!(self.write_byte(CONTROL_CODE));
+            assert!(self.write_byte((addr >> 8) as u8));
+            assert!(self.write_byte(addr as u8));
+            self.start();
+            assert!(self.write_byte(CONTROL_CODE | bits::BIT_D0));
+            let ret = (0..len).map(|x| self.read_byte(x + 1 < len)).collect();
+            self.stop();
+            ret
+        }
+    }
+
+    #[test]
+    fn write_then_random_read() {
+        let mut m = Master::new(SaveKey::default());
+
+        m.write(0x1234, &[0x11, 0x22, 0x33]);
+
+        assert_eq!(m.read(0x1234, 3), [0x11, 0x22, 0x33]);
+        assert_eq!(m.read(0x1233, 1), [0xFF]);
+    }
+
+    #[test]
+    fn current_address_read_continues_after_last_byte() {
+        let mut m = Master::new(SaveKey::default());
+        m.write(0x0100, &[1, 2, 3, 4]);
+        assert_eq!(m.read(0x0100, 2), [1, 2]);
+
+        m.start();
+        assert!(m.write_byte(CONTROL_CODE | bits::BIT_D0));
+        assert_eq!(m.read_byte(false), 3);
+        m.stop();
+    }
+
+    #[test]
+    fn page_write_wraps_within_page() {
+        let mut m = Master::new(SaveKey::default());
+
+        m.write(0x007F, &[0xAA, 0xBB]);
+
+        assert_eq!(m.read(0x007F, 1), [0xAA]);
+        assert_eq!(m.read(0x0040, 1), [0xBB]);
+        assert_eq!(m.read(0x0080, 1), [0xFF]);
+    }
+
+    #[test]
+    fn sequential_read_wraps_at_end_of_memory() {
+        let mut m = Master::new(SaveKey::default());
+        m.write(0x0000, &[0x5A]);
+
+        assert_eq!(m.read(0x7FFF, 2), [0xFF, 0x5A]);
+    }
+
+    #[test_case(0b1010_0010; "Other chip select")]
+    #[test_case(0b1101_0000; "Other device code")]
+    fn other_devices_are_not_acknowledged(control: u8) {
+        let mut m = Master::new(SaveKey::default());
+
+        m.start();
+        assert!(!m.write_byte(control));
+        m.stop();
+    }
+
+    #[test]
+    fn write_without_stop_is_discarded() {
+        let mut m = Master::new(SaveKey::default());
+
+        m.start();
+        assert!(m.write_byte(CONTROL_CODE));
+        assert!(m.write_byte(0x00));
+        assert!(m.write_byte(0x10));
+        assert!(m.write_byte(0x42));
+
+        assert_eq!(m.read(0x0010, 1), [0xFF]);
+    }
+
+    #[test]
+    fn storage_is_loaded_and_updated() {
+        let mut data = vec![0x00; EEPROM_SIZE];
+        data[0x0200] = 0x99;
+        let storage = MemoryStorage::new(data);
+        let mut m = Master::new(SaveKey::new(Box::new(storage.clone())));
+
+        assert_eq!(m.read(0x0200, 1), [0x99]);
+        assert_eq!(storage.data()[0x0201], 0x00);
+
+        m.write(0x0201, &[0x77]);
+
+        assert_eq!(storage.data()[0x0200..0x0202], [0x99, 0x77]);
+    }
+
+    #[test]
+    fn storage_of_wrong_size_is_ignored() {
+        let eeprom = Eeprom::new(Some(Box::new(MemoryStorage::new(vec![0x00; 16]))));
+
+        assert_eq!(eeprom.data(), vec![0xFF; EEPROM_SIZE]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn file_storage_roundtrip() {
+        let path = std::env::temp_dir().join("rustella-savekey-test.bin");
+        let _ = std::fs::remove_file(&path);
+        let mut m = Master::new(SaveKey::new(Box::new(FileStorage::new(&path))));
+        m.write(0x4000, &[0xC0, 0xDE]);
+
+        let mut m = Master::new(SaveKey::new(Box::new(FileStorage::new(&path))));
+
+        assert_eq!(m.read(0x4000, 2), [0xC0, 0xDE]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    bits,
+    state::{StateError, StateReader, StateWriter},
+};
+use core::{fmt, str::FromStr};
+
+/// Position of a difficulty switch.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Pro.
+    A,
+    /// Amateur.
+    #[default]
+    B,
+}
+
+/// Switches on the front of the console, read by the program through SWCHB.
+///
+/// Refer:
+/// - https://www.alienbill.com/2600/101/docs/stella.html#pia1.0
+/// - https://problemkaputt.de/2k6specs.htm#consoleswitches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleSwitches {
+    /// Game reset, momentary.
+    pub reset: bool,
+    /// Game select, momentary.
+    pub select: bool,
+    /// TV type, `false` being B&W.
+    pub color: bool,
+    pub left_difficulty: Difficulty,
+    pub right_difficulty: Difficulty,
+}
+
+impl Default for ConsoleSwitches {
+    fn default() -> Self {
+        Self {
+            reset: false,
+            select: false,
+            color: true,
+            left_difficulty: Difficulty::B,
+            right_difficulty: Difficulty::B,
+        }
+    }
+}
+
+impl ConsoleSwitches {
+    /// Value of SWCHB, as far as the switches are concerned.
+    ///
+    /// NOTE: D2, D4 & D5 are not connected and float high.
+    pub fn swchb_pins(&self) -> u8 {
+        [
+            (!self.reset, bits::BIT_D0),
+            (!self.select, bits::BIT_D1),
+            (true, bits::BIT_D2),
+            (self.color, bits::BIT_D3),
+            (true, bits::BIT_D4),
+            (true, bits::BIT_D5),
+            (self.left_difficulty == Difficulty::A, bits::BIT_D6),
+            (self.right_difficulty == Difficulty::A, bits::BIT_D7),
+        ]
+        .iter()
+        .filter(|(high, _)| *high)
+        .fold(0x00, |acc, (_, bit)| acc | bit)
+    }
+
+    pub(crate) fn save_state(&self, w: &mut StateWriter) {
+        w.put_bool(self.reset);
+        w.put_bool(self.select);
+        w.put_bool(self.color);
+        w.put_bool(self.left_difficulty == Difficulty::A);
+        w.put_bool(self.right_difficulty == Difficulty::A);
+    }
+
+    pub(crate) fn load_state(r: &mut StateReader) -> Result<Self, StateError> {
+        let difficulty = |a| if a { Difficulty::A } else { Difficulty::B };
+        Ok(Self {
+            reset: r.get_bool()?,
+            select: r.get_bool()?,
+            color: r.get_bool()?,
+            left_difficulty: difficulty(r.get_bool()?),
+            right_difficulty: difficulty(r.get_bool()?),
+        })
+    }
+}
+
+const NAMES: [&str; 5] = ["reset", "select", "bw", "left-a", "right-a"];
+
+/// Switches away from their default position joined with `+`, `-` when there are none.
+impl fmt::Display for ConsoleSwitches {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flipped = [
+            self.reset,
+            self.select,
+            !self.color,
+            self.left_difficulty == Difficulty::A,
+            self.right_difficulty == Difficulty::A,
+        ];
+        let mut sep = "";
+        for (_, name) in flipped.iter().zip(NAMES).filter(|(&x, _)| x) {
+            write!(f, "{sep}{name}")?;
+            sep = "+";
+        }
+        if sep.is_empty() {
+            write!(f, "-")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ConsoleSwitches {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ret = ConsoleSwitches::default();
+        if s == "-" {
+            return Ok(ret);
+        }
+
+        for name in s.split('+') {
+            match name {
+                "reset" => ret.reset = true,
+                "select" => ret.select = true,
+                "bw" => ret.color = false,
+                "left-a" => ret.left_difficulty = Difficulty::A,
+                "right-a" => ret.right_difficulty = Difficulty::A,
+                _ => return Err(()),
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    fn sw(
+        reset: bool,
+        select: bool,
+        color: bool,
+        left: Difficulty,
+        right: Difficulty,
+    ) -> ConsoleSwitches {
+        ConsoleSwitches {
+            reset,
+            select,
+            color,
+            left_difficulty: left,
+            right_difficulty: right,
+        }
+    }
+
+    #[test_case(sw(false, false, true, Difficulty::B, Difficulty::B), 0b0011_1111)]
+    #[test_case(sw(true, false, true, Difficulty::B, Difficulty::B), 0b0011_1110)]
+    #[test_case(sw(false, true, true, Difficulty::B, Difficulty::B), 0b0011_1101)]
+    #[test_case(sw(false, false, false, Difficulty::B, Difficulty::B), 0b0011_0111)]
+    #[test_case(sw(false, false, true, Difficulty::A, Difficulty::B), 0b0111_1111)]
+    #[test_case(sw(true, true, false, Difficulty::A, Difficulty::A), 0b1111_0100)]
+    fn swchb_pins(switches: ConsoleSwitches, exp: u8) {
+        assert_eq!(switches.swchb_pins(), exp);
+    }
+
+    #[test_case(sw(false, false, true, Difficulty::B, Difficulty::B), "-")]
+    #[test_case(sw(true, false, true, Difficulty::B, Difficulty::B), "reset")]
+    #[test_case(
+        sw(false, true, false, Difficulty::B, Difficulty::A),
+        "select+bw+right-a"
+    )]
+    #[test_case(
+        sw(true, true, false, Difficulty::A, Difficulty::A),
+        "reset+select+bw+left-a+right-a"
+    )]
+    fn text_roundtrip(switches: ConsoleSwitches, text: &str) {
+        assert_eq!(switches.to_string(), text);
+        assert_eq!(text.parse(), Ok(switches));
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case("reset+"; "Trailing separator")]
+    #[test_case("color"; "Unknown switch")]
+    fn text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<ConsoleSwitches>(), Err(()));
+    }
+}
@@ -1,14 +1,23 @@
 #![no_std]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 mod atari;
+pub mod audio;
 pub mod cmn;
 pub mod cpu;
+pub mod ctrl;
+pub mod movie;
+pub mod power_on;
+pub mod rewind;
 pub mod riot;
+pub mod state;
 pub mod tia;
 pub mod timer;
+pub mod tv_detect;
 
-pub use atari::NtscAtari;
+pub use atari::Atari;
 
 pub mod bits {
     pub const BIT_00: u8 = 0x00;
@@ -0,0 +1,618 @@
+//! Input movies: a starting point, every input change with its frame and a checksum per frame.
+//!
+//! Text format, one record per line. Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! rustella-movie 2
+//! start power-on rom <rom checksum, hex> ram <fill> cpu <fill> tia <fill> timer <fill>
+//! start state <save state, hex>
+//! input <frame> <input>
+//! frame <frame> <ram checksum, hex> <framebuffer checksum, hex>
+//! ```
+//!
+//! - Only one `start` record is present. For power-on, the fills are the text form of
+//!   [`PowerOnFill`] for each part of [`PowerOnConfig`], the checksum is [`Atari::rom_checksum`].
+//! - Frames are counted from the start of the movie, the first one is 0.
+//! - `input` records are applied, in order, right before their frame is run.
+//!   `<input>` is the text form of the host's [`MovieInput`] type and runs till the end of the line.
+//!   Movies started from a state open with the inputs the host had set, see [`MovieInput::current`].
+//! - `frame` records follow each run frame, in order, without gaps. Their count is the movie length.
+//! - Checksums are FNV-1a, see [`FrameChecksum`].
+
+use crate::{
+    ctrl::{ConsoleSwitches, ControllerInput, Port},
+    power_on::{PowerOnConfig, PowerOnFill},
+    state::StateError,
+    Atari,
+};
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, fmt::Write, str::FromStr};
+
+pub const MOVIE_MAGIC: &str = "rustella-movie";
+
+/// Version history:
+/// - 1: Initial format.
+/// - 2: Power-on movies record the cartridge and the power-on contents.
+pub const MOVIE_VERSION: u32 = 2;
+
+/// Anything the host feeds into the machine, e.g. controller and console switch changes.
+pub trait MovieInput: Clone + fmt::Display + FromStr {
+    fn apply(&self, atari: &mut Atari);
+
+    /// Inputs that bring the host side of a machine to where `atari` is. A save state does not
+    /// hold what the host feeds in.
+    fn current(_atari: &Atari) -> Vec<Self> {
+        Vec::new()
+    }
+}
+
+/// Everything the player can change on the console.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// `<port> <device> <state>`, e.g. `left joystick up+fire`.
+    Controller(Port, ControllerInput),
+    /// `switches <switches>`, e.g. `switches reset+bw`.
+    Switches(ConsoleSwitches),
+}
+
+impl MovieInput for InputEvent {
+    fn apply(&self, atari: &mut Atari) {
+        match *self {
+            InputEvent::Controller(port, input) => atari.set_input(port, input),
+            InputEvent::Switches(switches) => atari.set_switches(switches),
+        }
+    }
+
+    fn current(atari: &Atari) -> Vec<Self> {
+        let mut ret = Vec::from([InputEvent::Switches(atari.switches())]);
+        [Port::Left, Port::Right].into_iter().for_each(|port| {
+            if let Some(input) = atari.input(port) {
+                ret.push(InputEvent::Controller(port, input));
+            }
+        });
+
+        ret
+    }
+}
+
+impl fmt::Display for InputEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputEvent::Controller(port, input) => write!(f, "{port} {input}"),
+            InputEvent::Switches(switches) => write!(f, "switches {switches}"),
+        }
+    }
+}
+
+impl FromStr for InputEvent {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (head, rest) = s.split_once(' ').ok_or(())?;
+        if head == "switches" {
+            return rest.parse().map(InputEvent::Switches);
+        }
+
+        Ok(InputEvent::Controller(head.parse()?, rest.parse()?))
+    }
+}
+
+/// Identifies the machine state at a frame boundary.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameChecksum {
+    /// RIOT RAM at the end of the frame.
+    pub ram: u32,
+    /// Every pixel the TIA output during the frame.
+    pub video: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MovieStart {
+    /// Freshly created machine with the cartridge loaded. `None` for version 1 movies, which
+    /// record neither, the machine is then taken as is.
+    PowerOn(Option<PowerOnStart>),
+    /// Blob from [`Atari::save_state`].
+    State(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerOnStart {
+    pub power_on: PowerOnConfig,
+    /// See [`Atari::rom_checksum`].
+    pub rom_checksum: u32,
+}
+
+impl PowerOnStart {
+    pub fn of(atari: &Atari) -> Self {
+        Self {
+            power_on: atari.power_on_config(),
+            rom_checksum: atari.rom_checksum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MovieError {
+    /// Line (1 based) is malformed or out of order.
+    Parse(usize),
+    UnsupportedVersion(u32),
+    /// Power-on movie played on a machine that has already run.
+    NotAtPowerOn,
+    /// Power-on movie played with a different cartridge.
+    RomMismatch {
+        expected: u32,
+        found: u32,
+    },
+    /// Power-on movie played with different power-on contents than [`PowerOnStart::power_on`].
+    PowerOnMismatch,
+    State(StateError),
+    /// The program did not start the frame in time, see [`Atari::run_frame`].
+    NoFrame(u64),
+    /// First frame where the playback diverged from the recording.
+    Desync {
+        frame: u64,
+        expected: FrameChecksum,
+        found: FrameChecksum,
+    },
+}
+
+impl From<StateError> for MovieError {
+    fn from(value: StateError) -> Self {
+        MovieError::State(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movie<I> {
+    pub start: MovieStart,
+    /// Ordered by frame.
+    pub inputs: Vec<(u64, I)>,
+    /// One per recorded frame.
+    pub checksums: Vec<FrameChecksum>,
+}
+
+impl<I: MovieInput> Movie<I> {
+    /// Number of recorded frames.
+    pub fn len(&self) -> u64 {
+        self.checksums.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checksums.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut ret = format!("{MOVIE_MAGIC} {MOVIE_VERSION}\n");
+        match &self.start {
+            MovieStart::PowerOn(None) => ret.push_str("start power-on\n"),
+            MovieStart::PowerOn(Some(start)) => {
+                let cfg = &start.power_on;
+                let _ = writeln!(
+                    ret,
+                    "start power-on rom {:08x} ram {} cpu {} tia {} timer {}",
+                    start.rom_checksum,
+                    cfg.riot_ram,
+                    cfg.cpu_registers,
+                    cfg.tia_positions,
+                    cfg.riot_timer
+                );
+            }
+            MovieStart::State(state) => {
+                ret.push_str("start state ");
+                state.iter().for_each(|x| {
+                    let _ = write!(ret, "{x:02x}");
+                });
+                ret.push('\n');
+            }
+        }
+
+        let mut inputs = self.inputs.iter().peekable();
+        for (frame, cs) in self.checksums.iter().enumerate() {
+            let frame = frame as u64;
+            while let Some((f, input)) = inputs.next_if(|(f, _)| *f <= frame) {
+                let _ = writeln!(ret, "input {f} {input}");
+            }
+            let _ = writeln!(ret, "frame {frame} {cs}");
+        }
+        inputs.for_each(|(frame, input)| {
+            let _ = writeln!(ret, "input {frame} {input}");
+        });
+
+        ret
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, MovieError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(n, l)| (n + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+
+        let (n, header) = lines.next().ok_or(MovieError::Parse(1))?;
+        let version = header
+            .strip_prefix(MOVIE_MAGIC)
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .ok_or(MovieError::Parse(n))?;
+        if version == 0 || version > MOVIE_VERSION {
+            return Err(MovieError::UnsupportedVersion(version));
+        }
+
+        let (n, start) = lines.next().ok_or(MovieError::Parse(n + 1))?;
+        let start = match start.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["start", "power-on"] if version == 1 => MovieStart::PowerOn(None),
+            ["start", "power-on", "rom", rom, "ram", ram, "cpu", cpu, "tia", tia, "timer", timer] => {
+                parse_power_on(rom, [ram, cpu, tia, timer])
+                    .map(|x| MovieStart::PowerOn(Some(x)))
+                    .ok_or(MovieError::Parse(n))?
+            }
+            ["start", "state", hex] => {
+                MovieStart::State(parse_hex(hex).ok_or(MovieError::Parse(n))?)
+            }
+            _ => return Err(MovieError::Parse(n)),
+        };
+
+        let mut movie = Movie {
+            start,
+            inputs: Vec::new(),
+            checksums: Vec::new(),
+        };
+        for (n, line) in lines {
+            let mut fields = line.splitn(3, ' ');
+            let kind = fields.next();
+            let frame = fields
+                .next()
+                .and_then(|f| f.parse::<u64>().ok())
+                .ok_or(MovieError::Parse(n))?;
+            let rest = fields.next().unwrap_or_default().trim();
+            match kind {
+                Some("input") if frame >= movie.len() => {
+                    let input = I::from_str(rest).map_err(|_| MovieError::Parse(n))?;
+                    if movie.inputs.last().is_some_and(|(f, _)| *f > frame) {
+                        return Err(MovieError::Parse(n));
+                    }
+                    movie.inputs.push((frame, input));
+                }
+                Some("frame") if frame == movie.len() => {
+                    let cs = match rest.split_whitespace().collect::<Vec<_>>().as_slice() {
+                        [ram, video] => u32::from_str_radix(ram, 16)
+                            .ok()
+                            .zip(u32::from_str_radix(video, 16).ok()),
+                        _ => None,
+                    }
+                    .ok_or(MovieError::Parse(n))?;
+                    movie.checksums.push(FrameChecksum {
+                        ram: cs.0,
+                        video: cs.1,
+                    });
+                }
+                _ => return Err(MovieError::Parse(n)),
+            }
+        }
+
+        Ok(movie)
+    }
+}
+
+fn parse_power_on(rom: &str, fills: [&str; 4]) -> Option<PowerOnStart> {
+    let [riot_ram, cpu_registers, tia_positions, riot_timer] =
+        fills.map(|x| x.parse::<PowerOnFill>().ok());
+
+    Some(PowerOnStart {
+        power_on: PowerOnConfig {
+            riot_ram: riot_ram?,
+            cpu_registers: cpu_registers?,
+            tia_positions: tia_positions?,
+            riot_timer: riot_timer?,
+        },
+        rom_checksum: u32::from_str_radix(rom, 16).ok()?,
+    })
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Drives the machine one frame at a time while recording the inputs and the checksums.
+pub struct MovieRecorder<I> {
+    movie: Movie<I>,
+}
+
+impl<I: MovieInput> MovieRecorder<I> {
+    /// Record from power-on. `atari` must be freshly created, with the cartridge loaded.
+    pub fn power_on(atari: &Atari) -> Self {
+        Self::new(MovieStart::PowerOn(Some(PowerOnStart::of(atari))))
+    }
+
+    /// Record from the current state of `atari`, the host's inputs being recorded at frame 0.
+    pub fn from_state(atari: &Atari) -> Self {
+        let mut ret = Self::new(MovieStart::State(atari.save_state()));
+        ret.movie.inputs = I::current(atari).into_iter().map(|x| (0, x)).collect();
+        ret
+    }
+
+    fn new(start: MovieStart) -> Self {
+        Self {
+            movie: Movie {
+                start,
+                inputs: Vec::new(),
+                checksums: Vec::new(),
+            },
+        }
+    }
+
+    /// Frame that the next call to [`MovieRecorder::run_frame`] will run.
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.movie.len()
+    }
+
+    /// Applies `input` and records it against the upcoming frame.
+    pub fn input(&mut self, atari: &mut Atari, input: I) {
+        input.apply(atari);
+        self.movie.inputs.push((self.frame(), input));
+    }
+
+    /// Returns `false` if the program did not start a frame in time, see [`Atari::run_frame`].
+    /// The frame is recorded either way, its playback then fails with [`MovieError::NoFrame`].
+    pub fn run_frame(&mut self, atari: &mut Atari) -> bool {
+        let ret = atari.run_frame();
+        self.movie.checksums.push(atari.frame_checksum());
+        ret
+    }
+
+    pub fn finish(self) -> Movie<I> {
+        self.movie
+    }
+}
+
+/// Replays a [`Movie`], checking every frame against the recording.
+pub struct MoviePlayer<I> {
+    movie: Movie<I>,
+    frame: u64,
+    next_input: usize,
+}
+
+impl<I: MovieInput> MoviePlayer<I> {
+    /// Puts `atari` at the start of the movie.
+    /// For [`MovieStart::PowerOn`], `atari` must be freshly created with the cartridge & the
+    /// power-on contents the movie was recorded with.
+    pub fn new(movie: Movie<I>, atari: &mut Atari) -> Result<Self, MovieError> {
+        match &movie.start {
+            MovieStart::PowerOn(start) => {
+                if atari.cpu_state().cycles() != 0 || atari.frame_counter() != 0 {
+                    return Err(MovieError::NotAtPowerOn);
+                }
+                if let Some(start) = start {
+                    if start.rom_checksum != atari.rom_checksum() {
+                        return Err(MovieError::RomMismatch {
+                            expected: start.rom_checksum,
+                            found: atari.rom_checksum(),
+                        });
+                    }
+                    if start.power_on != atari.power_on_config() {
+                        return Err(MovieError::PowerOnMismatch);
+                    }
+                }
+            }
+            MovieStart::State(state) => atari.load_state(state)?,
+        }
+
+        Ok(Self {
+            movie,
+            frame: 0,
+            next_input: 0,
+        })
+    }
+
+    /// Frame that the next call to [`MoviePlayer::step`] will run.
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.movie.len()
+    }
+
+    /// Applies the inputs for the next frame, runs it and checks it against the recording.
+    /// Returns `false` once the movie is over.
+    pub fn step(&mut self, atari: &mut Atari) -> Result<bool, MovieError> {
+        if self.is_finished() {
+            return Ok(false);
+        }
+
+        while let Some((_, input)) = self
+            .movie
+            .inputs
+            .get(self.next_input)
+            .filter(|(f, _)| *f == self.frame)
+        {
+            input.apply(atari);
+            self.next_input += 1;
+        }
+
+        let started = atari.run_frame();
+        let frame = self.frame;
+        self.frame += 1;
+        if !started {
+            return Err(MovieError::NoFrame(frame));
+        }
+
+        let expected = self.movie.checksums[frame as usize];
+        let found = atari.frame_checksum();
+        if expected != found {
+            return Err(MovieError::Desync {
+                frame,
+                expected,
+                found,
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Plays the rest of the movie, stopping at the first desync.
+    pub fn play(&mut self, atari: &mut Atari) -> Result<(), MovieError> {
+        while self.step(atari)? {}
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for FrameChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:08x} {:08x}", self.ram, self.video)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+    use test_case::test_case;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Pins(u8);
+
+    impl fmt::Display for Pins {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "pins {:02x}", self.0)
+        }
+    }
+
+    impl FromStr for Pins {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            s.strip_prefix("pins ")
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .map(Pins)
+                .ok_or(())
+        }
+    }
+
+    impl MovieInput for Pins {
+        fn apply(&self, _: &mut Atari) {}
+    }
+
+    fn checksum(x: u32) -> FrameChecksum {
+        FrameChecksum { ram: x, video: !x }
+    }
+
+    #[test_case(MovieStart::PowerOn(Some(PowerOnStart {
+        power_on: PowerOnConfig::random(7),
+        rom_checksum: 0x1234_ABCD,
+    })); "Power on")]
+    #[test_case(MovieStart::State(vec![0x00, 0x7F, 0xFF]); "State")]
+    fn text_roundtrip(start: MovieStart) {
+        let movie = Movie {
+            start,
+            inputs: vec![
+                (0, Pins(0xFF)),
+                (0, Pins(0x7F)),
+                (2, Pins(0xBF)),
+                (3, Pins(0x01)),
+            ],
+            checksums: vec![checksum(1), checksum(2), checksum(3)],
+        };
+
+        let text = movie.to_text();
+        assert_eq!(Movie::<Pins>::from_text(&text), Ok(movie));
+    }
+
+    #[test]
+    fn text_layout() {
+        let movie = Movie {
+            start: MovieStart::State(vec![0xAB, 0x01]),
+            inputs: vec![(1, Pins(0x7F))],
+            checksums: vec![checksum(0x1234), checksum(0xFFFF_0000)],
+        };
+
+        assert_eq!(
+            movie.to_text(),
+            "rustella-movie 2\n\
+             start state ab01\n\
+             frame 0 00001234 ffffedcb\n\
+             input 1 pins 7f\n\
+             frame 1 ffff0000 0000ffff\n"
+        );
+    }
+
+    #[test]
+    fn power_on_text_layout() {
+        let movie = Movie::<Pins> {
+            start: MovieStart::PowerOn(Some(PowerOnStart {
+                power_on: PowerOnConfig::default(),
+                rom_checksum: 0xC0FFEE,
+            })),
+            inputs: vec![],
+            checksums: vec![],
+        };
+
+        assert_eq!(
+            movie.to_text(),
+            "rustella-movie 2\n\
+             start power-on rom 00c0ffee ram pattern:deadbeefbaadf00d cpu zero tia zero timer zero\n"
+        );
+    }
+
+    #[test]
+    fn text_ignores_comments_and_blanks() {
+        let text = "# recorded by hand\n\nrustella-movie 1\nstart power-on\n  # frame 0\nframe 0 00000001 fffffffe\n";
+
+        let movie = Movie::<Pins>::from_text(text).unwrap();
+        assert_eq!(movie.start, MovieStart::PowerOn(None));
+        assert_eq!(movie.checksums, vec![checksum(1)]);
+    }
+
+    #[test_case("", MovieError::Parse(1); "Empty")]
+    #[test_case("rustella-movie x\n", MovieError::Parse(1); "Bad version")]
+    #[test_case("rustella-movie 3\n", MovieError::UnsupportedVersion(3); "Future version")]
+    #[test_case("rustella-movie 1\n", MovieError::Parse(2); "No start")]
+    #[test_case("rustella-movie 1\nstart state abc\n", MovieError::Parse(2); "Odd hex")]
+    #[test_case("rustella-movie 1\nstart power-on\nframe 1 0 0\n", MovieError::Parse(3); "Frame gap")]
+    #[test_case("rustella-movie 1\nstart power-on\nframe 0 0\n", MovieError::Parse(3); "Missing checksum")]
+    #[test_case("rustella-movie 1\nstart power-on\ninput 0 pins zz\n", MovieError::Parse(3); "Bad input")]
+    #[test_case("rustella-movie 1\nstart power-on\ninput 2 pins 01\ninput 1 pins 01\n", MovieError::Parse(4); "Inputs out of order")]
+    #[test_case("rustella-movie 1\nstart power-on\nframe 0 0 0\ninput 0 pins 01\n", MovieError::Parse(4); "Input for past frame")]
+    #[test_case("rustella-movie 1\nstart power-on\nfoo 0\n", MovieError::Parse(3); "Unknown record")]
+    #[test_case("rustella-movie 2\nstart power-on\n", MovieError::Parse(2); "Power on without header")]
+    #[test_case("rustella-movie 2\nstart power-on rom xyz ram zero cpu zero tia zero timer zero\n", MovieError::Parse(2); "Bad ROM checksum")]
+    #[test_case("rustella-movie 2\nstart power-on rom 0 ram zero cpu one tia zero timer zero\n", MovieError::Parse(2); "Bad fill")]
+    fn text_rejects_malformed(text: &str, err: MovieError) {
+        assert_eq!(Movie::<Pins>::from_text(text), Err(err));
+    }
+
+    #[test_case("left joystick up+fire"; "Left joystick")]
+    #[test_case("right joystick -"; "Right joystick released")]
+    #[test_case("right paddles 0.5,0.25 fire-a"; "Paddles")]
+    #[test_case("left keypad 1+#"; "Keypad")]
+    #[test_case("right driving 3 fire"; "Driving controller")]
+    #[test_case("left genesis up+c"; "Genesis pad")]
+    #[test_case("switches select+left-a"; "Switches")]
+    #[test_case("switches -"; "Switches at default")]
+    fn input_event_text_roundtrip(text: &str) {
+        let event: InputEvent = text.parse().unwrap();
+        assert_eq!(event.to_string(), text);
+    }
+
+    #[test_case("middle joystick up"; "Bad port")]
+    #[test_case("left keyboard up"; "Bad device")]
+    #[test_case("left"; "Missing input")]
+    #[test_case("switches reset+game"; "Bad switch")]
+    fn input_event_text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<InputEvent>(), Err(()));
+    }
+
+    #[test]
+    fn checksum_display() {
+        assert_eq!(checksum(0xA).to_string(), "0000000a fffffff5");
+    }
+}
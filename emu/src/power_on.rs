@@ -0,0 +1,190 @@
+use core::{fmt, str::FromStr};
+
+/// Contents of RAM and registers before the program first writes them.
+/// Real consoles power on with garbage, programs that forget to initialize behave differently.
+pub const DEFAULT_PATTERN: u64 = 0xdeadbeef_baadf00d;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PowerOnFill {
+    #[default]
+    Zero,
+    /// Big endian bytes of the pattern, repeated.
+    Pattern(u64),
+    /// Pseudo-random bytes, the same for a given seed.
+    Random(u64),
+}
+
+impl PowerOnFill {
+    pub fn fill(&self, data: &mut [u8]) {
+        match *self {
+            PowerOnFill::Zero => data.fill(0x00),
+            PowerOnFill::Pattern(pattern) => data
+                .iter_mut()
+                .zip(pattern.to_be_bytes().iter().cycle())
+                .for_each(|(x, &p)| *x = p),
+            PowerOnFill::Random(seed) => {
+                let mut rng = SplitMix64(seed);
+                data.chunks_mut(8).for_each(|chunk| {
+                    let bytes = rng.next_u64().to_le_bytes();
+                    chunk.copy_from_slice(&bytes[..chunk.len()]);
+                });
+            }
+        }
+    }
+
+    pub fn bytes<const N: usize>(&self) -> [u8; N] {
+        let mut ret = [0x00; N];
+        self.fill(&mut ret);
+        ret
+    }
+}
+
+/// `zero`, `pattern:<hex>` or `random:<hex seed>`.
+impl fmt::Display for PowerOnFill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PowerOnFill::Zero => write!(f, "zero"),
+            PowerOnFill::Pattern(pattern) => write!(f, "pattern:{pattern:016x}"),
+            PowerOnFill::Random(seed) => write!(f, "random:{seed:016x}"),
+        }
+    }
+}
+
+impl FromStr for PowerOnFill {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "zero" {
+            return Ok(PowerOnFill::Zero);
+        }
+
+        let (kind, val) = s.split_once(':').ok_or(())?;
+        let val = u64::from_str_radix(val, 16).map_err(|_| ())?;
+        match kind {
+            "pattern" => Ok(PowerOnFill::Pattern(val)),
+            "random" => Ok(PowerOnFill::Random(val)),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What each part of the machine holds at power-on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerOnConfig {
+    /// RIOT RAM, $80-$FF. The rest of the address space is ROM or registers.
+    pub riot_ram: PowerOnFill,
+    /// A, X, Y, S & P.
+    pub cpu_registers: PowerOnFill,
+    /// Horizontal positions of the TIA objects.
+    pub tia_positions: PowerOnFill,
+    /// INTIM. A non zero count runs the timer from power-on, at the 1024 clock interval.
+    pub riot_timer: PowerOnFill,
+}
+
+impl Default for PowerOnConfig {
+    fn default() -> Self {
+        Self {
+            riot_ram: PowerOnFill::Pattern(DEFAULT_PATTERN),
+            cpu_registers: PowerOnFill::Zero,
+            tia_positions: PowerOnFill::Zero,
+            riot_timer: PowerOnFill::Zero,
+        }
+    }
+}
+
+impl PowerOnConfig {
+    pub fn zero() -> Self {
+        Self {
+            riot_ram: PowerOnFill::Zero,
+            cpu_registers: PowerOnFill::Zero,
+            tia_positions: PowerOnFill::Zero,
+            riot_timer: PowerOnFill::Zero,
+        }
+    }
+
+    /// Everything random, each part with its own seed derived from `seed`.
+    pub fn random(seed: u64) -> Self {
+        let mut rng = SplitMix64(seed);
+        Self {
+            riot_ram: PowerOnFill::Random(rng.next_u64()),
+            cpu_registers: PowerOnFill::Random(rng.next_u64()),
+            tia_positions: PowerOnFill::Random(rng.next_u64()),
+            riot_timer: PowerOnFill::Random(rng.next_u64()),
+        }
+    }
+}
+
+/// Refer: https://prng.di.unimi.it/splitmix64.c
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    #[test_case(PowerOnFill::Zero, [0x00; 10])]
+    #[test_case(PowerOnFill::Pattern(DEFAULT_PATTERN), [0xDE, 0xAD, 0xBE, 0xEF, 0xBA, 0xAD, 0xF0, 0x0D, 0xDE, 0xAD])]
+    fn fixed_fills(fill: PowerOnFill, exp: [u8; 10]) {
+        assert_eq!(fill.bytes::<10>(), exp);
+    }
+
+    #[test]
+    fn random_fill_depends_only_on_seed() {
+        let a = PowerOnFill::Random(42).bytes::<13>();
+
+        assert_eq!(PowerOnFill::Random(42).bytes::<13>(), a);
+        assert_ne!(PowerOnFill::Random(43).bytes::<13>(), a);
+        assert_eq!(PowerOnFill::Random(42).bytes::<5>(), a[..5]);
+        assert!(a.iter().any(|&x| x != a[0]));
+    }
+
+    #[test_case(PowerOnFill::Zero, "zero")]
+    #[test_case(PowerOnFill::Pattern(DEFAULT_PATTERN), "pattern:deadbeefbaadf00d")]
+    #[test_case(PowerOnFill::Random(0x2600), "random:0000000000002600")]
+    fn fill_text_roundtrip(fill: PowerOnFill, text: &str) {
+        assert_eq!(fill.to_string(), text);
+        assert_eq!(text.parse(), Ok(fill));
+    }
+
+    #[test_case("one"; "Unknown kind")]
+    #[test_case("random:xyz"; "Bad seed")]
+    #[test_case("pattern"; "Missing value")]
+    fn fill_text_rejects_malformed(text: &str) {
+        assert_eq!(text.parse::<PowerOnFill>(), Err(()));
+    }
+
+    #[test]
+    fn splitmix64_reference_values() {
+        let mut rng = SplitMix64(1234567);
+
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+    }
+
+    #[test]
+    fn random_config_uses_distinct_seeds() {
+        let cfg = PowerOnConfig::random(0);
+        let fills = [
+            cfg.riot_ram,
+            cfg.cpu_registers,
+            cfg.tia_positions,
+            cfg.riot_timer,
+        ];
+
+        (0..fills.len()).for_each(|i| {
+            assert!(matches!(fills[i], PowerOnFill::Random(_)));
+            assert!(!fills[i + 1..].contains(&fills[i]));
+        });
+    }
+}
@@ -0,0 +1,341 @@
+use crate::state::{self, StateError};
+use alloc::{collections::VecDeque, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewindConfig {
+    /// Capture a snapshot every these many frames.
+    pub interval_frames: u64,
+    /// Upper bound for the memory used by the snapshots. The oldest snapshots are dropped first.
+    pub budget_bytes: usize,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            interval_frames: 10,
+            budget_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Bounded ring buffer of periodic save states.
+///
+/// - The newest snapshot is kept as is.
+/// - Every older snapshot is stored as the RLE of its XOR against the next newer one.
+///   Consecutive save states differ in a handful of bytes so these compress very well.
+/// - Dropping the oldest snapshot needs no re-encoding, as nothing depends on it.
+#[derive(Debug, Default)]
+pub struct RewindBuffer {
+    cfg: RewindConfig,
+    head: Option<Snapshot>,
+    /// Oldest first.
+    deltas: VecDeque<Snapshot>,
+    memory_used: usize,
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    frame: u64,
+    /// Save state for the head, RLE of the XOR against the next newer snapshot otherwise.
+    data: Vec<u8>,
+    /// Head, or a delta whose newer snapshot had a different size. `data` is then the RLE of the full state.
+    full: bool,
+}
+
+impl RewindBuffer {
+    pub fn new(cfg: RewindConfig) -> Self {
+        Self {
+            cfg,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn config(&self) -> &RewindConfig {
+        &self.cfg
+    }
+
+    pub fn set_interval_frames(&mut self, frames: u64) {
+        self.cfg.interval_frames = frames;
+    }
+
+    pub fn set_budget_bytes(&mut self, bytes: usize) {
+        self.cfg.budget_bytes = bytes;
+        self.enforce_budget();
+    }
+
+    /// Bytes held by all the snapshots.
+    #[inline]
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
+    }
+
+    /// Number of snapshots held.
+    pub fn len(&self) -> usize {
+        self.deltas.len() + self.head.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Frames of the oldest and the newest snapshots.
+    pub fn frame_range(&self) -> Option<(u64, u64)> {
+        let head = self.head.as_ref()?.frame;
+        let oldest = self.deltas.front().map_or(head, |s| s.frame);
+        Some((oldest, head))
+    }
+
+    /// `true` if a snapshot for `frame` would be captured.
+    pub fn is_due(&self, frame: u64) -> bool {
+        match &self.head {
+            None => true,
+            Some(head) => frame >= head.frame + self.cfg.interval_frames.max(1),
+        }
+    }
+
+    /// Stores `state` taken at `frame`, if it is due.
+    pub fn capture(&mut self, frame: u64, state: Vec<u8>) -> bool {
+        if !self.is_due(frame) {
+            return false;
+        }
+
+        if let Some(prev) = self.head.take() {
+            let delta = if prev.data.len() == state.len() {
+                let diff: Vec<_> = prev
+                    .data
+                    .iter()
+                    .zip(state.iter())
+                    .map(|(a, b)| a ^ b)
+                    .collect();
+                Snapshot {
+                    frame: prev.frame,
+                    data: state::rle_encode(&diff),
+                    full: false,
+                }
+            } else {
+                Snapshot {
+                    frame: prev.frame,
+                    data: state::rle_encode(&prev.data),
+                    full: true,
+                }
+            };
+            self.memory_used = self.memory_used - prev.data.len() + delta.data.len();
+            self.deltas.push_back(delta);
+        }
+
+        self.memory_used += state.len();
+        self.head = Some(Snapshot {
+            frame,
+            data: state,
+            full: true,
+        });
+        self.enforce_budget();
+
+        true
+    }
+
+    /// Drops every snapshot newer than `frame` and returns the newest remaining one.
+    /// Falls back to the oldest snapshot if none is old enough. `None` only when empty.
+    /// A snapshot that fails to decode is left in place along with everything older.
+    pub fn rewind_to(&mut self, frame: u64) -> Result<Option<(u64, Vec<u8>)>, StateError> {
+        let Some(mut head) = self.head.take() else {
+            return Ok(None);
+        };
+
+        while head.frame > frame {
+            let Some(delta) = self.deltas.pop_back() else {
+                break;
+            };
+
+            let data = match Self::decode(&delta, &head) {
+                Ok(x) => x,
+                Err(e) => {
+                    self.deltas.push_back(delta);
+                    self.head = Some(head);
+                    return Err(e);
+                }
+            };
+
+            self.memory_used = self.memory_used - head.data.len() - delta.data.len() + data.len();
+            head = Snapshot {
+                frame: delta.frame,
+                data,
+                full: true,
+            };
+        }
+
+        let ret = (head.frame, head.data.clone());
+        self.head = Some(head);
+
+        Ok(Some(ret))
+    }
+
+    pub fn clear(&mut self) {
+        self.head = None;
+        self.deltas.clear();
+        self.memory_used = 0;
+    }
+
+    /// Full state of `delta`, given the state of the next newer snapshot.
+    fn decode(delta: &Snapshot, newer: &Snapshot) -> Result<Vec<u8>, StateError> {
+        const CORRUPT: StateError = StateError::Corrupt(*b"RWND");
+
+        let data = state::rle_decode(&delta.data).ok_or(CORRUPT)?;
+        if delta.full {
+            return Ok(data);
+        }
+        if data.len() != newer.data.len() {
+            return Err(CORRUPT);
+        }
+
+        Ok(data
+            .iter()
+            .zip(newer.data.iter())
+            .map(|(a, b)| a ^ b)
+            .collect())
+    }
+
+    fn enforce_budget(&mut self) {
+        while self.memory_used > self.cfg.budget_bytes {
+            match self.deltas.pop_front() {
+                Some(oldest) => self.memory_used -= oldest.data.len(),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use test_case::test_case;
+
+    fn state_for(frame: u64) -> Vec<u8> {
+        let mut s = vec![0xAAu8; 256];
+        s[0..8].copy_from_slice(&frame.to_le_bytes());
+        s[100] = frame as u8;
+        s
+    }
+
+    fn every_frame() -> RewindConfig {
+        RewindConfig {
+            interval_frames: 1,
+            ..Default::default()
+        }
+    }
+
+    fn filled(cfg: RewindConfig, frames: u64) -> RewindBuffer {
+        let mut rb = RewindBuffer::new(cfg);
+        (0..frames).for_each(|f| {
+            rb.capture(f, state_for(f));
+        });
+        rb
+    }
+
+    #[test_case(1, 10, 10)]
+    #[test_case(3, 10, 4)]
+    #[test_case(5, 11, 3)]
+    fn capture_honours_interval(interval: u64, frames: u64, expected: usize) {
+        let cfg = RewindConfig {
+            interval_frames: interval,
+            ..Default::default()
+        };
+        let rb = filled(cfg, frames);
+
+        assert_eq!(rb.len(), expected);
+        assert_eq!(
+            rb.frame_range(),
+            Some((0, (expected as u64 - 1) * interval))
+        );
+    }
+
+    #[test]
+    fn deltas_are_compressed() {
+        let rb = filled(every_frame(), 100);
+
+        assert_eq!(rb.len(), 100);
+        assert!(rb.memory_used() < 256 + 99 * 16, "{}", rb.memory_used());
+    }
+
+    #[test_case(99, 99)]
+    #[test_case(50, 50)]
+    #[test_case(0, 0)]
+    fn rewind_restores_exact_snapshot(target: u64, expected: u64) {
+        let mut rb = filled(every_frame(), 100);
+
+        let (frame, data) = rb.rewind_to(target).unwrap().unwrap();
+        assert_eq!(frame, expected);
+        assert_eq!(data, state_for(expected));
+        assert_eq!(rb.frame_range(), Some((0, expected)));
+    }
+
+    #[test]
+    fn rewind_picks_snapshot_at_or_before_target() {
+        let cfg = RewindConfig {
+            interval_frames: 10,
+            ..Default::default()
+        };
+        let mut rb = filled(cfg, 100);
+
+        let (frame, data) = rb.rewind_to(45).unwrap().unwrap();
+        assert_eq!(frame, 40);
+        assert_eq!(data, state_for(40));
+        assert!(!rb.capture(41, state_for(41)));
+        assert!(rb.capture(50, state_for(50)));
+        assert_eq!(rb.rewind_to(45).unwrap().unwrap(), (40, state_for(40)));
+    }
+
+    #[test]
+    fn budget_drops_oldest() {
+        let cfg = RewindConfig {
+            interval_frames: 1,
+            budget_bytes: 600,
+        };
+        let mut rb = filled(cfg, 100);
+
+        assert!(rb.memory_used() <= 600);
+        let (oldest, newest) = rb.frame_range().unwrap();
+        assert!(oldest > 0);
+        assert_eq!(newest, 99);
+        assert_eq!(
+            rb.rewind_to(0).unwrap().unwrap(),
+            (oldest, state_for(oldest))
+        );
+
+        rb.set_budget_bytes(0);
+        assert_eq!(rb.len(), 1);
+    }
+
+    #[test]
+    fn size_change_is_stored_in_full() {
+        let mut rb = RewindBuffer::new(every_frame());
+        rb.capture(0, vec![0x01; 10]);
+        rb.capture(1, vec![0x02; 20]);
+        rb.capture(2, vec![0x03; 20]);
+
+        assert_eq!(rb.rewind_to(1).unwrap().unwrap(), (1, vec![0x02; 20]));
+        assert_eq!(rb.rewind_to(0).unwrap().unwrap(), (0, vec![0x01; 10]));
+    }
+
+    #[test]
+    fn empty_buffer() {
+        let mut rb = RewindBuffer::new(RewindConfig::default());
+
+        assert!(rb.is_empty());
+        assert_eq!(rb.rewind_to(0), Ok(None));
+        assert_eq!(rb.frame_range(), None);
+    }
+
+    #[test_case(vec![0x80]; "Malformed RLE")]
+    #[test_case(state::rle_encode(&[0x00; 10]); "Wrong size")]
+    fn corrupt_snapshot_is_reported(data: Vec<u8>) {
+        let mut rb = filled(every_frame(), 10);
+        rb.deltas[5].data = data;
+
+        assert_eq!(rb.rewind_to(0), Err(StateError::Corrupt(*b"RWND")));
+        assert_eq!(rb.frame_range(), Some((0, 6)));
+        assert_eq!(rb.rewind_to(7).unwrap().unwrap(), (6, state_for(6)));
+    }
+}
@@ -5,6 +5,9 @@ pub trait MemorySegment {
     fn write(&mut self, addr: usize, val: u8);
 }
 
+pub const RAM_MIN_ADDRESS: usize = 0x0080;
+pub const RAM_MAX_ADDRESS: usize = 0x00FF;
+
 pub const IOT_MIN_ADDRESS: usize = 0x0280;
 pub const IOT_MAX_ADDRESS: usize = 0x029F;
 
@@ -13,12 +16,13 @@ pub mod regs {
     pub const SWCHA: usize = 0x0280;
     /// Port A DDR, 0= input, 1=output
     pub const SWACNT: usize = 0x0281;
-    /// Port B; console switches (read only)
+    /// Port B; console switches (read or write)
     pub const SWCHB: usize = 0x0282;
-    /// Port B DDR (hardwired as input)
+    /// Port B DDR, 0= input, 1=output
     pub const SWBCNT: usize = 0x0283;
-    /// Timer output (read only)
+    /// Timer output (read only). Reading clears the timer interrupt flag.
     pub const INTIM: usize = 0x0284;
+    /// Interrupt flags, D7 timer & D6 PA7 edge (read only). Reading clears the PA7 flag.
     pub const TIMINT: usize = 0x0285;
     pub const RX0286: usize = 0x0286;
     pub const RX0287: usize = 0x0287;
@@ -54,37 +58,37 @@ pub mod regs {
     #[rustfmt::skip]
     pub static IMPLEMENTED_REGISTERS: &[(bool, bool, &str, u8); super::IOT_MAX_ADDRESS - super::IOT_MIN_ADDRESS + 1] = &[
         // R      W     Name     Supported Mask
-        (false, false, "SWCHA",  0b_0000_0000),  // 0x280	SWCHA	Port A; input or output (read or write)
-        (false, false, "SWACNT", 0b_0000_0000),  // 0x281	SWACNT	Port A DDR, 0= input, 1=output
-        (true,  false, "SWCHB",  0b_0000_0000),  // 0x282	SWCHB	Port B; console switches (read only)
-        (false, false, "SWBCNT", 0b_0000_0000),  // 0x283	SWBCNT	Port B DDR (hardwired as input)
-        (true , false, "INTIM",  0b_0000_0000),  // 0x284	INTIM	Timer output (read only)
-        (false, false, "TIMINT", 0b_0000_0000),  // 0x285   TIMINT  ???
-        (false, false, "RX0286", 0b_0000_0000),  // 
-        (false, false, "RX0287", 0b_0000_0000),  // 
-        (false, false, "RX0288", 0b_0000_0000),  // 
-        (false, false, "RX0289", 0b_0000_0000),  // 
-        (false, false, "RX028A", 0b_0000_0000),  // 
-        (false, false, "RX028B", 0b_0000_0000),  // 
-        (false, false, "RX028C", 0b_0000_0000),  // 
-        (false, false, "RX028D", 0b_0000_0000),  // 
-        (false, false, "RX028E", 0b_0000_0000),  // 
-        (false, false, "RX028F", 0b_0000_0000),  // 
-        (false, false, "RX0290", 0b_0000_0000),  // 
-        (false, false, "RX0291", 0b_0000_0000),  // 
-        (false, false, "RX0292", 0b_0000_0000),  // 
-        (false, false, "RX0293", 0b_0000_0000),  // 
-        (false, true , "TIM1T",  0b_1111_1111),  // 0x294	TIM1T	set 1 clock interval (838 nsec/interval)
-        (false, true , "TIM8T",  0b_1111_1111),  // 0x295	TIM8T	set 8 clock interval (6.7 usec/interval)
-        (false, true , "TIM64T", 0b_1111_1111),  // 0x296	TIM64T	set 64 clock interval (53.6 usec/interval)
-        (false, true , "T1024T", 0b_1111_1111),  // 0x297	T1024T	set 1024 clock interval (858.2 usec/interval)
-        (false, false, "RX0298", 0b_0000_0000),  // 
-        (false, false, "RX0299", 0b_0000_0000),  // 
-        (false, false, "RX029A", 0b_0000_0000),  // 
-        (false, false, "RX029B", 0b_0000_0000),  // 
-        (false, false, "RX029C", 0b_0000_0000),  // 
-        (false, false, "RX029D", 0b_0000_0000),  // 
-        (false, false, "RX029E", 0b_0000_0000),  // 
-        (false, false, "RX029F", 0b_0000_0000),  // 
+        (true , true , "SWCHA",  0b_1111_1111),  // 0x280	SWCHA	Port A; input or output (read or write)
+        (true , true , "SWACNT", 0b_1111_1111),  // 0x281	SWACNT	Port A DDR, 0= input, 1=output
+        (true , true , "SWCHB",  0b_1111_1111),  // 0x282	SWCHB	Port B; console switches (read or write)
+        (true , true , "SWBCNT", 0b_1111_1111),  // 0x283	SWBCNT	Port B DDR, 0= input, 1=output
+        (true , true , "INTIM",  0b_1111_1111),  // 0x284	INTIM	Timer output, write PA7 negative edge detect
+        (true , true , "TIMINT", 0b_1111_1111),  // 0x285	TIMINT	Interrupt flags, write PA7 positive edge detect
+        (true , true , "RX0286", 0b_1111_1111),  // 0x286	read INTIM, write PA7 negative edge detect with IRQ
+        (true , true , "RX0287", 0b_1111_1111),  // 0x287	read TIMINT, write PA7 positive edge detect with IRQ
+        (true , true , "RX0288", 0b_1111_1111),  // 0x288	mirror of SWCHA
+        (true , true , "RX0289", 0b_1111_1111),  // 0x289	mirror of SWACNT
+        (true , true , "RX028A", 0b_1111_1111),  // 0x28A	mirror of SWCHB
+        (true , true , "RX028B", 0b_1111_1111),  // 0x28B	mirror of SWBCNT
+        (true , true , "RX028C", 0b_1111_1111),  // 0x28C	read INTIM, write PA7 negative edge detect
+        (true , true , "RX028D", 0b_1111_1111),  // 0x28D	read TIMINT, write PA7 positive edge detect
+        (true , true , "RX028E", 0b_1111_1111),  // 0x28E	read INTIM, write PA7 negative edge detect with IRQ
+        (true , true , "RX028F", 0b_1111_1111),  // 0x28F	read TIMINT, write PA7 positive edge detect with IRQ
+        (true , true , "RX0290", 0b_1111_1111),  // 0x290	mirror of SWCHA
+        (true , true , "RX0291", 0b_1111_1111),  // 0x291	mirror of SWACNT
+        (true , true , "RX0292", 0b_1111_1111),  // 0x292	mirror of SWCHB
+        (true , true , "RX0293", 0b_1111_1111),  // 0x293	mirror of SWBCNT
+        (true , true , "TIM1T",  0b_1111_1111),  // 0x294	TIM1T	set 1 clock interval (838 nsec/interval)
+        (true , true , "TIM8T",  0b_1111_1111),  // 0x295	TIM8T	set 8 clock interval (6.7 usec/interval)
+        (true , true , "TIM64T", 0b_1111_1111),  // 0x296	TIM64T	set 64 clock interval (53.6 usec/interval)
+        (true , true , "T1024T", 0b_1111_1111),  // 0x297	T1024T	set 1024 clock interval (858.2 usec/interval)
+        (true , true , "RX0298", 0b_1111_1111),  // 0x298	mirror of SWCHA
+        (true , true , "RX0299", 0b_1111_1111),  // 0x299	mirror of SWACNT
+        (true , true , "RX029A", 0b_1111_1111),  // 0x29A	mirror of SWCHB
+        (true , true , "RX029B", 0b_1111_1111),  // 0x29B	mirror of SWBCNT
+        (true , true , "RX029C", 0b_1111_1111),  // 0x29C	read INTIM, write TIM1T with IRQ
+        (true , true , "RX029D", 0b_1111_1111),  // 0x29D	read TIMINT, write TIM8T with IRQ
+        (true , true , "RX029E", 0b_1111_1111),  // 0x29E	read INTIM, write TIM64T with IRQ
+        (true , true , "RX029F", 0b_1111_1111),  // 0x29F	read TIMINT, write T1024T with IRQ
     ];
 }
@@ -1,21 +1,30 @@
 use crate::{
     cmn::*,
-    riot::{cmn, mmaps, IOT_MAX_ADDRESS, IOT_MIN_ADDRESS},
+    power_on::{PowerOnFill, DEFAULT_PATTERN},
+    riot::{cmn, mmaps, IOT_MAX_ADDRESS, IOT_MIN_ADDRESS, RAM_MAX_ADDRESS, RAM_MIN_ADDRESS},
+    state::{self, SaveState, StateError, StateReader, StateWriter},
     tia,
 };
-use alloc::rc::Rc;
-use core::{cell::RefCell, mem};
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::RefCell;
 
 pub struct Memory {
     data: [u8; cmn::TOTAL_MEMORY_SIZE],
+    fill: PowerOnFill,
     mmap: fn(LoHi) -> usize,
     tia: Option<Rc<RefCell<dyn cmn::MemorySegment>>>,
     iot: Option<Rc<RefCell<dyn cmn::MemorySegment>>>,
 }
 
 impl Memory {
+    /// `init` fills memory with [`DEFAULT_PATTERN`], with zeros otherwise.
     pub fn new(init: bool) -> Self {
-        Self::new_with_rom(&[], Default::default(), mmaps::mm_6502, None, None, init)
+        let fill = if init {
+            PowerOnFill::Pattern(DEFAULT_PATTERN)
+        } else {
+            PowerOnFill::Zero
+        };
+        Self::new_with_rom(&[], Default::default(), mmaps::mm_6502, None, None, fill)
     }
 
     pub fn new_with_rom(
@@ -24,15 +33,11 @@ impl Memory {
         mmap: fn(LoHi) -> usize,
         tia: Option<Rc<RefCell<dyn cmn::MemorySegment>>>,
         iot: Option<Rc<RefCell<dyn cmn::MemorySegment>>>,
-        init: bool,
+        fill: PowerOnFill,
     ) -> Self {
-        let mut data = [0u8; cmn::TOTAL_MEMORY_SIZE];
-        if init {
-            Self::fill_with_pattern(&mut data, 0xdeadbeef_baadf00d)
-        }
-
         let mut ret = Self {
-            data,
+            data: Self::power_on_data(fill),
+            fill,
             mmap,
             tia,
             iot,
@@ -46,12 +51,12 @@ impl Memory {
     pub fn get(&self, addr: LoHi, index: u8) -> u8 {
         let addr = (self.mmap)(addr + index);
 
-        if self.tia.is_some() && addr <= tia::TIA_MAX_ADDRESS {
-            self.tia.as_ref().unwrap().borrow().read(addr)
-        } else if self.iot.is_some() && (IOT_MIN_ADDRESS..=IOT_MAX_ADDRESS).contains(&addr) {
-            self.iot.as_ref().unwrap().borrow_mut().read(addr)
-        } else {
-            self.data[addr]
+        match (&self.tia, &self.iot) {
+            (Some(tia), _) if addr <= tia::TIA_MAX_ADDRESS => tia.borrow().read(addr),
+            (_, Some(iot)) if (IOT_MIN_ADDRESS..=IOT_MAX_ADDRESS).contains(&addr) => {
+                iot.borrow_mut().read(addr)
+            }
+            _ => self.data[addr],
         }
     }
 
@@ -59,21 +64,22 @@ impl Memory {
     pub fn set(&mut self, addr: LoHi, index: u8, value: u8) {
         let addr = (self.mmap)(addr + index);
 
-        if self.tia.is_some() && addr <= tia::TIA_MAX_ADDRESS {
-            self.tia.as_ref().unwrap().borrow_mut().write(addr, value);
-        } else if self.iot.is_some() && (IOT_MIN_ADDRESS..=IOT_MAX_ADDRESS).contains(&addr) {
-            self.iot.as_ref().unwrap().borrow_mut().write(addr, value);
-        } else {
-            self.data[addr] = value;
+        match (&self.tia, &self.iot) {
+            (Some(tia), _) if addr <= tia::TIA_MAX_ADDRESS => tia.borrow_mut().write(addr, value),
+            (_, Some(iot)) if (IOT_MIN_ADDRESS..=IOT_MAX_ADDRESS).contains(&addr) => {
+                iot.borrow_mut().write(addr, value)
+            }
+            _ => self.data[addr] = value,
         }
     }
 
-    fn fill_with_pattern(data: &mut [u8], pattern: u64) {
-        let pattern_bytes = pattern.to_be_bytes();
-        let pattern_size = mem::size_of_val(&pattern);
-        for word in data.chunks_exact_mut(pattern_size) {
-            word[..pattern_size].copy_from_slice(&pattern_bytes[..pattern_size]);
-        }
+    /// Power-on contents of RIOT RAM, for memory created with [`PowerOnFill::Zero`].
+    pub fn fill_ram(&mut self, fill: PowerOnFill) {
+        fill.fill(&mut self.data[RAM_MIN_ADDRESS..=RAM_MAX_ADDRESS]);
+    }
+
+    fn power_on_data(fill: PowerOnFill) -> [u8; cmn::TOTAL_MEMORY_SIZE] {
+        fill.bytes()
     }
 
     pub fn load(&mut self, bytes: &[u8], start: LoHi) {
@@ -82,6 +88,55 @@ impl Memory {
     }
 }
 
+/// Only the bytes that differ from the power-on contents are stored. i.e. RAM + cartridge ROM.
+impl SaveState for Memory {
+    fn save_state(&self, w: &mut StateWriter) {
+        let (kind, val) = match self.fill {
+            PowerOnFill::Zero => (0, 0),
+            PowerOnFill::Pattern(pattern) => (1, pattern),
+            PowerOnFill::Random(seed) => (2, seed),
+        };
+        w.put_u8(kind);
+        w.put_u64(val);
+
+        let diff: Vec<_> = Self::power_on_data(self.fill)
+            .iter()
+            .zip(self.data.iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+        w.put_bytes(&state::rle_encode(&diff));
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        if r.version() >= 3 {
+            let kind = r.get_u8()?;
+            let val = r.get_u64()?;
+            self.fill = match kind {
+                0 => PowerOnFill::Zero,
+                1 => PowerOnFill::Pattern(val),
+                2 => PowerOnFill::Random(val),
+                _ => return Err(r.corrupt()),
+            };
+        } else {
+            // NOTE: Till version 3 the whole memory always powered on with the pattern.
+            self.fill = PowerOnFill::Pattern(DEFAULT_PATTERN);
+        }
+
+        let diff = state::rle_decode(r.get_bytes()?).ok_or(r.corrupt())?;
+        if diff.len() != self.data.len() {
+            return Err(r.corrupt());
+        }
+
+        self.data = Self::power_on_data(self.fill);
+        self.data
+            .iter_mut()
+            .zip(diff.iter())
+            .for_each(|(a, b)| *a ^= b);
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +168,51 @@ mod tests {
         mem.set(LoHi(0xA0, 0x00), 0, 0xFE);
         assert_eq!(mem.get(LoHi(0xA0, 0x00), 0), 0xFE);
     }
+
+    #[test]
+    fn save_load_state() {
+        let mut mem = Memory::new(true);
+        mem.load(&[0x01, 0x02, 0x03], LoHi(0x00, 0xF0));
+        mem.set(LoHi(0x80, 0x00), 0, 0x99);
+
+        let mut w = StateWriter::new();
+        mem.save_state(&mut w);
+        let blob = w.into_bytes();
+
+        let mut mem2 = Memory::new(true);
+        mem2.set(LoHi(0x81, 0x00), 0, 0x42);
+        mem2.load_state(&mut StateReader::new(&blob).unwrap())
+            .unwrap();
+        assert_eq!(mem2.get(LoHi(0x00, 0xF0), 2), 0x03);
+        assert_eq!(mem2.get(LoHi(0x80, 0x00), 0), 0x99);
+        assert_eq!(mem2.get(LoHi(0x81, 0x00), 0), 0xAD);
+    }
+
+    #[test]
+    fn load_state_restores_power_on_fill() {
+        let fill = PowerOnFill::Random(7);
+        let mut mem = Memory::new_with_rom(&[], LoHi(0x00, 0x00), mmaps::mm_6502, None, None, fill);
+        mem.set(LoHi(0x80, 0x00), 0, 0x99);
+
+        let mut w = StateWriter::new();
+        mem.save_state(&mut w);
+        let blob = w.into_bytes();
+
+        let mut mem2 = Memory::new(false);
+        mem2.load_state(&mut StateReader::new(&blob).unwrap())
+            .unwrap();
+        assert_eq!(mem2.data, mem.data);
+        assert_eq!(mem2.fill, fill);
+    }
+
+    #[test]
+    fn fill_ram_leaves_rest_of_memory() {
+        let mut mem = Memory::new(false);
+        mem.fill_ram(PowerOnFill::Pattern(DEFAULT_PATTERN));
+
+        assert_eq!(mem.get(LoHi(0x7F, 0x00), 0), 0x00);
+        assert_eq!(mem.get(LoHi(0x80, 0x00), 0), 0xDE);
+        assert_eq!(mem.get(LoHi(0xFF, 0x00), 0), 0x0D);
+        assert_eq!(mem.get(LoHi(0x00, 0x01), 0), 0x00);
+    }
 }
@@ -1,16 +1,63 @@
-use crate::{bits, riot::cmn::*};
+use crate::{
+    bits,
+    ctrl::{ConsoleSwitches, ControllerPorts},
+    power_on::PowerOnFill,
+    riot::cmn::*,
+    state::{SaveState, StateError, StateReader, StateWriter},
+};
+use alloc::rc::Rc;
+use core::cell::{Cell, RefCell};
 
 /// Refer:
 /// - https://www.alienbill.com/2600/101/docs/stella.html#pia1.0
-pub trait PIA6532: MemorySegment {
+pub trait PIA6532: MemorySegment + SaveState {
     fn tick(&mut self, cycles: usize);
 }
 
+/// Address lines the RIOT decodes within its I/O & timer space.
+///
+/// Refer: https://problemkaputt.de/2k6specs.htm#memoryandiomap
+mod addr_lines {
+    /// Ports when low, timer & interrupt flags when high.
+    pub const A2: usize = 0b0_0100;
+    /// Writes: timer when high, PA7 edge detect control when low.
+    pub const A4: usize = 0b1_0000;
+    /// Reads: TIMINT when high, INTIM when low. Edge detect writes: positive edge when high.
+    pub const A0: usize = 0b0_0001;
+    /// Ports: register selector. Timer writes: interval selector.
+    pub const REGISTER: usize = 0b0_0011;
+}
+
+/// Timer interval running at power-on, see [`InMemory6532::power_on`].
+const POWER_ON_TIMER_FACTOR: u16 = 1024;
+
+/// Interrupt flags, as read from TIMINT.
+const TIMER_FLAG: u8 = bits::BIT_D7;
+const PA7_FLAG: u8 = bits::BIT_D6;
+
 #[derive(Default)]
 pub struct InMemory6532 {
-    timer_clk: usize,
     timer_count: u8,
+    /// Clocks per timer decrement. `0` till the program first sets the timer.
     timer_factor: u16,
+    /// Clocks left till the next decrement.
+    timer_prescaler: u16,
+    /// NOTE: Cleared as a side effect of reads.
+    interrupt_flags: Cell<u8>,
+    /// PA7 edge that raises [`PA7_FLAG`], `false` being negative.
+    pa7_positive_edge: bool,
+    /// PA7 level as of the last clock.
+    pa7: bool,
+    ports: Rc<RefCell<ControllerPorts>>,
+    /// Port A output register.
+    swcha: u8,
+    /// Port A data direction register.
+    swacnt: u8,
+    switches: Rc<Cell<ConsoleSwitches>>,
+    /// Port B output register.
+    swchb: u8,
+    /// Port B data direction register.
+    swbcnt: u8,
 }
 
 impl PIA6532 for InMemory6532 {
@@ -26,40 +73,197 @@ impl MemorySegment for InMemory6532 {
         #[cfg(debug_assertions)]
         self.check_read_unsupported_register_flags(addr);
 
-        if addr == regs::INTIM {
-            return self.timer_count;
+        if addr & addr_lines::A2 == 0 {
+            return match (addr & addr_lines::REGISTER) + regs::SWCHA {
+                regs::SWCHA => self.read_swcha(),
+                regs::SWACNT => self.swacnt,
+                regs::SWCHB => {
+                    // NOTE: Unlike port A, output pins read back what was written.
+                    let pins = self.switches.get().swchb_pins();
+                    (pins & !self.swbcnt) | (self.swchb & self.swbcnt)
+                }
+                _ => self.swbcnt,
+            };
         }
 
-        if addr == regs::SWCHB {
-            return bits::BIT_D3;
+        // NOTE: A3 enables the timer IRQ on INTIM reads. The 6507 has no IRQ pin, so it is ignored.
+        let flags = self.interrupt_flags.get();
+        if addr & addr_lines::A0 == 0 {
+            self.interrupt_flags.set(flags & !TIMER_FLAG);
+            self.timer_count
+        } else {
+            self.interrupt_flags.set(flags & !PA7_FLAG);
+            flags
         }
-
-        0
     }
 
     fn write(&mut self, addr: usize, val: u8) {
         #[cfg(debug_assertions)]
         self.check_write_unsupported_register_flags(addr, val);
 
-        if (regs::TIM1T..=regs::T1024T).contains(&addr) {
-            (self.timer_count, self.timer_factor) = match addr {
-                regs::TIM1T => (val, 1),
-                regs::TIM8T => (val, 8),
-                regs::TIM64T => (val, 64),
-                regs::T1024T => (val, 1024),
-                _ => panic!("Invalid case. Should not happen."),
-            };
-            self.timer_clk = self.timer_count as usize * self.timer_factor as usize;
+        if addr & addr_lines::A2 == 0 {
+            match (addr & addr_lines::REGISTER) + regs::SWCHA {
+                regs::SWCHA => {
+                    self.swcha = val;
+                    self.drive_ports();
+                }
+                regs::SWACNT => {
+                    self.swacnt = val;
+                    self.drive_ports();
+                }
+                regs::SWCHB => self.swchb = val,
+                _ => self.swbcnt = val,
+            }
+            return;
+        }
+
+        // NOTE: A3 / A1 enable the timer / PA7 IRQ. The 6507 has no IRQ pin, so they are ignored.
+        if addr & addr_lines::A4 != 0 {
+            self.timer_factor = [1, 8, 64, 1024][addr & addr_lines::REGISTER];
+            self.timer_count = val;
+            // NOTE: The first decrement happens on the very next clock.
+            self.timer_prescaler = 0;
+            self.interrupt_flags
+                .set(self.interrupt_flags.get() & !TIMER_FLAG);
+        } else {
+            self.pa7_positive_edge = addr & addr_lines::A0 != 0;
+        }
+    }
+}
+
+impl SaveState for InMemory6532 {
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_u16(self.timer_prescaler);
+        w.put_u8(self.timer_count);
+        w.put_u16(self.timer_factor);
+        w.put_u8(self.swcha);
+        w.put_u8(self.swacnt);
+        w.put_u8(self.swchb);
+        w.put_u8(self.swbcnt);
+        w.put_u8(self.interrupt_flags.get());
+        w.put_bool(self.pa7_positive_edge);
+        w.put_bool(self.pa7);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        // NOTE: Till version 6 the timer held at 0 on expiry and counted down from the
+        // full interval, it is carried over into the hardware schedule.
+        let timer_clk = if r.version() >= 6 {
+            r.get_u16()? as usize
+        } else {
+            r.get_u32()? as usize
+        };
+        let timer_count = r.get_u8()?;
+        self.timer_factor = r.get_u16()?;
+        if !matches!(self.timer_factor, 0 | 1 | 8 | 64 | 1024)
+            || (self.timer_factor == 0 && timer_clk != 0)
+        {
+            return Err(r.corrupt());
+        }
+        (self.timer_count, self.timer_prescaler) = if r.version() >= 6 {
+            if timer_clk >= self.timer_factor.max(1) as usize {
+                return Err(r.corrupt());
+            }
+            (timer_count, timer_clk as u16)
+        } else {
+            let factor = self.timer_factor.max(1) as usize;
+            ((timer_clk / factor) as u8, (timer_clk % factor) as u16)
+        };
+        (self.swcha, self.swacnt) = if r.version() >= 4 {
+            (r.get_u8()?, r.get_u8()?)
+        } else {
+            (0x00, 0x00)
+        };
+        (self.swchb, self.swbcnt) = if r.version() >= 5 {
+            (r.get_u8()?, r.get_u8()?)
+        } else {
+            (0x00, 0x00)
+        };
+        self.drive_ports();
+        if r.version() >= 6 {
+            self.interrupt_flags.set(r.get_u8()?);
+            self.pa7_positive_edge = r.get_bool()?;
+            self.pa7 = r.get_bool()?;
+        } else {
+            self.interrupt_flags.set(0x00);
+            self.pa7_positive_edge = false;
+            self.pa7 = bits::tst_bits(self.read_swcha(), bits::BIT_D7);
         }
+
+        Ok(())
     }
 }
 
 impl InMemory6532 {
+    pub fn new(ports: Rc<RefCell<ControllerPorts>>, switches: Rc<Cell<ConsoleSwitches>>) -> Self {
+        let mut ret = Self {
+            ports,
+            switches,
+            ..Default::default()
+        };
+        ret.pa7 = bits::tst_bits(ret.read_swcha(), bits::BIT_D7);
+
+        ret
+    }
+
+    /// INTIM is undefined at power-on. A non zero count has the timer already running, at the
+    /// 1024 clock interval, so programs seeding from INTIM see it change.
+    pub fn power_on(&mut self, fill: PowerOnFill) {
+        let [count] = fill.bytes();
+        if count != 0 {
+            self.timer_count = count;
+            self.timer_factor = POWER_ON_TIMER_FACTOR;
+            self.timer_prescaler = POWER_ON_TIMER_FACTOR - 1;
+        }
+    }
+
+    fn read_swcha(&self) -> u8 {
+        // NOTE: Pins are read, so a device can pull an output pin low.
+        self.ports.borrow().swcha_pins() & (self.swcha | !self.swacnt)
+    }
+
+    fn drive_ports(&self) {
+        self.ports.borrow_mut().drive_swcha(self.swcha, self.swacnt);
+    }
+
     fn one_tick(&mut self) {
-        if self.timer_clk != 0 {
-            self.timer_clk -= 1;
-            self.timer_count = self.timer_clk.div_ceil(self.timer_factor as usize) as u8;
+        self.tick_timer();
+        self.ports.borrow_mut().tick();
+        self.detect_pa7_edge();
+    }
+
+    /// Counts down once per interval. On underflow the flag is raised and the
+    /// timer decrements every clock, till INTIM is read or the timer is set.
+    fn tick_timer(&mut self) {
+        if self.timer_factor == 0 {
+            return;
+        }
+
+        let flags = self.interrupt_flags.get();
+        if flags & TIMER_FLAG != 0 {
+            self.timer_count = self.timer_count.wrapping_sub(1);
+            return;
+        }
+
+        if self.timer_prescaler != 0 {
+            self.timer_prescaler -= 1;
+            return;
+        }
+
+        self.timer_prescaler = self.timer_factor - 1;
+        if self.timer_count == 0 {
+            self.interrupt_flags.set(flags | TIMER_FLAG);
+        }
+        self.timer_count = self.timer_count.wrapping_sub(1);
+    }
+
+    fn detect_pa7_edge(&mut self) {
+        let pa7 = bits::tst_bits(self.read_swcha(), bits::BIT_D7);
+        if pa7 != self.pa7 && pa7 == self.pa7_positive_edge {
+            self.interrupt_flags
+                .set(self.interrupt_flags.get() | PA7_FLAG);
         }
+        self.pa7 = pa7;
     }
 
     #[cfg(debug_assertions)]
@@ -92,8 +296,22 @@ impl InMemory6532 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ctrl::{
+        ControllerInput, Difficulty, DrivingState, JoystickState, KeypadState, Port,
+    };
     use test_case::test_case;
 
+    #[test_case(PowerOnFill::Zero, 0x00, 0x00; "Stopped")]
+    #[test_case(PowerOnFill::Pattern(0x10 << 56), 0x10, 0x0F; "Running")]
+    fn power_on_timer(fill: PowerOnFill, exp: u8, exp_later: u8) {
+        let mut pia = InMemory6532::default();
+        pia.power_on(fill);
+
+        assert_eq!(pia.read(regs::INTIM), exp);
+        pia.tick(POWER_ON_TIMER_FACTOR as usize);
+        assert_eq!(pia.read(regs::INTIM), exp_later);
+    }
+
     #[test]
     fn simple_timer_test() {
         let mut pia = InMemory6532::default();
@@ -102,19 +320,13 @@ mod tests {
         assert_eq!(pia.read(regs::INTIM), 1);
 
         pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
-        pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
-        pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
-        pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
+        assert_eq!(pia.read(regs::INTIM), 0);
         pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
+        assert_eq!(pia.read(regs::INTIM), 0);
         pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
+        assert_eq!(pia.read(regs::INTIM), 0);
         pia.tick(1);
-        assert_eq!(pia.read(regs::INTIM), 1);
+        assert_eq!(pia.read(regs::INTIM), 0);
         pia.tick(1);
         assert_eq!(pia.read(regs::INTIM), 0);
         pia.tick(1);
@@ -123,29 +335,39 @@ mod tests {
         assert_eq!(pia.read(regs::INTIM), 0);
         pia.tick(1);
         assert_eq!(pia.read(regs::INTIM), 0);
+        pia.tick(1);
+        assert_eq!(pia.read(regs::INTIM), 0xFF);
+        pia.tick(1);
+        assert_eq!(pia.read(regs::INTIM), 0xFF);
+        pia.tick(6);
+        assert_eq!(pia.read(regs::INTIM), 0xFF);
+        pia.tick(1);
+        assert_eq!(pia.read(regs::INTIM), 0xFE);
     }
 
     #[test_case(regs::TIM1T, 2, 0, 2; "TIM1T 0 cycles")]
     #[test_case(regs::TIM1T, 2, 1, 1; "TIM1T less cycles")]
     #[test_case(regs::TIM1T, 2, 2, 0; "TIM1T same cycles")]
-    #[test_case(regs::TIM1T, 2, 3, 0; "TIM1T more cycles")]
+    #[test_case(regs::TIM1T, 2, 3, 0xFF; "TIM1T more cycles")]
     #[test_case(regs::TIM8T, 2, 0, 2; "TIM8T 0 cycles")]
-    #[test_case(regs::TIM8T, 2, 7, 2; "TIM8T less cycles - non multiple")]
+    #[test_case(regs::TIM8T, 2, 1, 1; "TIM8T first cycle")]
+    #[test_case(regs::TIM8T, 2, 7, 1; "TIM8T less cycles - non multiple")]
     #[test_case(regs::TIM8T, 2, 8, 1; "TIM8T less cycles")]
+    #[test_case(regs::TIM8T, 2, 9, 0; "TIM8T less cycles - non multiple - 2")]
     #[test_case(regs::TIM8T, 2, 16, 0; "TIM8T same cycles")]
-    #[test_case(regs::TIM8T, 2, 17, 0; "TIM8T more cycles")]
+    #[test_case(regs::TIM8T, 2, 17, 0xFF; "TIM8T more cycles")]
     #[test_case(regs::TIM64T, 2, 0, 2; "TIM64T 0 cycles")]
-    #[test_case(regs::TIM64T, 2, 63, 2; "TIM64T less cycles - non multiple")]
+    #[test_case(regs::TIM64T, 2, 63, 1; "TIM64T less cycles - non multiple")]
     #[test_case(regs::TIM64T, 2, 64, 1; "TIM64T less cycles")]
-    #[test_case(regs::TIM64T, 2, 65, 1; "TIM64T less cycles - non multiple - 2")]
+    #[test_case(regs::TIM64T, 2, 65, 0; "TIM64T less cycles - non multiple - 2")]
     #[test_case(regs::TIM64T, 2, 128, 0; "TIM64T same cycles")]
-    #[test_case(regs::TIM64T, 2, 129, 0; "TIM64T more cycles")]
+    #[test_case(regs::TIM64T, 2, 129, 0xFF; "TIM64T more cycles")]
     #[test_case(regs::T1024T, 2, 0, 2; "T1024T 0 cycles")]
-    #[test_case(regs::T1024T, 2, 1020, 2; "T1024T less cycles - non multiple")]
+    #[test_case(regs::T1024T, 2, 1020, 1; "T1024T less cycles - non multiple")]
     #[test_case(regs::T1024T, 2, 1024, 1; "T1024T less cycles")]
-    #[test_case(regs::T1024T, 2, 1030, 1; "T1024T less cycles - non multiple - 2")]
+    #[test_case(regs::T1024T, 2, 1030, 0; "T1024T less cycles - non multiple - 2")]
     #[test_case(regs::T1024T, 2, 2048, 0; "T1024T same cycles")]
-    #[test_case(regs::T1024T, 2, 3000, 0; "T1024T more cycles")]
+    #[test_case(regs::T1024T, 2, 2049, 0xFF; "T1024T more cycles")]
     fn timer_tests(reg: usize, val: u8, ticks: usize, remain: u8) {
         let mut pia = InMemory6532::default();
 
@@ -155,4 +377,246 @@ mod tests {
 
         assert_eq!(pia.read(regs::INTIM), remain);
     }
+
+    #[test_case(regs::TIM1T, 0, 1, 0xFF; "TIM1T expires on first cycle")]
+    #[test_case(regs::TIM8T, 1, 9, 0xFF; "TIM8T expired")]
+    #[test_case(regs::TIM8T, 1, 10, 0xFE; "TIM8T 1 cycle after expiry")]
+    #[test_case(regs::TIM64T, 1, 65 + 255, 0x00; "TIM64T 255 cycles after expiry")]
+    #[test_case(regs::TIM64T, 1, 65 + 256, 0xFF; "TIM64T wraps again")]
+    #[test_case(regs::T1024T, 0, 1 + 100, 0x9B; "T1024T 100 cycles after expiry")]
+    fn timer_counts_every_cycle_after_expiry(reg: usize, val: u8, ticks: usize, remain: u8) {
+        let mut pia = InMemory6532::default();
+
+        pia.write(reg, val);
+
+        pia.tick(ticks);
+
+        assert_eq!(pia.read(regs::TIMINT), TIMER_FLAG);
+        assert_eq!(pia.read(regs::INTIM), remain);
+    }
+
+    #[test_case(regs::TIM8T, 1, 9, 8; "TIM8T")]
+    #[test_case(regs::TIM64T, 0, 1, 64; "TIM64T")]
+    fn intim_read_restores_interval(reg: usize, val: u8, expiry: usize, interval: usize) {
+        let mut pia = InMemory6532::default();
+        pia.write(reg, val);
+        pia.tick(expiry + 3);
+
+        assert_eq!(pia.read(regs::INTIM), 0xFC);
+        pia.tick(interval - 1);
+        assert_eq!(pia.read(regs::INTIM), 0xFC);
+        pia.tick(1);
+        assert_eq!(pia.read(regs::INTIM), 0xFB);
+    }
+
+    #[test]
+    fn timer_flag() {
+        let mut pia = InMemory6532::default();
+        pia.write(regs::TIM8T, 1);
+        pia.tick(8);
+        assert_eq!(pia.read(regs::TIMINT), 0x00);
+
+        pia.tick(1);
+        assert_eq!(pia.read(regs::TIMINT), TIMER_FLAG);
+        assert_eq!(pia.read(regs::TIMINT), TIMER_FLAG, "TIMINT reads keep it");
+        pia.read(regs::INTIM);
+        assert_eq!(pia.read(regs::TIMINT), 0x00, "INTIM reads clear it");
+
+        pia.write(regs::TIM1T, 0);
+        pia.tick(1);
+        assert_eq!(pia.read(regs::TIMINT), TIMER_FLAG);
+        pia.write(regs::TIM1T, 10);
+        assert_eq!(pia.read(regs::TIMINT), 0x00, "Setting the timer clears it");
+    }
+
+    #[test_case(0x284, false; "Negative edge")]
+    #[test_case(0x285, true; "Positive edge")]
+    #[test_case(0x286, false; "Negative edge, interrupt enabled")]
+    #[test_case(0x287, true; "Positive edge, interrupt enabled")]
+    #[test_case(0x28D, true; "Positive edge, mirror")]
+    fn pa7_edge_detect(addr: usize, positive: bool) {
+        let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+        let mut pia = InMemory6532::new(ports.clone(), Default::default());
+        pia.write(addr, 0x00);
+        let mut press_right = |pressed: bool| {
+            let state = JoystickState {
+                right: pressed,
+                ..Default::default()
+            };
+            ports
+                .borrow_mut()
+                .get_mut(Port::Left)
+                .set_input(ControllerInput::Joystick(state));
+            pia.tick(1);
+            pia.read(regs::TIMINT)
+        };
+
+        // NOTE: Joystick switches pull PA7 low when closed.
+        let on_press = press_right(true);
+        let on_release = press_right(false);
+
+        assert_eq!(on_press, if positive { 0x00 } else { PA7_FLAG });
+        assert_eq!(on_release, if positive { PA7_FLAG } else { 0x00 });
+    }
+
+    #[test]
+    fn timint_read_clears_pa7_flag() {
+        let mut pia = InMemory6532::default();
+        pia.write(regs::SWACNT, 0x80);
+        pia.write(regs::SWCHA, 0x80);
+        pia.tick(1);
+        pia.write(regs::SWCHA, 0x00);
+        pia.tick(1);
+
+        assert_eq!(pia.read(regs::INTIM), 0x00);
+        assert_eq!(pia.read(regs::TIMINT), PA7_FLAG);
+        assert_eq!(pia.read(regs::TIMINT), 0x00);
+    }
+
+    #[test_case(0x288, regs::SWCHA; "SWCHA")]
+    #[test_case(0x293, regs::SWBCNT; "SWBCNT")]
+    #[test_case(0x286, regs::INTIM; "INTIM")]
+    #[test_case(0x29C, regs::INTIM; "INTIM, interrupt enabled")]
+    #[test_case(0x297, regs::TIMINT; "TIMINT")]
+    #[test_case(0x28F, regs::TIMINT; "TIMINT - 2")]
+    fn reads_are_mirrored(addr: usize, reg: usize) {
+        let mut pia = InMemory6532::default();
+        pia.write(regs::SWACNT, 0xA5);
+        pia.write(regs::SWBCNT, 0x5A);
+        pia.write(regs::TIM1T, 0x42);
+        pia.tick(0x43);
+
+        let exp = pia.read(reg);
+        pia.write(regs::TIM1T, 0x42);
+        pia.tick(0x43);
+
+        assert_eq!(pia.read(addr), exp);
+    }
+
+    #[test_case(0x29D, 8; "TIM8T, interrupt enabled")]
+    #[test_case(0x29F, 1024; "T1024T, interrupt enabled")]
+    fn timer_writes_are_mirrored(addr: usize, interval: usize) {
+        let mut pia = InMemory6532::default();
+
+        pia.write(addr, 3);
+        pia.tick(1 + interval);
+
+        assert_eq!(pia.read(regs::INTIM), 1);
+    }
+
+    #[test]
+    fn save_load_state_mid_countdown() {
+        let mut pia = InMemory6532::default();
+        pia.write(regs::TIM64T, 3);
+        pia.tick(70);
+
+        let mut w = StateWriter::new();
+        pia.save_state(&mut w);
+        let blob = w.into_bytes();
+
+        let mut pia2 = InMemory6532::default();
+        pia2.load_state(&mut StateReader::new(&blob).unwrap())
+            .unwrap();
+        assert_eq!(pia2.read(regs::INTIM), 1);
+        pia.tick(59);
+        pia2.tick(59);
+        assert_eq!(pia2.read(regs::INTIM), 0);
+        assert_eq!(pia2.read(regs::INTIM), pia.read(regs::INTIM));
+    }
+
+    #[test_case(0x00, 0x00, 0b1110_0111; "All inputs")]
+    #[test_case(0xF0, 0x0F, 0b1110_0000; "Right port outputs low")]
+    #[test_case(0xFF, 0x0F, 0b1110_0111; "Right port outputs high")]
+    #[test_case(0x0F, 0xF0, 0b0000_0111; "Left port outputs low")]
+    #[test_case(0xFF, 0xFF, 0b1110_0111; "Pressed switches pull outputs low")]
+    fn swcha_honours_swacnt(out: u8, ddr: u8, exp: u8) {
+        let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+        let left = JoystickState {
+            up: true,
+            ..Default::default()
+        };
+        let right = JoystickState {
+            right: true,
+            ..Default::default()
+        };
+        let mut ports_mut = ports.borrow_mut();
+        ports_mut
+            .get_mut(Port::Left)
+            .set_input(ControllerInput::Joystick(left));
+        ports_mut
+            .get_mut(Port::Right)
+            .set_input(ControllerInput::Joystick(right));
+        drop(ports_mut);
+        let mut pia = InMemory6532::new(ports, Default::default());
+
+        pia.write(regs::SWCHA, out);
+        pia.write(regs::SWACNT, ddr);
+
+        assert_eq!(pia.read(regs::SWCHA), exp);
+        assert_eq!(pia.read(regs::SWACNT), ddr);
+    }
+
+    #[test_case(0x00, 0x00, 0b1011_1110; "All inputs")]
+    #[test_case(0x00, 0b0011_0100, 0b1000_1010; "Unused pins as outputs low")]
+    #[test_case(0xFF, 0b0011_0100, 0b1011_1110; "Unused pins as outputs high")]
+    #[test_case(0xFF, 0xFF, 0xFF; "All outputs")]
+    fn swchb_honours_swbcnt(out: u8, ddr: u8, exp: u8) {
+        let switches = Rc::new(Cell::new(ConsoleSwitches {
+            reset: true,
+            right_difficulty: Difficulty::A,
+            ..Default::default()
+        }));
+        let mut pia = InMemory6532::new(Default::default(), switches);
+
+        pia.write(regs::SWCHB, out);
+        pia.write(regs::SWBCNT, ddr);
+
+        assert_eq!(pia.read(regs::SWCHB), exp);
+        assert_eq!(pia.read(regs::SWBCNT), ddr);
+    }
+
+    /// Games set SWACNT once, then drive one row low at a time and read the columns.
+    #[test_case(0xEE, [true, true, true], [true, true, true]; "Top rows")]
+    #[test_case(0xDD, [false, true, true], [true, true, true]; "Second rows")]
+    #[test_case(0x77, [true, true, true], [true, true, false]; "Bottom rows")]
+    #[test_case(0xFF, [true, true, true], [true, true, true]; "No row")]
+    fn keypad_rows_follow_swcha(out: u8, left: [bool; 3], right: [bool; 3]) {
+        let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+        let mut pia = InMemory6532::new(ports.clone(), Default::default());
+        pia.write(regs::SWACNT, 0xFF);
+        pia.write(regs::SWCHA, out);
+
+        // NOTE: Plugged in after the program set the port up, like a player would.
+        let press = |text: &str| ControllerInput::Keypad(text.parse::<KeypadState>().unwrap());
+        ports.borrow_mut().set_input(Port::Left, press("4"));
+        ports.borrow_mut().set_input(Port::Right, press("#"));
+
+        let columns = |port| {
+            let ports = ports.borrow();
+            let x = ports.get(port);
+            [x.pot(0).is_some(), x.pot(1).is_some(), x.fire()]
+        };
+        assert_eq!(columns(Port::Left), left);
+        assert_eq!(columns(Port::Right), right);
+        assert_eq!(pia.read(regs::SWCHA), out);
+    }
+
+    #[test_case(0, 0b1100)]
+    #[test_case(1, 0b1101)]
+    #[test_case(2, 0b1111)]
+    #[test_case(3, 0b1110)]
+    fn driving_gray_code_on_swcha(rotation: u8, nibble: u8) {
+        let ports = Rc::new(RefCell::new(ControllerPorts::default()));
+        let state = DrivingState {
+            rotation,
+            fire: false,
+        };
+        ports
+            .borrow_mut()
+            .set_input(Port::Right, ControllerInput::Driving(state));
+        let mut pia = InMemory6532::new(ports, Default::default());
+        pia.write(regs::SWACNT, 0x00);
+
+        assert_eq!(pia.read(regs::SWCHA), 0xF0 | nibble);
+    }
 }
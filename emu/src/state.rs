@@ -0,0 +1,389 @@
+//! Save state blob layout:
+//!
+//! ```text
+//! "RSTL" | version: u16 | section*
+//! section = tag: [u8; 4] | length: u32 | payload: [u8; length]
+//! ```
+//!
+//! - All integers are little endian.
+//! - Sections appear in a fixed order. Each component owns the layout of its own payload.
+//! - Fields added by a later format version are read only when the blob version is new enough,
+//!   otherwise the component falls back to its power-on value. This keeps older blobs loadable.
+
+use alloc::vec::Vec;
+
+pub const STATE_MAGIC: &[u8; 4] = b"RSTL";
+
+/// Version history:
+/// - 1: CPU, memory, RIOT, TIA & TV beam.
+/// - 2: TIA frame checksums.
+/// - 3: Memory power-on fill.
+/// - 4: RIOT port A & TIA input latches.
+/// - 5: RIOT port B.
+/// - 6: RIOT timer prescaler, interrupt flags & PA7 edge detect. CPU / TIA clock phase.
+/// - 7: TIA paddle capacitor charge.
+/// - 8: TIA missile position counters.
+/// - 9: TIA ball position counter & delayed enable.
+/// - 10: TIA delayed player graphics.
+/// - 11: TIA collision latches.
+/// - 12: TIA extended HBLANK & HMOVE motion counter.
+/// - 13: TIA delayed register writes.
+/// - 14: TIA audio channels.
+/// - 15: TV config & B&W setting. Console switches. Controller devices.
+pub const STATE_VERSION: u16 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// Blob does not start with [`STATE_MAGIC`].
+    BadMagic,
+    /// Blob was produced by a newer emulator.
+    UnsupportedVersion(u16),
+    /// Expected section was not found at its position.
+    MissingSection([u8; 4]),
+    /// Blob ended prematurely.
+    UnexpectedEnd,
+    /// Section payload was not consumed exactly or contains an impossible value.
+    Corrupt([u8; 4]),
+}
+
+/// Implemented by every hardware component that takes part in a save state.
+pub trait SaveState {
+    fn save_state(&self, w: &mut StateWriter);
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError>;
+}
+
+#[derive(Default)]
+pub struct StateWriter {
+    buf: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        let mut w = Self::default();
+        w.buf.extend_from_slice(STATE_MAGIC);
+        w.put_u16(STATE_VERSION);
+        w
+    }
+
+    /// Writes a tagged section, the payload is produced by `f`.
+    pub fn section(&mut self, tag: &[u8; 4], f: impl FnOnce(&mut Self)) {
+        self.buf.extend_from_slice(tag);
+        let len_pos = self.buf.len();
+        self.put_u32(0);
+        f(self);
+        let len = (self.buf.len() - len_pos - 4) as u32;
+        self.buf[len_pos..len_pos + 4].copy_from_slice(&len.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn put_u8(&mut self, val: u8) {
+        self.buf.push(val);
+    }
+
+    #[inline]
+    pub fn put_bool(&mut self, val: bool) {
+        self.put_u8(val as u8);
+    }
+
+    #[inline]
+    pub fn put_u16(&mut self, val: u16) {
+        self.buf.extend_from_slice(&val.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn put_u32(&mut self, val: u32) {
+        self.buf.extend_from_slice(&val.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn put_u64(&mut self, val: u64) {
+        self.buf.extend_from_slice(&val.to_le_bytes());
+    }
+
+    /// Bit for bit, so that a restored machine continues exactly.
+    #[inline]
+    pub fn put_f32(&mut self, val: f32) {
+        self.put_u32(val.to_bits());
+    }
+
+    /// Length prefixed byte array.
+    pub fn put_bytes(&mut self, val: &[u8]) {
+        self.put_u32(val.len() as u32);
+        self.buf.extend_from_slice(val);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    version: u16,
+    tag: [u8; 4],
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, StateError> {
+        if data.len() < STATE_MAGIC.len() || &data[..STATE_MAGIC.len()] != STATE_MAGIC {
+            return Err(StateError::BadMagic);
+        }
+
+        let mut r = Self {
+            data,
+            pos: STATE_MAGIC.len(),
+            version: 0,
+            tag: *b"HEAD",
+        };
+        r.version = r.get_u16()?;
+        if r.version == 0 || r.version > STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(r.version));
+        }
+
+        Ok(r)
+    }
+
+    /// Format version of the blob being read.
+    #[inline]
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Reads the next section, which must be `tag`, with `f`. `f` must consume the payload exactly.
+    pub fn section(
+        &mut self,
+        tag: &[u8; 4],
+        f: impl FnOnce(&mut StateReader<'a>) -> Result<(), StateError>,
+    ) -> Result<(), StateError> {
+        let found = self.take(4)?;
+        if found != tag {
+            return Err(StateError::MissingSection(*tag));
+        }
+        let len = self.get_u32()? as usize;
+        let payload = self.take(len)?;
+
+        let mut sub = StateReader {
+            data: payload,
+            pos: 0,
+            version: self.version,
+            tag: *tag,
+        };
+        f(&mut sub)?;
+        if sub.pos != payload.len() {
+            return Err(StateError::Corrupt(*tag));
+        }
+
+        Ok(())
+    }
+
+    /// Error for a value that cannot be produced by the emulator.
+    #[inline]
+    pub fn corrupt(&self) -> StateError {
+        StateError::Corrupt(self.tag)
+    }
+
+    #[inline]
+    pub fn get_u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    #[inline]
+    pub fn get_bool(&mut self) -> Result<bool, StateError> {
+        match self.get_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(self.corrupt()),
+        }
+    }
+
+    #[inline]
+    pub fn get_u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    #[inline]
+    pub fn get_u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    #[inline]
+    pub fn get_u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    #[inline]
+    pub fn get_f32(&mut self) -> Result<f32, StateError> {
+        Ok(f32::from_bits(self.get_u32()?))
+    }
+
+    pub fn get_bytes(&mut self) -> Result<&'a [u8], StateError> {
+        let len = self.get_u32()? as usize;
+        self.take(len)
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        let end = self.pos.checked_add(len).ok_or(StateError::UnexpectedEnd)?;
+        if end > self.data.len() {
+            return Err(StateError::UnexpectedEnd);
+        }
+        let ret = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(ret)
+    }
+}
+
+/// PackBits style run length encoding.
+/// - `0x00..=0x7F` n: copy the next n + 1 bytes.
+/// - `0x80..=0xFF` n: repeat the next byte n - 0x7E times.
+pub fn rle_encode(data: &[u8]) -> Vec<u8> {
+    const MAX_RUN: usize = 0x81;
+
+    let mut ret = Vec::new();
+    let mut i = 0;
+    let mut literal_start = 0;
+    while i < data.len() {
+        let run = data[i..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&b| b == data[i])
+            .count();
+
+        if run >= 3 {
+            flush_literals(&mut ret, &data[literal_start..i]);
+            ret.push((run + 0x7E) as u8);
+            ret.push(data[i]);
+            i += run;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+    flush_literals(&mut ret, &data[literal_start..]);
+
+    ret
+}
+
+fn flush_literals(ret: &mut Vec<u8>, literals: &[u8]) {
+    literals.chunks(0x80).for_each(|chunk| {
+        ret.push((chunk.len() - 1) as u8);
+        ret.extend_from_slice(chunk);
+    });
+}
+
+/// Inverse of [`rle_encode`]. `None` if the input is malformed.
+pub fn rle_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut ret = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let ctrl = data[i] as usize;
+        i += 1;
+        if ctrl < 0x80 {
+            let literals = data.get(i..i + ctrl + 1)?;
+            ret.extend_from_slice(literals);
+            i += ctrl + 1;
+        } else {
+            let val = *data.get(i)?;
+            ret.extend(core::iter::repeat_n(val, ctrl - 0x7E));
+            i += 1;
+        }
+    }
+
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use test_case::test_case;
+
+    #[test]
+    fn writer_reader_roundtrip() {
+        let mut w = StateWriter::new();
+        w.section(b"TEST", |w| {
+            w.put_u8(0x12);
+            w.put_bool(true);
+            w.put_u16(0x3456);
+            w.put_u32(0x789A_BCDE);
+            w.put_u64(0x0123_4567_89AB_CDEF);
+            w.put_f32(-0.375);
+            w.put_bytes(&[0x01, 0x02, 0x03]);
+        });
+        let blob = w.into_bytes();
+
+        let mut r = StateReader::new(&blob).unwrap();
+        assert_eq!(r.version(), STATE_VERSION);
+        r.section(b"TEST", |r| {
+            assert_eq!(r.get_u8()?, 0x12);
+            assert!(r.get_bool()?);
+            assert_eq!(r.get_u16()?, 0x3456);
+            assert_eq!(r.get_u32()?, 0x789A_BCDE);
+            assert_eq!(r.get_u64()?, 0x0123_4567_89AB_CDEF);
+            assert_eq!(r.get_f32()?, -0.375);
+            assert_eq!(r.get_bytes()?, &[0x01, 0x02, 0x03]);
+            Ok(())
+        })
+        .unwrap();
+        assert!(r.is_at_end());
+    }
+
+    #[test_case(b"XXXX\x01\x00", StateError::BadMagic; "Bad magic")]
+    #[test_case(b"RS", StateError::BadMagic; "Truncated magic")]
+    #[test_case(b"RSTL\x00\x00", StateError::UnsupportedVersion(0); "Version 0")]
+    #[test_case(b"RSTL\xFF\xFF", StateError::UnsupportedVersion(0xFFFF); "Future version")]
+    #[test_case(b"RSTL\x01", StateError::UnexpectedEnd; "Truncated version")]
+    fn reader_rejects_bad_headers(blob: &[u8], err: StateError) {
+        assert_eq!(StateReader::new(blob).err(), Some(err));
+    }
+
+    #[test]
+    fn reader_rejects_bad_sections() {
+        let mut w = StateWriter::new();
+        w.section(b"TEST", |w| w.put_u16(0x0102));
+        let blob = w.into_bytes();
+
+        let mut r = StateReader::new(&blob).unwrap();
+        let err = r.section(b"OTHR", |_| Ok(()));
+        assert_eq!(err, Err(StateError::MissingSection(*b"OTHR")));
+
+        let mut r = StateReader::new(&blob).unwrap();
+        let err = r.section(b"TEST", |r| r.get_u8().map(|_| ()));
+        assert_eq!(err, Err(StateError::Corrupt(*b"TEST")));
+
+        let mut r = StateReader::new(&blob[..blob.len() - 1]).unwrap();
+        let err = r.section(b"TEST", |r| r.get_u16().map(|_| ()));
+        assert_eq!(err, Err(StateError::UnexpectedEnd));
+    }
+
+    #[test_case(vec![]; "Empty")]
+    #[test_case(vec![0x01]; "Single")]
+    #[test_case(vec![0x01, 0x01]; "Short run")]
+    #[test_case(vec![0x00; 1000]; "Long run")]
+    #[test_case((0..=255).collect(); "Literals")]
+    #[test_case((0..1000).map(|x| (x / 7) as u8).collect(); "Mixed")]
+    #[test_case([vec![0x05; 3], vec![0x01, 0x02], vec![0x06; 0x81], vec![0x06; 0x82]].concat(); "Run boundaries")]
+    fn rle_roundtrip(data: Vec<u8>) {
+        let encoded = rle_encode(&data);
+        assert_eq!(rle_decode(&encoded), Some(data));
+    }
+
+    #[test]
+    fn rle_compresses_runs() {
+        assert_eq!(
+            rle_encode(&[0x00; 0x1_0000]).len(),
+            2 * 0x1_0000usize.div_ceil(0x81)
+        );
+    }
+
+    #[test_case(&[0x01, 0xAA]; "Literals past end")]
+    #[test_case(&[0x80]; "Run without value")]
+    fn rle_decode_rejects_malformed(data: &[u8]) {
+        assert_eq!(rle_decode(data), None);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{core, tv};
+use super::tv;
 
 pub const TIA_MAX_ADDRESS: usize = 0x003F;
 
@@ -98,50 +98,50 @@ pub mod regs {
     pub static IMPLEMENTED_REGISTERS: &[(bool, u8, &str, u8); super::TIA_MAX_ADDRESS + 1] = &[
         // W     Valid Mask    Name     Supported Mask
         (true , 0b_0000_0010, "VSYNC",  0b_0000_0010),  // = $00   0000 00x0   Vertical Sync Set-Clear
-        (true , 0b_1100_0010, "VBLANK", 0b_0000_0010),  // = $01   xx00 00x0   Vertical Blank Set-Clear
+        (true , 0b_1100_0010, "VBLANK", 0b_1100_0010),  // = $01   xx00 00x0   Vertical Blank Set-Clear
         (true , 0b_0000_0000, "WSYNC",  0b_0000_0000),  // = $02   ---- ----   Wait for Horizontal Blank
-        (false, 0b_0000_0000, "RSYNC",  0b_0000_0000),  // = $03   ---- ----   Reset Horizontal Sync Counter
-        (false, 0b_0011_0111, "NUSIZ0", 0b_0000_0000),  // = $04   00xx 0xxx   Number-Size player/missle 0
-        (false, 0b_0011_0111, "NUSIZ1", 0b_0000_0000),  // = $05   00xx 0xxx   Number-Size player/missle 1
+        (true , 0b_0000_0000, "RSYNC",  0b_0000_0000),  // = $03   ---- ----   Reset Horizontal Sync Counter
+        (true,  0b_0011_0111, "NUSIZ0", 0b_0011_0111),  // = $04   00xx 0xxx   Number-Size player/missle 0
+        (true,  0b_0011_0111, "NUSIZ1", 0b_0011_0111),  // = $05   00xx 0xxx   Number-Size player/missle 1
         (true,  0b_1111_1110, "COLUP0", 0b_1111_1111),  // = $06   xxxx xxx0   Color-Luminance Player 0
         (true,  0b_1111_1110, "COLUP1", 0b_1111_1111),  // = $07   xxxx xxx0   Color-Luminance Player 1
         (true,  0b_1111_1110, "COLUPF", 0b_1111_1111),  // = $08   xxxx xxx0   Color-Luminance Playfield
         (true,  0b_1111_1110, "COLUBK", 0b_1111_1111),  // = $09   xxxx xxx0   Color-Luminance Background
-        (true,  0b_0011_0111, "CTRLPF", 0b_0000_0011),  // = $0A   00xx 0xxx   Control Playfield, Ball, Collisions
+        (true,  0b_0011_0111, "CTRLPF", 0b_0011_0111),  // = $0A   00xx 0xxx   Control Playfield, Ball, Collisions
         (false, 0b_0000_1000, "REFP0",  0b_0000_0000),  // = $0B   0000 x000   Reflection Player 0
         (false, 0b_0000_1000, "REFP1",  0b_0000_0000),  // = $0C   0000 x000   Reflection Player 1
         (true,  0b_1111_0000, "PF0",    0b_1111_0000),  // = $0D   xxxx 0000   Playfield Register Byte 0
         (true,  0b_1111_1111, "PF1",    0b_1111_1111),  // = $0E   xxxx xxxx   Playfield Register Byte 1
         (true,  0b_1111_1111, "PF2",    0b_1111_1111),  // = $0F   xxxx xxxx   Playfield Register Byte 2
-        (false, 0b_0000_0000, "RESP0",  0b_0000_0000),  // = $10   ---- ----   Reset Player 0
-        (false, 0b_0000_0000, "RESP1",  0b_0000_0000),  // = $11   ---- ----   Reset Player 1
-        (false, 0b_0000_0000, "RESM0",  0b_0000_0000),  // = $12   ---- ----   Reset Missle 0
-        (false, 0b_0000_0000, "RESM1",  0b_0000_0000),  // = $13   ---- ----   Reset Missle 1
-        (false, 0b_0000_0000, "RESBL",  0b_0000_0000),  // = $14   ---- ----   Reset Ball
-        (false, 0b_0000_1111, "AUDC0",  0b_0000_0000),  // = $15   0000 xxxx   Audio Control 0
-        (false, 0b_0000_1111, "AUDC1",  0b_0000_0000),  // = $16   0000 xxxx   Audio Control 1
-        (false, 0b_0001_1111, "AUDF0",  0b_0000_0000),  // = $17   000x xxxx   Audio Frequency 0
-        (false, 0b_0001_1111, "AUDF1",  0b_0000_0000),  // = $18   000x xxxx   Audio Frequency 1
-        (false, 0b_0000_1111, "AUDV0",  0b_0000_0000),  // = $19   0000 xxxx   Audio Volume 0
-        (false, 0b_0000_1111, "AUDV1",  0b_0000_0000),  // = $1A   0000 xxxx   Audio Volume 1
+        (true,  0b_0000_0000, "RESP0",  0b_0000_0000),  // = $10   ---- ----   Reset Player 0
+        (true,  0b_0000_0000, "RESP1",  0b_0000_0000),  // = $11   ---- ----   Reset Player 1
+        (true,  0b_0000_0000, "RESM0",  0b_0000_0000),  // = $12   ---- ----   Reset Missle 0
+        (true,  0b_0000_0000, "RESM1",  0b_0000_0000),  // = $13   ---- ----   Reset Missle 1
+        (true,  0b_0000_0000, "RESBL",  0b_0000_0000),  // = $14   ---- ----   Reset Ball
+        (true,  0b_0000_1111, "AUDC0",  0b_0000_1111),  // = $15   0000 xxxx   Audio Control 0
+        (true,  0b_0000_1111, "AUDC1",  0b_0000_1111),  // = $16   0000 xxxx   Audio Control 1
+        (true,  0b_0001_1111, "AUDF0",  0b_0001_1111),  // = $17   000x xxxx   Audio Frequency 0
+        (true,  0b_0001_1111, "AUDF1",  0b_0001_1111),  // = $18   000x xxxx   Audio Frequency 1
+        (true,  0b_0000_1111, "AUDV0",  0b_0000_1111),  // = $19   0000 xxxx   Audio Volume 0
+        (true,  0b_0000_1111, "AUDV1",  0b_0000_1111),  // = $1A   0000 xxxx   Audio Volume 1
         (true,  0b_1111_1111, "GRP0",   0b_1111_1111),  // = $1B   xxxx xxxx   Graphics Register Player 0
-        (false, 0b_1111_1111, "GRP1",   0b_0000_0000),  // = $1C   xxxx xxxx   Graphics Register Player 1
-        (false, 0b_0000_0010, "ENAM0",  0b_0000_0000),  // = $1D   0000 00x0   Graphics Enable Missle 0
-        (false, 0b_0000_0010, "ENAM1",  0b_0000_0000),  // = $1E   0000 00x0   Graphics Enable Missle 1
-        (false, 0b_0000_0010, "ENABL",  0b_0000_0000),  // = $1F   0000 00x0   Graphics Enable Ball
+        (true,  0b_1111_1111, "GRP1",   0b_1111_1111),  // = $1C   xxxx xxxx   Graphics Register Player 1
+        (true,  0b_0000_0010, "ENAM0",  0b_0000_0010),  // = $1D   0000 00x0   Graphics Enable Missle 0
+        (true,  0b_0000_0010, "ENAM1",  0b_0000_0010),  // = $1E   0000 00x0   Graphics Enable Missle 1
+        (true,  0b_0000_0010, "ENABL",  0b_0000_0010),  // = $1F   0000 00x0   Graphics Enable Ball
         (true,  0b_1111_0000, "HMP0",   0b_1111_0000),  // = $20   xxxx 0000   Horizontal Motion Player 0
-        (false, 0b_1111_0000, "HMP1",   0b_0000_0000),  // = $21   xxxx 0000   Horizontal Motion Player 1
-        (false, 0b_1111_0000, "HMM0",   0b_0000_0000),  // = $22   xxxx 0000   Horizontal Motion Missle 0
-        (false, 0b_1111_0000, "HMM1",   0b_0000_0000),  // = $23   xxxx 0000   Horizontal Motion Missle 1
-        (false, 0b_1111_0000, "HMBL",   0b_0000_0000),  // = $24   xxxx 0000   Horizontal Motion Ball
-        (false, 0b_0000_0001, "VDELP0", 0b_0000_0000),  // = $25   0000 000x   Vertical Delay Player 0
-        (false, 0b_0000_0001, "VDELP1", 0b_0000_0000),  // = $26   0000 000x   Vertical Delay Player 1
-        (false, 0b_0000_0001, "VDELBL", 0b_0000_0000),  // = $27   0000 000x   Vertical Delay Ball
-        (false, 0b_0000_0010, "RESMP0", 0b_0000_0000),  // = $28   0000 00x0   Reset Missle 0 to Player 0
-        (false, 0b_0000_0010, "RESMP1", 0b_0000_0000),  // = $29   0000 00x0   Reset Missle 1 to Player 1
-        (false, 0b_0000_0000, "HMOVE",  0b_0000_0000),  // = $2A   ---- ----   Apply Horizontal Motion
-        (false, 0b_0000_0000, "HMCLR",  0b_0000_0000),  // = $2B   ---- ----   Clear Horizontal Move Registers
-        (false, 0b_0000_0000, "CXCLR",  0b_0000_0000),  // = $2C   ---- ----   Clear Collision Latches
+        (true,  0b_1111_0000, "HMP1",   0b_1111_0000),  // = $21   xxxx 0000   Horizontal Motion Player 1
+        (true,  0b_1111_0000, "HMM0",   0b_1111_0000),  // = $22   xxxx 0000   Horizontal Motion Missle 0
+        (true,  0b_1111_0000, "HMM1",   0b_1111_0000),  // = $23   xxxx 0000   Horizontal Motion Missle 1
+        (true,  0b_1111_0000, "HMBL",   0b_1111_0000),  // = $24   xxxx 0000   Horizontal Motion Ball
+        (true,  0b_0000_0001, "VDELP0", 0b_0000_0001),  // = $25   0000 000x   Vertical Delay Player 0
+        (true,  0b_0000_0001, "VDELP1", 0b_0000_0001),  // = $26   0000 000x   Vertical Delay Player 1
+        (true,  0b_0000_0001, "VDELBL", 0b_0000_0001),  // = $27   0000 000x   Vertical Delay Ball
+        (true,  0b_0000_0010, "RESMP0", 0b_0000_0010),  // = $28   0000 00x0   Reset Missle 0 to Player 0
+        (true,  0b_0000_0010, "RESMP1", 0b_0000_0010),  // = $29   0000 00x0   Reset Missle 1 to Player 1
+        (true,  0b_0000_0000, "HMOVE",  0b_0000_0000),  // = $2A   ---- ----   Apply Horizontal Motion
+        (true,  0b_0000_0000, "HMCLR",  0b_0000_0000),  // = $2B   ---- ----   Clear Horizontal Move Registers
+        (true,  0b_0000_0000, "CXCLR",  0b_0000_0000),  // = $2C   ---- ----   Clear Collision Latches
         (false, 0b_0000_0000, "????",   0b_0000_0000),  // = $2D   ---- ----
         (false, 0b_0000_0000, "????",   0b_0000_0000),  // = $2E   ---- ----
         (false, 0b_0000_0000, "????",   0b_0000_0000),  // = $2F   ---- ----
@@ -162,148 +162,502 @@ pub mod regs {
         (false, 0b_0000_0000, "????",   0b_0000_0000),  // = $3E   ---- ----
         (false, 0b_0000_0000, "????",   0b_0000_0000),  // = $3F   ---- ----
     ];
+}
+
+/// Refer: https://www.atarimax.com/freenet/freenet_material/12.AtariLibrary/2.MiscellaneousTextFiles/showarticle.php?129
+pub mod read_regs {
+    /// Only A3-A0 are decoded for reads.
+    pub const ADDRESS_MASK: usize = 0x0F;
+
+    /// $00   xx00 0000   Read Collision  M0-P1   M0-P0
+    pub const CXM0P: usize = 0x00;
+    /// $01   xx00 0000                   M1-P0   M1-P1
+    pub const CXM1P: usize = 0x01;
+    /// $02   xx00 0000                   P0-PF   P0-BL
+    pub const CXP0FB: usize = 0x02;
+    /// $03   xx00 0000                   P1-PF   P1-BL
+    pub const CXP1FB: usize = 0x03;
+    /// $04   xx00 0000                   M0-PF   M0-BL
+    pub const CXM0FB: usize = 0x04;
+    /// $05   xx00 0000                   M1-PF   M1-BL
+    pub const CXM1FB: usize = 0x05;
+    /// $06   x000 0000                   BL-PF   -----
+    pub const CXBLPF: usize = 0x06;
+    /// $07   xx00 0000                   P0-P1   M0-M1
+    pub const CXPPMM: usize = 0x07;
+    /// $08   x000 0000   Read Pot Port 0
+    pub const INPT0: usize = 0x08;
+    /// $09   x000 0000   Read Pot Port 1
+    pub const INPT1: usize = 0x09;
+    /// $0A   x000 0000   Read Pot Port 2
+    pub const INPT2: usize = 0x0A;
+    /// $0B   x000 0000   Read Pot Port 3
+    pub const INPT3: usize = 0x0B;
+    /// $0C   x000 0000   Read Input (Trigger) 0
+    pub const INPT4: usize = 0x0C;
+    /// $0D   x000 0000   Read Input (Trigger) 1
+    pub const INPT5: usize = 0x0D;
 
-    // Refer for read registers https://www.atarimax.com/freenet/freenet_material/12.AtariLibrary/2.MiscellaneousTextFiles/showarticle.php?129
+    #[rustfmt::skip]
+    pub static IMPLEMENTED_REGISTERS: &[(bool, &str); ADDRESS_MASK + 1] = &[
+        // R     Name
+        (true , "CXM0P"),   // = $00   xx00 0000   Read Collision  M0-P1   M0-P0
+        (true , "CXM1P"),   // = $01   xx00 0000                   M1-P0   M1-P1
+        (true , "CXP0FB"),  // = $02   xx00 0000                   P0-PF   P0-BL
+        (true , "CXP1FB"),  // = $03   xx00 0000                   P1-PF   P1-BL
+        (true , "CXM0FB"),  // = $04   xx00 0000                   M0-PF   M0-BL
+        (true , "CXM1FB"),  // = $05   xx00 0000                   M1-PF   M1-BL
+        (true , "CXBLPF"),  // = $06   x000 0000                   BL-PF   -----
+        (true , "CXPPMM"),  // = $07   xx00 0000                   P0-P1   M0-M1
+        (true , "INPT0"),   // = $08   x000 0000   Read Pot Port 0
+        (true , "INPT1"),   // = $09   x000 0000   Read Pot Port 1
+        (true , "INPT2"),   // = $0A   x000 0000   Read Pot Port 2
+        (true , "INPT3"),   // = $0B   x000 0000   Read Pot Port 3
+        (true , "INPT4"),   // = $0C   x000 0000   Read Input (Trigger) 0
+        (true , "INPT5"),   // = $0D   x000 0000   Read Input (Trigger) 1
+        (false, "????"),    // = $0E
+        (false, "????"),    // = $0F
+    ];
 }
 
 pub const NTSC_SCANLINES: usize = 262;
 pub const NTSC_PIXELS_PER_SCANLINE: usize = 228;
+pub const PAL_SCANLINES: usize = 312;
+pub const PAL_PIXELS_PER_SCANLINE: usize = 228;
+
+/// CPU clock in Hz, a third of the color clock.
+pub const NTSC_CPU_HZ: u32 = 1_193_182;
+pub const PAL_CPU_HZ: u32 = 1_182_298;
+/// CPU cycles per sample of audio generated by the TIA & devices, i.e. two samples per scanline.
+pub const CPU_CYCLES_PER_AUDIO_SAMPLE: u32 = 38;
+/// In Hz, on a NTSC console. See [`TVStandard::audio_sample_rate`].
+pub const AUDIO_SAMPLE_RATE: u32 = NTSC_CPU_HZ / CPU_CYCLES_PER_AUDIO_SAMPLE;
+
+/// Visible pixels of a scanline, the same for all standards.
+const DRAW_PIXELS: usize = 160;
+
+pub fn ntsc_tv_config() -> tv::TVConfig {
+    ntsc_geometry(NTSC_PALETTE)
+}
+
+pub fn pal_tv_config() -> tv::TVConfig {
+    pal_geometry(PAL_PALETTE)
+}
+
+/// SECAM sets use the PAL timing but show only 8 colors.
+pub fn secam_tv_config() -> tv::TVConfig {
+    pal_geometry(secam_palette())
+}
+
+/// PAL colors at NTSC timing, as produced by 60Hz PAL games.
+pub fn pal60_tv_config() -> tv::TVConfig {
+    ntsc_geometry(PAL_PALETTE)
+}
+
+/// NTSC colors at PAL timing, as produced by NTSC games patched for 50Hz sets.
+pub fn ntsc50_tv_config() -> tv::TVConfig {
+    pal_geometry(NTSC_PALETTE)
+}
+
+/// Combination of frame timing & colors a TV expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TVStandard {
+    Ntsc,
+    Pal,
+    Secam,
+    Pal60,
+    Ntsc50,
+}
+
+impl TVStandard {
+    pub const ALL: [TVStandard; 5] = [
+        TVStandard::Ntsc,
+        TVStandard::Pal,
+        TVStandard::Secam,
+        TVStandard::Pal60,
+        TVStandard::Ntsc50,
+    ];
 
-pub type NtscTV = tv::InMemoryTV<NTSC_SCANLINES, NTSC_PIXELS_PER_SCANLINE>;
-pub type NtscTIA = core::InMemoryTIA<NTSC_SCANLINES, NTSC_PIXELS_PER_SCANLINE>;
+    pub fn tv_config(self) -> tv::TVConfig {
+        match self {
+            TVStandard::Ntsc => ntsc_tv_config(),
+            TVStandard::Pal => pal_tv_config(),
+            TVStandard::Secam => secam_tv_config(),
+            TVStandard::Pal60 => pal60_tv_config(),
+            TVStandard::Ntsc50 => ntsc50_tv_config(),
+        }
+    }
+
+    /// CPU clock of the console made for the standard. PAL60 is output by PAL consoles, NTSC50 by
+    /// NTSC ones.
+    pub fn cpu_hz(self) -> u32 {
+        match self {
+            TVStandard::Ntsc | TVStandard::Ntsc50 => NTSC_CPU_HZ,
+            TVStandard::Pal | TVStandard::Secam | TVStandard::Pal60 => PAL_CPU_HZ,
+        }
+    }
+
+    /// In Hz, audio being generated every [`CPU_CYCLES_PER_AUDIO_SAMPLE`].
+    pub fn audio_sample_rate(self) -> u32 {
+        self.cpu_hz() / CPU_CYCLES_PER_AUDIO_SAMPLE
+    }
+
+    /// The standard `config` was made for, if any.
+    pub fn of(config: &tv::TVConfig) -> Option<TVStandard> {
+        TVStandard::ALL
+            .into_iter()
+            .find(|x| x.tv_config() == *config)
+    }
+}
+
+fn ntsc_geometry(color_map: [u32; 128]) -> tv::TVConfig {
+    tv::TVConfig::new(
+        NTSC_SCANLINES,
+        NTSC_PIXELS_PER_SCANLINE,
+        DRAW_PIXELS,
+        color_map,
+    )
+}
+
+fn pal_geometry(color_map: [u32; 128]) -> tv::TVConfig {
+    tv::TVConfig::new(
+        PAL_SCANLINES,
+        PAL_PIXELS_PER_SCANLINE,
+        DRAW_PIXELS,
+        color_map,
+    )
+}
+
+/// From https://www.randomterrain.com/atari-2600-memories-tia-color-charts.html
+#[rustfmt::skip]
+const NTSC_PALETTE: [u32; 128] = [
+    0x000000FF, // $00
+    0x1A1A1AFF, // $02
+    0x393939FF, // $04
+    0x5B5B5BFF, // $06
+    0x7E7E7EFF, // $08
+    0xA2A2A2FF, // $0A
+    0xC7C7C7FF, // $0C
+    0xEDEDEDFF, // $0E
+    0x190200FF, // $10
+    0x3A1F00FF, // $12
+    0x5D4100FF, // $14
+    0x826400FF, // $16
+    0xA78800FF, // $18
+    0xCCAD00FF, // $1A
+    0xF2D219FF, // $1C
+    0xFEFA40FF, // $1E
+    0x370000FF, // $20
+    0x5E0800FF, // $22
+    0x832700FF, // $24
+    0xA94900FF, // $26
+    0xCF6C00FF, // $28
+    0xF58F17FF, // $2A
+    0xFEB438FF, // $2C
+    0xFEDF6FFF, // $2E
+    0x470000FF, // $30
+    0x730000FF, // $32
+    0x981300FF, // $34
+    0xBE3216FF, // $36
+    0xE45335FF, // $38
+    0xFE7657FF, // $3A
+    0xFE9C81FF, // $3C
+    0xFEC6BBFF, // $3E
+    0x440008FF, // $40
+    0x6F001FFF, // $42
+    0x960640FF, // $44
+    0xBB2462FF, // $46
+    0xE14585FF, // $48
+    0xFE67AAFF, // $4A
+    0xFE8CD6FF, // $4C
+    0xFEB7F6FF, // $4E
+    0x2D004AFF, // $50
+    0x570067FF, // $52
+    0x7D058CFF, // $54
+    0xA122B1FF, // $56
+    0xC743D7FF, // $58
+    0xED65FEFF, // $5A
+    0xFE8AF6FF, // $5C
+    0xFEB5F7FF, // $5E
+    0x0D0082FF, // $60
+    0x3300A2FF, // $62
+    0x550FC9FF, // $64
+    0x782DF0FF, // $66
+    0x9C4EFEFF, // $68
+    0xC372FEFF, // $6A
+    0xEB98FEFF, // $6C
+    0xFEC0F9FF, // $6E
+    0x000091FF, // $70
+    0x0A05BDFF, // $72
+    0x2822E4FF, // $74
+    0x4842FEFF, // $76
+    0x6B64FEFF, // $78
+    0x908AFEFF, // $7A
+    0xB7B0FEFF, // $7C
+    0xDFD8FEFF, // $7E
+    0x000072FF, // $80
+    0x001CABFF, // $82
+    0x033CD6FF, // $84
+    0x205EFDFF, // $86
+    0x4081FEFF, // $88
+    0x64A6FEFF, // $8A
+    0x89CEFEFF, // $8C
+    0xB0F6FEFF, // $8E
+    0x00103AFF, // $90
+    0x00316EFF, // $92
+    0x0055A2FF, // $94
+    0x0579C8FF, // $96
+    0x239DEEFF, // $98
+    0x44C2FEFF, // $9A
+    0x68E9FEFF, // $9C
+    0x8FFEFEFF, // $9E
+    0x001F02FF, // $A0
+    0x004326FF, // $A2
+    0x006957FF, // $A4
+    0x008D7AFF, // $A6
+    0x1BB19EFF, // $A8
+    0x3BD7C3FF, // $AA
+    0x5DFEE9FF, // $AC
+    0x86FEFEFF, // $AE
+    0x002403FF, // $B0
+    0x004A05FF, // $B2
+    0x00700CFF, // $B4
+    0x09952BFF, // $B6
+    0x28BA4CFF, // $B8
+    0x49E06EFF, // $BA
+    0x6CFE92FF, // $BC
+    0x97FEB5FF, // $BE
+    0x002102FF, // $C0
+    0x004604FF, // $C2
+    0x086B00FF, // $C4
+    0x289000FF, // $C6
+    0x49B509FF, // $C8
+    0x6BDB28FF, // $CA
+    0x8FFE49FF, // $CC
+    0xBBFE69FF, // $CE
+    0x001501FF, // $D0
+    0x103600FF, // $D2
+    0x305900FF, // $D4
+    0x537E00FF, // $D6
+    0x76A300FF, // $D8
+    0x9AC800FF, // $DA
+    0xBFEE1EFF, // $DC
+    0xE8FE3EFF, // $DE
+    0x1A0200FF, // $E0
+    0x3B1F00FF, // $E2
+    0x5E4100FF, // $E4
+    0x836400FF, // $E6
+    0xA88800FF, // $E8
+    0xCEAD00FF, // $EA
+    0xF4D218FF, // $EC
+    0xFEFA40FF, // $EE
+    0x380000FF, // $F0
+    0x5F0800FF, // $F2
+    0x842700FF, // $F4
+    0xAA4900FF, // $F6
+    0xD06B00FF, // $F8
+    0xF68F18FF, // $FA
+    0xFEB439FF, // $FC
+    0xFEDF70FF, // $FE
+];
+
+/// Same as Stella's default PAL palette. Hues $0, $1, $E & $F are greys.
 #[rustfmt::skip]
-pub fn ntsc_tv_config() -> tv::TVConfig<NTSC_SCANLINES, NTSC_PIXELS_PER_SCANLINE> {
-    tv::TVConfig::<NTSC_SCANLINES, NTSC_PIXELS_PER_SCANLINE>::new(
-        160,
-        // From https://www.randomterrain.com/atari-2600-memories-tia-color-charts.html
-        [
-            0x000000FF, // $00
-            0x1A1A1AFF, // $02
-            0x393939FF, // $04
-            0x5B5B5BFF, // $06
-            0x7E7E7EFF, // $08
-            0xA2A2A2FF, // $0A
-            0xC7C7C7FF, // $0C
-            0xEDEDEDFF, // $0E
-            0x190200FF, // $10
-            0x3A1F00FF, // $12
-            0x5D4100FF, // $14
-            0x826400FF, // $16
-            0xA78800FF, // $18
-            0xCCAD00FF, // $1A
-            0xF2D219FF, // $1C
-            0xFEFA40FF, // $1E
-            0x370000FF, // $20
-            0x5E0800FF, // $22
-            0x832700FF, // $24
-            0xA94900FF, // $26
-            0xCF6C00FF, // $28
-            0xF58F17FF, // $2A
-            0xFEB438FF, // $2C
-            0xFEDF6FFF, // $2E
-            0x470000FF, // $30
-            0x730000FF, // $32
-            0x981300FF, // $34
-            0xBE3216FF, // $36
-            0xE45335FF, // $38
-            0xFE7657FF, // $3A
-            0xFE9C81FF, // $3C
-            0xFEC6BBFF, // $3E
-            0x440008FF, // $40
-            0x6F001FFF, // $42
-            0x960640FF, // $44
-            0xBB2462FF, // $46
-            0xE14585FF, // $48
-            0xFE67AAFF, // $4A
-            0xFE8CD6FF, // $4C
-            0xFEB7F6FF, // $4E
-            0x2D004AFF, // $50
-            0x570067FF, // $52
-            0x7D058CFF, // $54
-            0xA122B1FF, // $56
-            0xC743D7FF, // $58
-            0xED65FEFF, // $5A
-            0xFE8AF6FF, // $5C
-            0xFEB5F7FF, // $5E
-            0x0D0082FF, // $60
-            0x3300A2FF, // $62
-            0x550FC9FF, // $64
-            0x782DF0FF, // $66
-            0x9C4EFEFF, // $68
-            0xC372FEFF, // $6A
-            0xEB98FEFF, // $6C
-            0xFEC0F9FF, // $6E
-            0x000091FF, // $70
-            0x0A05BDFF, // $72
-            0x2822E4FF, // $74
-            0x4842FEFF, // $76
-            0x6B64FEFF, // $78
-            0x908AFEFF, // $7A
-            0xB7B0FEFF, // $7C
-            0xDFD8FEFF, // $7E
-            0x000072FF, // $80
-            0x001CABFF, // $82
-            0x033CD6FF, // $84
-            0x205EFDFF, // $86
-            0x4081FEFF, // $88
-            0x64A6FEFF, // $8A
-            0x89CEFEFF, // $8C
-            0xB0F6FEFF, // $8E
-            0x00103AFF, // $90
-            0x00316EFF, // $92
-            0x0055A2FF, // $94
-            0x0579C8FF, // $96
-            0x239DEEFF, // $98
-            0x44C2FEFF, // $9A
-            0x68E9FEFF, // $9C
-            0x8FFEFEFF, // $9E
-            0x001F02FF, // $A0
-            0x004326FF, // $A2
-            0x006957FF, // $A4
-            0x008D7AFF, // $A6
-            0x1BB19EFF, // $A8
-            0x3BD7C3FF, // $AA
-            0x5DFEE9FF, // $AC
-            0x86FEFEFF, // $AE
-            0x002403FF, // $B0
-            0x004A05FF, // $B2
-            0x00700CFF, // $B4
-            0x09952BFF, // $B6
-            0x28BA4CFF, // $B8
-            0x49E06EFF, // $BA
-            0x6CFE92FF, // $BC
-            0x97FEB5FF, // $BE
-            0x002102FF, // $C0
-            0x004604FF, // $C2
-            0x086B00FF, // $C4
-            0x289000FF, // $C6
-            0x49B509FF, // $C8
-            0x6BDB28FF, // $CA
-            0x8FFE49FF, // $CC
-            0xBBFE69FF, // $CE
-            0x001501FF, // $D0
-            0x103600FF, // $D2
-            0x305900FF, // $D4
-            0x537E00FF, // $D6
-            0x76A300FF, // $D8
-            0x9AC800FF, // $DA
-            0xBFEE1EFF, // $DC
-            0xE8FE3EFF, // $DE
-            0x1A0200FF, // $E0
-            0x3B1F00FF, // $E2
-            0x5E4100FF, // $E4
-            0x836400FF, // $E6
-            0xA88800FF, // $E8
-            0xCEAD00FF, // $EA
-            0xF4D218FF, // $EC
-            0xFEFA40FF, // $EE
-            0x380000FF, // $F0
-            0x5F0800FF, // $F2
-            0x842700FF, // $F4
-            0xAA4900FF, // $F6
-            0xD06B00FF, // $F8
-            0xF68F18FF, // $FA
-            0xFEB439FF, // $FC
-            0xFEDF70FF, // $FE
-    ])
+const PAL_PALETTE: [u32; 128] = [
+    0x000000FF, // $00
+    0x2B2B2BFF, // $02
+    0x525252FF, // $04
+    0x767676FF, // $06
+    0x979797FF, // $08
+    0xB6B6B6FF, // $0A
+    0xD2D2D2FF, // $0C
+    0xECECECFF, // $0E
+    0x000000FF, // $10
+    0x2B2B2BFF, // $12
+    0x525252FF, // $14
+    0x767676FF, // $16
+    0x979797FF, // $18
+    0xB6B6B6FF, // $1A
+    0xD2D2D2FF, // $1C
+    0xECECECFF, // $1E
+    0x805800FF, // $20
+    0x96711AFF, // $22
+    0xAB8732FF, // $24
+    0xBE9C48FF, // $26
+    0xCFAF5CFF, // $28
+    0xDFC06FFF, // $2A
+    0xEED180FF, // $2C
+    0xFCE090FF, // $2E
+    0x445C00FF, // $30
+    0x5E791AFF, // $32
+    0x769332FF, // $34
+    0x8CAC48FF, // $36
+    0xA0C25CFF, // $38
+    0xB3D76FFF, // $3A
+    0xC4EA80FF, // $3C
+    0xD4FC90FF, // $3E
+    0x703400FF, // $40
+    0x89511AFF, // $42
+    0xA06B32FF, // $44
+    0xB68448FF, // $46
+    0xC99A5CFF, // $48
+    0xDCAF6FFF, // $4A
+    0xECC280FF, // $4C
+    0xFCD490FF, // $4E
+    0x006414FF, // $50
+    0x1A8035FF, // $52
+    0x329852FF, // $54
+    0x48B06EFF, // $56
+    0x5CC587FF, // $58
+    0x6FD99EFF, // $5A
+    0x80EBB4FF, // $5C
+    0x90FCC8FF, // $5E
+    0x700014FF, // $60
+    0x891A35FF, // $62
+    0xA03252FF, // $64
+    0xB6486EFF, // $66
+    0xC95C87FF, // $68
+    0xDC6F9EFF, // $6A
+    0xEC80B4FF, // $6C
+    0xFC90C8FF, // $6E
+    0x005C5CFF, // $70
+    0x1A7676FF, // $72
+    0x328E8EFF, // $74
+    0x48A4A4FF, // $76
+    0x5CB8B8FF, // $78
+    0x6FCBCBFF, // $7A
+    0x80DCDCFF, // $7C
+    0x90ECECFF, // $7E
+    0x70005CFF, // $80
+    0x841A74FF, // $82
+    0x963289FF, // $84
+    0xA8489EFF, // $86
+    0xB75CB0FF, // $88
+    0xC66FC1FF, // $8A
+    0xD380D1FF, // $8C
+    0xE090E0FF, // $8E
+    0x003C70FF, // $90
+    0x195A89FF, // $92
+    0x2F75A0FF, // $94
+    0x448EB6FF, // $96
+    0x57A5C9FF, // $98
+    0x68BADCFF, // $9A
+    0x79CEECFF, // $9C
+    0x88E0FCFF, // $9E
+    0x580070FF, // $A0
+    0x6E1A89FF, // $A2
+    0x8332A0FF, // $A4
+    0x9648B6FF, // $A6
+    0xA75CC9FF, // $A8
+    0xB86FDCFF, // $AA
+    0xC780ECFF, // $AC
+    0xD690FCFF, // $AE
+    0x002070FF, // $B0
+    0x1A3F89FF, // $B2
+    0x325BA0FF, // $B4
+    0x4874B6FF, // $B6
+    0x5C8BC9FF, // $B8
+    0x6FA0DCFF, // $BA
+    0x80B4ECFF, // $BC
+    0x90C6FCFF, // $BE
+    0x380070FF, // $C0
+    0x501A89FF, // $C2
+    0x6532A0FF, // $C4
+    0x7A48B6FF, // $C6
+    0x8D5CC9FF, // $C8
+    0x9F6FDCFF, // $CA
+    0xB080ECFF, // $CC
+    0xC090FCFF, // $CE
+    0x000070FF, // $D0
+    0x1A1A89FF, // $D2
+    0x3232A0FF, // $D4
+    0x4848B6FF, // $D6
+    0x5C5CC9FF, // $D8
+    0x6F6FDCFF, // $DA
+    0x8080ECFF, // $DC
+    0x9090FCFF, // $DE
+    0x000000FF, // $E0
+    0x2B2B2BFF, // $E2
+    0x525252FF, // $E4
+    0x767676FF, // $E6
+    0x979797FF, // $E8
+    0xB6B6B6FF, // $EA
+    0xD2D2D2FF, // $EC
+    0xECECECFF, // $EE
+    0x000000FF, // $F0
+    0x2B2B2BFF, // $F2
+    0x525252FF, // $F4
+    0x767676FF, // $F6
+    0x979797FF, // $F8
+    0xB6B6B6FF, // $FA
+    0xD2D2D2FF, // $FC
+    0xECECECFF, // $FE
+];
+
+/// Only the luminance bits D3-D1 select a color, the hue is ignored.
+#[rustfmt::skip]
+const SECAM_COLORS: [u32; 8] = [
+    0x000000FF, // $0
+    0x2121FFFF, // $2
+    0xF03C79FF, // $4
+    0xFF50FFFF, // $6
+    0x7FFF00FF, // $8
+    0x7FFFFFFF, // $A
+    0xFFFF3FFF, // $C
+    0xFFFFFFFF, // $E
+];
+
+fn secam_palette() -> [u32; 128] {
+    core::array::from_fn(|x| SECAM_COLORS[x % SECAM_COLORS.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0x0E, 0xECECECFF; "White")]
+    #[test_case(0x2A, 0xDFC06FFF; "Gold")]
+    #[test_case(0x64, 0xA03252FF; "Red")]
+    #[test_case(0xD8, 0x5C5CC9FF; "Blue")]
+    #[test_case(0xF0, 0x000000FF; "Black")]
+    fn pal_colors(color: u8, exp: u32) {
+        assert_eq!(pal_tv_config().color_map()[color as usize >> 1], exp);
+    }
+
+    #[test_case(0x00, 0x000000FF; "Black")]
+    #[test_case(0x04, 0xF03C79FF; "Red")]
+    #[test_case(0x44, 0xF03C79FF; "Red, other hue")]
+    #[test_case(0x9A, 0x7FFFFFFF; "Cyan")]
+    #[test_case(0xFE, 0xFFFFFFFF; "White")]
+    fn secam_ignores_hue(color: u8, exp: u32) {
+        assert_eq!(secam_tv_config().color_map()[color as usize >> 1], exp);
+    }
+
+    #[test]
+    fn standard_of_config() {
+        for x in TVStandard::ALL {
+            assert_eq!(TVStandard::of(&x.tv_config()), Some(x));
+        }
+        let cfg = ntsc_tv_config();
+        let custom = tv::TVConfig::new(200, 228, 160, *cfg.color_map());
+        assert_eq!(TVStandard::of(&custom), None);
+    }
+
+    #[test]
+    fn mixed_standards() {
+        assert_eq!(pal60_tv_config().scanlines(), NTSC_SCANLINES);
+        assert_eq!(pal60_tv_config().color_map(), pal_tv_config().color_map());
+        assert_eq!(ntsc50_tv_config().scanlines(), PAL_SCANLINES);
+        assert_eq!(ntsc50_tv_config().color_map(), ntsc_tv_config().color_map());
+    }
+
+    #[test_case(TVStandard::Ntsc, NTSC_CPU_HZ)]
+    #[test_case(TVStandard::Ntsc50, NTSC_CPU_HZ)]
+    #[test_case(TVStandard::Pal, PAL_CPU_HZ)]
+    #[test_case(TVStandard::Secam, PAL_CPU_HZ)]
+    #[test_case(TVStandard::Pal60, PAL_CPU_HZ)]
+    fn cpu_clock_of_console(standard: TVStandard, exp: u32) {
+        assert_eq!(standard.cpu_hz(), exp);
+    }
 }
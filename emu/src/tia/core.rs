@@ -1,40 +1,90 @@
 use crate::{
     bits,
-    cmn::{Line, LineState},
+    cmn::{fnv1a, Line, LineState, FNV1A_INIT},
+    ctrl::{ControllerPorts, Port},
+    power_on::PowerOnFill,
     riot::MemorySegment,
+    state::{SaveState, StateError, StateReader, StateWriter},
     tia::{
-        cmn,
+        cmn::{self, AUDIO_SAMPLE_RATE},
         tv::{TVConfig, TV},
     },
 };
-use alloc::rc::Rc;
+use alloc::{collections::VecDeque, rc::Rc, vec::Vec};
 use core::cell::RefCell;
 
 // TODO: for debug pass PC to writes
 
+/// Color clocks left in the scanline after RSYNC.
+const RSYNC_CLOCKS: usize = 3;
+
 /// Refer:
 /// - https://www.atarihq.com/danb/files/TIA_HW_Notes.txt
 /// - module README.md
-pub trait TIA: MemorySegment {
+pub trait TIA: MemorySegment + SaveState {
     fn tick(&mut self, cycles: usize);
+
+    /// Checksum of the pixels output during the last complete frame.
+    fn frame_checksum(&self) -> u32;
+
+    /// AUDV0 & AUDV1 levels output since the last call, one pair per audio clock, i.e. every
+    /// [`cmn::CPU_CYCLES_PER_AUDIO_SAMPLE`]. A channel whose waveform is low outputs 0.
+    fn drain_audio(&mut self) -> Vec<[u8; 2]>;
 }
 
-pub struct InMemoryTIA<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
+pub struct InMemoryTIA {
     rdy: Line,
-    tv: Rc<RefCell<dyn TV<SCANLINES, PIXELS_PER_SCANLINE>>>,
-    tv_cfg: TVConfig<SCANLINES, PIXELS_PER_SCANLINE>,
+    tv: Rc<RefCell<dyn TV>>,
+    tv_cfg: TVConfig,
 
     registers: [u8; cmn::TIA_MAX_ADDRESS + 1],
     hsync_counter: usize,
     player_hpos_counters: [usize; 2],
     player_hpos_counters_for_next_scanline: [Option<usize>; 2],
+    missile_hpos_counters: [usize; 2],
+    missile_hpos_counters_for_next_scanline: [Option<usize>; 2],
+    ball_hpos_counter: usize,
+    ball_hpos_counter_for_next_scanline: Option<usize>,
+    /// ENABL as it was on the last GRP1 write, drawn instead of ENABL when VDELBL is set.
+    enabl_delayed: u8,
+    /// "Old" GRP0/GRP1, copied from the "new" ones on a write to the other player's GRP.
+    /// Drawn instead of GRPx when VDELPx is set.
+    grp_delayed: [u8; 2],
+    /// CXM0P-CXPPMM as read, only D7 & D6 are used.
+    collisions: [u8; cx::REGISTERS],
+    /// Set by HMOVE during HBLANK, which then ends 8 pixels late. Cleared at the start of each scanline.
+    extended_hblank: bool,
+    /// HMOVE motion counter, counts up every 4 color clocks from the last HMOVE.
+    motion_clock: u8,
+    /// Objects still receiving extra clocks from the last HMOVE. Indexed by the `cx` object ids.
+    moving: [bool; hm::OBJECTS],
+    /// Color clocks left, address & value of writes yet to take effect.
+    delayed_writes: Vec<(u8, usize, u8)>,
+    /// Audio channels 0 & 1, driven by AUDC0/AUDF0/AUDV0 & AUDC1/AUDF1/AUDV1.
+    channels: [aud::Channel; 2],
+    /// Channel levels not yet drained by the host, capped at [`aud::MAX_QUEUED_SAMPLES`].
+    audio: VecDeque<[u8; 2]>,
+    /// Running checksum of the pixels output since the last VSYNC.
+    pixel_checksum: u32,
+    frame_checksum: u32,
+    ports: Rc<RefCell<ControllerPorts>>,
+    /// INPT4/5 latches, enabled by VBLANK D6. Low once the fire button is pressed.
+    fire_latches: [bool; 2],
+    /// Color clocks the INPT0-3 capacitors have been charging for, since VBLANK D7 last grounded them.
+    pot_clocks: u32,
 }
 
 #[allow(dead_code)]
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
-    InMemoryTIA<SCANLINES, PIXELS_PER_SCANLINE>
-{
-    pub fn new(rdy: Line, tv: Rc<RefCell<dyn TV<SCANLINES, PIXELS_PER_SCANLINE>>>) -> Self {
+impl InMemoryTIA {
+    pub fn new(rdy: Line, tv: Rc<RefCell<dyn TV>>) -> Self {
+        Self::new_with_ports(rdy, tv, Default::default())
+    }
+
+    pub fn new_with_ports(
+        rdy: Line,
+        tv: Rc<RefCell<dyn TV>>,
+        ports: Rc<RefCell<ControllerPorts>>,
+    ) -> Self {
         let tv_cfg = *tv.borrow().config();
         Self {
             rdy,
@@ -44,59 +94,163 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
             hsync_counter: 0,
             player_hpos_counters: [0, 0],
             player_hpos_counters_for_next_scanline: [None, None],
+            missile_hpos_counters: [0, 0],
+            missile_hpos_counters_for_next_scanline: [None, None],
+            ball_hpos_counter: 0,
+            ball_hpos_counter_for_next_scanline: None,
+            enabl_delayed: 0x00,
+            grp_delayed: [0x00, 0x00],
+            collisions: [0x00; cx::REGISTERS],
+            extended_hblank: false,
+            motion_clock: 0,
+            moving: [false; hm::OBJECTS],
+            delayed_writes: Vec::new(),
+            channels: Default::default(),
+            audio: VecDeque::new(),
+            pixel_checksum: FNV1A_INIT,
+            frame_checksum: FNV1A_INIT,
+            ports,
+            fire_latches: [true, true],
+            pot_clocks: 0,
         }
     }
 
+    /// Object positions are undefined at power-on. One little endian u16 per object: players,
+    /// missiles then ball.
+    pub fn power_on(&mut self, fill: PowerOnFill) {
+        let pos: [u8; 10] = fill.bytes();
+        let pos = |x: usize| {
+            u16::from_le_bytes([pos[2 * x], pos[2 * x + 1]]) as usize % self.tv_cfg.visible_pixels()
+        };
+        (0..2).for_each(|x| {
+            self.player_hpos_counters[x] = pos(x);
+            self.missile_hpos_counters[x] = pos(2 + x);
+        });
+        self.ball_hpos_counter = pos(4);
+    }
+
     fn one_tick(&mut self) {
+        self.pot_clocks = if self.pots_dumped() {
+            0
+        } else {
+            self.pot_clocks.saturating_add(1)
+        };
+
+        if self.fire_latches_enabled() {
+            let ports = self.ports.borrow();
+            self.fire_latches[0] &= ports.get(Port::Left).fire();
+            self.fire_latches[1] &= ports.get(Port::Right).fire();
+        }
+
         if self.hsync_counter == 0 {
             self.rdy.set(LineState::High);
+            self.extended_hblank = false;
             (0..2).for_each(|x| {
                 self.player_hpos_counters_for_next_scanline[x] =
                     self.player_hpos_counters_for_next_scanline[x].and_then(|val| {
                         self.player_hpos_counters[x] = val;
                         None
                     });
-            })
+                self.missile_hpos_counters_for_next_scanline[x] =
+                    self.missile_hpos_counters_for_next_scanline[x].and_then(|val| {
+                        self.missile_hpos_counters[x] = val;
+                        None
+                    });
+            });
+            if let Some(val) = self.ball_hpos_counter_for_next_scanline.take() {
+                self.ball_hpos_counter = val;
+            }
         }
 
-        let color = if bits::tst_bits(self.registers[cmn::regs::VBLANK], bits::BIT_D1)
-            || self.is_on_hblank()
-        {
+        self.tick_motion();
+        self.tick_audio();
+        (0..2).for_each(|x| self.lock_missile_to_player(x));
+
+        let color = if self.is_on_hblank() {
             0x00
         } else {
-            grp::get_color(
-                self.player_hpos_counters()[0],
-                self.registers[cmn::regs::GRP0],
-                self.registers[cmn::regs::COLUP0],
-                self.registers[cmn::regs::REFP0],
-            )
-            .or_else(|| {
-                grp::get_color(
-                    self.player_hpos_counters()[1],
-                    self.registers[cmn::regs::GRP1],
-                    self.registers[cmn::regs::COLUP1],
-                    self.registers[cmn::regs::REFP1],
+            // NOTE: Collisions are detected while VBLANK blanks the output too.
+            let objects = self.object_colors();
+            cx::latch(&mut self.collisions, &objects.map(|x| x.is_some()));
+
+            if bits::tst_bits(self.registers[cmn::regs::VBLANK], bits::BIT_D1) {
+                0x00
+            } else {
+                pri::get_color(
+                    &objects,
+                    self.registers[cmn::regs::CTRLPF],
+                    pf::is_first_half(self.hsync_counter, &self.tv_cfg),
                 )
-            })
-            .or_else(|| pf::get_color(self.hsync_counter, &self.registers, &self.tv_cfg))
-            .unwrap_or(self.registers[cmn::regs::COLUBK])
+                .unwrap_or(self.registers[cmn::regs::COLUBK])
+            }
         };
 
         self.tv.borrow_mut().render_pixel(color);
+        self.pixel_checksum = fnv1a(self.pixel_checksum, &[color]);
 
         if !self.is_on_hblank() {
             (0..2).for_each(|x| {
                 self.player_hpos_counters[x] =
                     (self.player_hpos_counters[x] + 1) % self.tv_cfg.visible_pixels();
+                self.missile_hpos_counters[x] =
+                    (self.missile_hpos_counters[x] + 1) % self.tv_cfg.visible_pixels();
             });
+            self.ball_hpos_counter = (self.ball_hpos_counter + 1) % self.tv_cfg.visible_pixels();
         }
         // NOTE: This needs to be done last to signify start of next color clk.
         self.hsync_counter = (self.hsync_counter + 1) % self.tv_cfg.pixels_per_scanline();
+        self.tick_delayed_writes();
+    }
+
+    /// Colour of each object at the current pixel, if drawn. Indexed by the `cx` object ids.
+    fn object_colors(&self) -> [Option<u8>; cx::OBJECTS] {
+        [
+            grp::get_color(
+                self.player_hpos_counters[0],
+                self.player_graphics(0),
+                self.registers[cmn::regs::COLUP0],
+                self.registers[cmn::regs::REFP0],
+                self.registers[cmn::regs::NUSIZ0],
+            ),
+            grp::get_color(
+                self.player_hpos_counters[1],
+                self.player_graphics(1),
+                self.registers[cmn::regs::COLUP1],
+                self.registers[cmn::regs::REFP1],
+                self.registers[cmn::regs::NUSIZ1],
+            ),
+            msl::get_color(
+                self.missile_hpos_counters[0],
+                self.registers[cmn::regs::ENAM0],
+                self.registers[cmn::regs::RESMP0],
+                self.registers[cmn::regs::NUSIZ0],
+                self.registers[cmn::regs::COLUP0],
+            ),
+            msl::get_color(
+                self.missile_hpos_counters[1],
+                self.registers[cmn::regs::ENAM1],
+                self.registers[cmn::regs::RESMP1],
+                self.registers[cmn::regs::NUSIZ1],
+                self.registers[cmn::regs::COLUP1],
+            ),
+            bl::get_color(
+                self.ball_hpos_counter,
+                self.ball_enabled(),
+                self.registers[cmn::regs::CTRLPF],
+                self.registers[cmn::regs::COLUPF],
+            ),
+            pf::get_color(self.hsync_counter, &self.registers, &self.tv_cfg),
+        ]
     }
 
     #[inline]
     pub fn is_on_hblank(&self) -> bool {
-        self.hsync_counter < self.tv_cfg.hblank_pixels()
+        let extended = if self.extended_hblank {
+            hm::EXTENDED_HBLANK
+        } else {
+            0
+        };
+        self.hsync_counter < self.tv_cfg.hblank_pixels() + extended
     }
 
     #[inline]
@@ -109,11 +263,75 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
         &mut self.player_hpos_counters
     }
 
+    #[inline]
+    pub fn missile_hpos_counters(&mut self) -> &mut [usize; 2] {
+        &mut self.missile_hpos_counters
+    }
+
+    #[inline]
+    pub fn ball_hpos_counter(&mut self) -> &mut usize {
+        &mut self.ball_hpos_counter
+    }
+
+    /// GRPx, or its "old" copy while VDELPx is set.
+    #[inline]
+    fn player_graphics(&self, id: usize) -> u8 {
+        if bits::tst_bits(self.registers[cmn::regs::VDELP0 + id], bits::BIT_D0) {
+            self.grp_delayed[id]
+        } else {
+            self.registers[cmn::regs::GRP0 + id]
+        }
+    }
+
+    /// ENABL, or its copy from the last GRP1 write while VDELBL is set.
+    #[inline]
+    fn ball_enabled(&self) -> bool {
+        let enabl = if bits::tst_bits(self.registers[cmn::regs::VDELBL], bits::BIT_D0) {
+            self.enabl_delayed
+        } else {
+            self.registers[cmn::regs::ENABL]
+        };
+        bits::tst_bits(enabl, bits::BIT_D1)
+    }
+
+    #[inline]
+    fn fire_latches_enabled(&self) -> bool {
+        bits::tst_bits(self.registers[cmn::regs::VBLANK], bits::BIT_D6)
+    }
+
+    #[inline]
+    fn pots_dumped(&self) -> bool {
+        bits::tst_bits(self.registers[cmn::regs::VBLANK], bits::BIT_D7)
+    }
+
+    /// Level of INPT0-3. High once the capacitor has charged for as long as the pot takes.
+    ///
+    /// Refer: https://problemkaputt.de/2k6specs.htm#controllerspaddles
+    fn pot_input(&self, port: Port, index: usize) -> bool {
+        let charge = self.ports.borrow().get(port).pot(index);
+        !self.pots_dumped()
+            && charge.is_some_and(|x| {
+                self.pot_clocks >= x as u32 * self.tv_cfg.pixels_per_scanline() as u32
+            })
+    }
+
+    /// Level of the fire button input, through the latch if enabled.
+    fn fire_input(&self, port: Port) -> bool {
+        let pin = self.ports.borrow().get(port).fire();
+        if self.fire_latches_enabled() {
+            pin && self.fire_latches[port as usize]
+        } else {
+            pin
+        }
+    }
+
     #[cfg(debug_assertions)]
     #[inline]
     fn check_read_unsupported_register_flags(&self, addr: usize) {
-        let (_, _, name, _) = cmn::regs::IMPLEMENTED_REGISTERS[addr];
-        log::error!("Read for {name} ({addr:02X}) is not implemented yet.")
+        let (r, name) = cmn::read_regs::IMPLEMENTED_REGISTERS[addr];
+        if !r {
+            log::error!("Read for {name} ({addr:02X}) is not implemented yet.")
+        }
     }
 
     #[cfg(debug_assertions)]
@@ -131,60 +349,164 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
         }
     }
 
+    /// Position counter value after a reset strobe, for an object that starts drawing `delay`
+    /// pixels after it. During HBLANK the object starts `delay - 2` pixels into the visible line.
+    fn reset_hpos_counter(&self, delay: usize) -> usize {
+        if self.is_on_hblank() {
+            self.tv_cfg.visible_pixels() - (delay - 2)
+        } else {
+            self.tv_cfg.visible_pixels()
+                - (self.hsync_counter - self.tv_cfg.hblank_pixels() + delay)
+                    % self.tv_cfg.visible_pixels()
+        }
+    }
+
     fn initialize_player_hpos_counter(&mut self, player_id: usize) {
+        let val = self.reset_hpos_counter(grp::START_DELAY);
         if self.is_on_hblank() {
-            self.player_hpos_counters[player_id] = self.tv_cfg.visible_pixels() - 3;
+            self.player_hpos_counters[player_id] = val;
         } else {
-            self.player_hpos_counters_for_next_scanline[player_id] = Some(
-                self.tv_cfg.visible_pixels()
-                    - (self.hsync_counter - self.tv_cfg.hblank_pixels() + 5)
-                        % self.tv_cfg.visible_pixels(),
-            );
+            self.player_hpos_counters_for_next_scanline[player_id] = Some(val);
         };
     }
 
-    fn update_player_hpos_counter(&mut self, player_id: usize) {
-        let temp = self.tv_cfg.visible_pixels() + self.player_hpos_counters[player_id];
-        let delta = (self.registers[cmn::regs::HMP0 + player_id] as i8 as isize) / 0x10;
-        self.player_hpos_counters[player_id] =
-            temp.wrapping_add_signed(delta) % self.tv_cfg.visible_pixels();
+    fn initialize_missile_hpos_counter(&mut self, missile_id: usize) {
+        let val = self.reset_hpos_counter(msl::START_DELAY);
+        if self.is_on_hblank() {
+            self.missile_hpos_counters[missile_id] = val;
+        } else {
+            self.missile_hpos_counters_for_next_scanline[missile_id] = Some(val);
+        };
     }
-}
 
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> TIA
-    for InMemoryTIA<SCANLINES, PIXELS_PER_SCANLINE>
-{
-    fn tick(&mut self, cycles: usize) {
-        for _ in 0..cycles {
-            self.one_tick();
+    fn initialize_ball_hpos_counter(&mut self) {
+        let val = self.reset_hpos_counter(bl::START_DELAY);
+        if self.is_on_hblank() {
+            self.ball_hpos_counter = val;
+        } else {
+            self.ball_hpos_counter_for_next_scanline = Some(val);
+        };
+    }
+
+    fn hpos_counter_mut(&mut self, id: usize) -> &mut usize {
+        match id {
+            cx::P0 | cx::P1 => &mut self.player_hpos_counters[id - cx::P0],
+            cx::M0 | cx::M1 => &mut self.missile_hpos_counters[id - cx::M0],
+            _ => &mut self.ball_hpos_counter,
         }
     }
-}
 
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> MemorySegment
-    for InMemoryTIA<SCANLINES, PIXELS_PER_SCANLINE>
-{
-    fn read(&self, _addr: usize) -> u8 {
-        #[cfg(debug_assertions)]
-        self.check_read_unsupported_register_flags(_addr);
+    /// Every 4 color clocks after HMOVE, objects that have not yet had as many extra clocks as
+    /// their HMxx asks for get one more. Only clocks that land on HBLANK move an object, as it
+    /// also gets its normal clock otherwise.
+    ///
+    /// Refer: https://www.atarihq.com/danb/files/TIA_HW_Notes.txt
+    fn tick_motion(&mut self) {
+        if !self.moving.contains(&true) || !self.hsync_counter.is_multiple_of(hm::CLOCK_PERIOD) {
+            return;
+        }
 
-        0
+        let hblank = self.is_on_hblank();
+        let visible = self.tv_cfg.visible_pixels();
+        for id in 0..hm::OBJECTS {
+            if self.motion_clock == hm::clocks(self.registers[cmn::regs::HMP0 + id]) {
+                self.moving[id] = false;
+            }
+            if self.moving[id] && hblank {
+                let counter = self.hpos_counter_mut(id);
+                *counter = (*counter + 1) % visible;
+            }
+        }
+        self.motion_clock += 1;
     }
 
-    fn write(&mut self, addr: usize, val: u8) {
-        let (_, selector, _, _) = cmn::regs::IMPLEMENTED_REGISTERS[addr];
-        let val = val & selector;
+    /// Audio is clocked twice per scanline, each channel outputting its level to the queue.
+    fn tick_audio(&mut self) {
+        let period = self.tv_cfg.pixels_per_scanline() / aud::CLOCKS_PER_SCANLINE;
+        if !self.hsync_counter.is_multiple_of(period) {
+            return;
+        }
 
-        #[cfg(debug_assertions)]
-        self.check_write_unsupported_register_flags(addr, val);
+        let levels: [u8; 2] = core::array::from_fn(|x| {
+            self.channels[x].tick(
+                self.registers[cmn::regs::AUDC0 + x],
+                self.registers[cmn::regs::AUDF0 + x],
+                self.registers[cmn::regs::AUDV0 + x],
+            )
+        });
+        if self.audio.len() == aud::MAX_QUEUED_SAMPLES {
+            self.audio.pop_front();
+        }
+        self.audio.push_back(levels);
+    }
+
+    /// While RESMPx is set the missile follows the centre of its player.
+    fn lock_missile_to_player(&mut self, id: usize) {
+        if !bits::tst_bits(self.registers[cmn::regs::RESMP0 + id], bits::BIT_D1) {
+            return;
+        }
+
+        let offset = msl::centre_offset(self.registers[cmn::regs::NUSIZ0 + id]);
+        let visible = self.tv_cfg.visible_pixels();
+        self.missile_hpos_counters[id] =
+            (self.player_hpos_counters[id] + visible - offset) % visible;
+        self.missile_hpos_counters_for_next_scanline[id] = None;
+    }
+
+    /// Ends the scanline early: the horizontal counter skips to 3 color clocks before its end,
+    /// with the pixels skipped over left black. Objects are not clocked for those pixels.
+    ///
+    /// NOTE: Strobed within the last 3 color clocks it would lengthen the scanline instead,
+    /// which the TV cannot follow, so the scanline just ends as usual.
+    ///
+    /// Refer: https://github.com/stella-emu/stella/blob/master/src/emucore/tia/TIA.cxx
+    fn rsync(&mut self) {
+        let end = self.tv_cfg.pixels_per_scanline() - RSYNC_CLOCKS;
+        if self.hsync_counter >= end {
+            return;
+        }
 
+        (self.hsync_counter..end).for_each(|_| {
+            self.tv.borrow_mut().render_pixel(0x00);
+            self.pixel_checksum = fnv1a(self.pixel_checksum, &[0x00]);
+        });
+        self.hsync_counter = end;
+    }
+
+    /// Writes get a color clock closer to taking effect, those whose delay has passed do.
+    fn tick_delayed_writes(&mut self) {
+        let mut i = 0;
+        while i < self.delayed_writes.len() {
+            self.delayed_writes[i].0 -= 1;
+            if self.delayed_writes[i].0 == 0 {
+                let (_, addr, val) = self.delayed_writes.remove(i);
+                self.apply_write(addr, val);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn apply_write(&mut self, addr: usize, val: u8) {
         match addr {
             cmn::regs::WSYNC => {
                 self.rdy.set(LineState::Low);
             }
 
+            cmn::regs::RSYNC => {
+                self.rsync();
+            }
+
+            cmn::regs::VBLANK if !bits::tst_bits(val, bits::BIT_D6) => {
+                self.fire_latches = [true, true];
+            }
+
             cmn::regs::VSYNC => {
                 if bits::tst_bits(val, bits::BIT_D1) {
+                    if !self.tv.borrow().vsync_on() {
+                        self.frame_checksum = self.pixel_checksum;
+                        self.pixel_checksum = FNV1A_INIT;
+                    }
                     self.tv.borrow_mut().vsync_start();
                 } else {
                     self.tv.borrow_mut().vsync_end();
@@ -195,9 +517,32 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> MemorySegment
                 self.initialize_player_hpos_counter(addr - cmn::regs::RESP0);
             }
 
-            cmn::regs::HMOVE => (0..2).for_each(|x| {
-                self.update_player_hpos_counter(x);
-            }),
+            cmn::regs::RESM0..=cmn::regs::RESM1 => {
+                self.initialize_missile_hpos_counter(addr - cmn::regs::RESM0);
+            }
+
+            cmn::regs::RESBL => {
+                self.initialize_ball_hpos_counter();
+            }
+
+            cmn::regs::GRP0 => {
+                self.grp_delayed[1] = self.registers[cmn::regs::GRP1];
+            }
+
+            cmn::regs::GRP1 => {
+                self.grp_delayed[0] = self.registers[cmn::regs::GRP0];
+                self.enabl_delayed = self.registers[cmn::regs::ENABL];
+            }
+
+            cmn::regs::HMOVE => {
+                self.extended_hblank |= self.is_on_hblank();
+                self.motion_clock = 0;
+                self.moving = [true; hm::OBJECTS];
+            }
+
+            cmn::regs::CXCLR => {
+                self.collisions = [0x00; cx::REGISTERS];
+            }
 
             cmn::regs::HMCLR => {
                 self.registers[cmn::regs::HMP0] = 0;
@@ -214,11 +559,223 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> MemorySegment
     }
 }
 
+impl TIA for InMemoryTIA {
+    fn tick(&mut self, cycles: usize) {
+        for _ in 0..cycles {
+            self.one_tick();
+        }
+    }
+
+    #[inline]
+    fn frame_checksum(&self) -> u32 {
+        self.frame_checksum
+    }
+
+    fn drain_audio(&mut self) -> Vec<[u8; 2]> {
+        self.audio.drain(..).collect()
+    }
+}
+
+/// NOTE: The TV is saved separately, by the owner of the machine.
+impl SaveState for InMemoryTIA {
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_bool(self.rdy.get() == LineState::High);
+        self.registers.iter().for_each(|&x| w.put_u8(x));
+        w.put_u16(self.hsync_counter as u16);
+        (0..2).for_each(|x| {
+            w.put_u16(self.player_hpos_counters[x] as u16);
+            w.put_bool(self.player_hpos_counters_for_next_scanline[x].is_some());
+            w.put_u16(self.player_hpos_counters_for_next_scanline[x].unwrap_or(0) as u16);
+        });
+        w.put_u32(self.pixel_checksum);
+        w.put_u32(self.frame_checksum);
+        self.fire_latches.iter().for_each(|&x| w.put_bool(x));
+        w.put_u32(self.pot_clocks);
+        (0..2).for_each(|x| {
+            w.put_u16(self.missile_hpos_counters[x] as u16);
+            w.put_bool(self.missile_hpos_counters_for_next_scanline[x].is_some());
+            w.put_u16(self.missile_hpos_counters_for_next_scanline[x].unwrap_or(0) as u16);
+        });
+        w.put_u16(self.ball_hpos_counter as u16);
+        w.put_bool(self.ball_hpos_counter_for_next_scanline.is_some());
+        w.put_u16(self.ball_hpos_counter_for_next_scanline.unwrap_or(0) as u16);
+        w.put_u8(self.enabl_delayed);
+        self.grp_delayed.iter().for_each(|&x| w.put_u8(x));
+        self.collisions.iter().for_each(|&x| w.put_u8(x));
+        w.put_bool(self.extended_hblank);
+        w.put_u8(self.motion_clock);
+        self.moving.iter().for_each(|&x| w.put_bool(x));
+        w.put_u8(self.delayed_writes.len() as u8);
+        self.delayed_writes.iter().for_each(|&(clocks, addr, val)| {
+            w.put_u8(clocks);
+            w.put_u8(addr as u8);
+            w.put_u8(val);
+        });
+        self.channels.iter().for_each(|x| x.save_state(w));
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.rdy.set(if r.get_bool()? {
+            LineState::High
+        } else {
+            LineState::Low
+        });
+        for x in self.registers.iter_mut() {
+            *x = r.get_u8()?;
+        }
+        self.hsync_counter = r.get_u16()? as usize;
+        if self.hsync_counter >= self.tv_cfg.pixels_per_scanline() {
+            return Err(r.corrupt());
+        }
+        for x in 0..2 {
+            self.player_hpos_counters[x] = r.get_u16()? as usize;
+            let pending = r.get_bool()?;
+            let val = r.get_u16()? as usize;
+            self.player_hpos_counters_for_next_scanline[x] = pending.then_some(val);
+        }
+        if self
+            .player_hpos_counters
+            .iter()
+            .any(|&x| x >= self.tv_cfg.visible_pixels())
+        {
+            return Err(r.corrupt());
+        }
+        (self.pixel_checksum, self.frame_checksum) = if r.version() >= 2 {
+            (r.get_u32()?, r.get_u32()?)
+        } else {
+            (FNV1A_INIT, FNV1A_INIT)
+        };
+        self.fire_latches = if r.version() >= 4 {
+            [r.get_bool()?, r.get_bool()?]
+        } else {
+            [true, true]
+        };
+        self.pot_clocks = if r.version() >= 7 { r.get_u32()? } else { 0 };
+        (
+            self.missile_hpos_counters,
+            self.missile_hpos_counters_for_next_scanline,
+        ) = ([0, 0], [None, None]);
+        if r.version() >= 8 {
+            for x in 0..2 {
+                self.missile_hpos_counters[x] = r.get_u16()? as usize;
+                let pending = r.get_bool()?;
+                let val = r.get_u16()? as usize;
+                self.missile_hpos_counters_for_next_scanline[x] = pending.then_some(val);
+            }
+            if self
+                .missile_hpos_counters
+                .iter()
+                .any(|&x| x >= self.tv_cfg.visible_pixels())
+            {
+                return Err(r.corrupt());
+            }
+        }
+        (
+            self.ball_hpos_counter,
+            self.ball_hpos_counter_for_next_scanline,
+            self.enabl_delayed,
+        ) = (0, None, 0x00);
+        if r.version() >= 9 {
+            self.ball_hpos_counter = r.get_u16()? as usize;
+            let pending = r.get_bool()?;
+            let val = r.get_u16()? as usize;
+            self.ball_hpos_counter_for_next_scanline = pending.then_some(val);
+            self.enabl_delayed = r.get_u8()?;
+            if self.ball_hpos_counter >= self.tv_cfg.visible_pixels() {
+                return Err(r.corrupt());
+            }
+        }
+        self.grp_delayed = if r.version() >= 10 {
+            [r.get_u8()?, r.get_u8()?]
+        } else {
+            [0x00, 0x00]
+        };
+        self.collisions = [0x00; cx::REGISTERS];
+        if r.version() >= 11 {
+            for x in self.collisions.iter_mut() {
+                *x = r.get_u8()?;
+            }
+        }
+        (self.extended_hblank, self.motion_clock, self.moving) = (false, 0, [false; hm::OBJECTS]);
+        if r.version() >= 12 {
+            self.extended_hblank = r.get_bool()?;
+            self.motion_clock = r.get_u8()?;
+            for x in self.moving.iter_mut() {
+                *x = r.get_bool()?;
+            }
+            if self.motion_clock > hm::MAX_CLOCKS + 1 {
+                return Err(r.corrupt());
+            }
+        }
+        self.delayed_writes.clear();
+        if r.version() >= 13 {
+            for _ in 0..r.get_u8()? {
+                let (clocks, addr, val) = (r.get_u8()?, r.get_u8()? as usize, r.get_u8()?);
+                if addr > cmn::TIA_MAX_ADDRESS {
+                    return Err(r.corrupt());
+                }
+                self.delayed_writes.push((clocks, addr, val));
+            }
+        }
+        self.channels = Default::default();
+        if r.version() >= 14 {
+            for x in self.channels.iter_mut() {
+                x.load_state(r)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MemorySegment for InMemoryTIA {
+    fn read(&self, addr: usize) -> u8 {
+        let addr = addr & cmn::read_regs::ADDRESS_MASK;
+
+        #[cfg(debug_assertions)]
+        self.check_read_unsupported_register_flags(addr);
+
+        if addr < cx::REGISTERS {
+            return self.collisions[addr];
+        }
+
+        let high = match addr {
+            cmn::read_regs::INPT0 => self.pot_input(Port::Left, 0),
+            cmn::read_regs::INPT1 => self.pot_input(Port::Left, 1),
+            cmn::read_regs::INPT2 => self.pot_input(Port::Right, 0),
+            cmn::read_regs::INPT3 => self.pot_input(Port::Right, 1),
+            cmn::read_regs::INPT4 => self.fire_input(Port::Left),
+            cmn::read_regs::INPT5 => self.fire_input(Port::Right),
+            _ => false,
+        };
+
+        if high {
+            bits::BIT_D7
+        } else {
+            0
+        }
+    }
+
+    fn write(&mut self, addr: usize, val: u8) {
+        let (_, selector, _, _) = cmn::regs::IMPLEMENTED_REGISTERS[addr];
+        let val = val & selector;
+
+        #[cfg(debug_assertions)]
+        self.check_write_unsupported_register_flags(addr, val);
+
+        match dly::clocks(addr, self.hsync_counter, self.tv_cfg.hblank_pixels()) {
+            0 => self.apply_write(addr, val),
+            clocks => self.delayed_writes.push((clocks, addr, val)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         cmn::{LineState, RefExtensions},
+        ctrl::{ControllerInput, JoystickState, PaddlesState, POT_MAX_SCANLINES},
         tia::tv::*,
     };
     use core::cell::Cell;
@@ -235,15 +792,21 @@ mod tests {
     ///  1│x  │  
     ///  2│x  │  
     ///   └───┘            
-    type TestableTV = InMemoryTV<SCANLINES, PIXELS_PER_SCANLINE>;
-    type TestableTVConfig = TVConfig<SCANLINES, PIXELS_PER_SCANLINE>;
+    fn testable_config() -> TVConfig {
+        TVConfig::new(
+            SCANLINES,
+            PIXELS_PER_SCANLINE,
+            PIXELS_PER_SCANLINE - 1,
+            [0x00; 128],
+        )
+    }
 
     #[test_case(0, 0, 3, LineState::High)]
     #[test_case(1, 1, 2, LineState::High)]
     #[test_case(2, 2, 1, LineState::Low)]
     fn test_wsync(scanline: usize, pixel: usize, ticks: usize, final_line_state: LineState) {
         let rdy = LineState::Low.rc_cell();
-        let tv = TestableTV::new_testable(scanline, pixel, TestableTVConfig::default());
+        let tv = InMemoryTV::new_testable(scanline, pixel, testable_config());
         let mut tia = InMemoryTIA::new(rdy.clone(), Rc::new(RefCell::new(tv)));
 
         assert_eq!(rdy.get(), LineState::Low);
@@ -269,7 +832,7 @@ mod tests {
     fn render_solid_display() {
         let rdy = LineState::Low.rc_cell();
         let cfg = solid_display_config();
-        let tv = Rc::new(RefCell::new(InMemoryTV::<5, 3>::new_testable(0, 0, cfg)));
+        let tv = Rc::new(RefCell::new(InMemoryTV::new_testable(0, 0, cfg)));
         let mut tia = InMemoryTIA::new(rdy.clone(), tv.clone());
 
         tia.write(cmn::regs::VBLANK, bits::BIT_D1);
@@ -333,8 +896,8 @@ mod tests {
     #[test_case(1, 0)]
     fn test_vsync(scanline: usize, pixel: usize) {
         let rdy = LineState::Low.rc_cell();
-        let cfg = TestableTVConfig::default();
-        let tv = Rc::new(RefCell::new(TestableTV::new_testable(scanline, pixel, cfg)));
+        let cfg = testable_config();
+        let tv = Rc::new(RefCell::new(InMemoryTV::new_testable(scanline, pixel, cfg)));
         let mut tia = InMemoryTIA::new(rdy.clone(), tv.clone());
 
         tia.tick(2);
@@ -356,7 +919,7 @@ mod tests {
     fn test_vblank() {
         let rdy = LineState::Low.rc_cell();
         let cfg = solid_display_config();
-        let tv = Rc::new(RefCell::new(InMemoryTV::<5, 3>::new_testable(0, 0, cfg)));
+        let tv = Rc::new(RefCell::new(InMemoryTV::new_testable(0, 0, cfg)));
         let mut tia = InMemoryTIA::new(rdy.clone(), tv.clone());
 
         (0..cfg.pixels_per_scanline()).for_each(|_| tia.tick(1));
@@ -375,8 +938,118 @@ mod tests {
         assert_eq!(tv.borrow().buffer()[3], [0x00, 0x02, 0x02]);
     }
 
-    fn solid_display_config() -> TVConfig<5, 3> {
-        TVConfig::<5, 3>::new(2, [0x00; 128])
+    fn press_fire(ports: &Rc<RefCell<ControllerPorts>>, port: Port, fire: bool) {
+        let state = JoystickState {
+            fire,
+            ..Default::default()
+        };
+        ports
+            .borrow_mut()
+            .get_mut(port)
+            .set_input(ControllerInput::Joystick(state));
+    }
+
+    #[test_case(Port::Left, cmn::read_regs::INPT4)]
+    #[test_case(Port::Right, cmn::read_regs::INPT5)]
+    #[test_case(Port::Right, cmn::read_regs::INPT5 + 0x30; "Mirrored")]
+    fn fire_buttons_without_latch(port: Port, addr: usize) {
+        let ports = ControllerPorts::default().rc_refcell();
+        let tv = InMemoryTV::new_testable(0, 0, testable_config());
+        let mut tia =
+            InMemoryTIA::new_with_ports(LineState::Low.rc_cell(), tv.rc_refcell(), ports.clone());
+
+        assert_eq!(tia.read(addr), bits::BIT_D7);
+        press_fire(&ports, port, true);
+        assert_eq!(tia.read(addr), 0x00);
+        tia.tick(1);
+        press_fire(&ports, port, false);
+        assert_eq!(tia.read(addr), bits::BIT_D7);
+    }
+
+    #[test]
+    fn fire_buttons_with_latch() {
+        let ports = ControllerPorts::default().rc_refcell();
+        let tv = InMemoryTV::new_testable(0, 0, testable_config());
+        let mut tia =
+            InMemoryTIA::new_with_ports(LineState::Low.rc_cell(), tv.rc_refcell(), ports.clone());
+
+        tia.write(cmn::regs::VBLANK, bits::BIT_D6);
+        tia.tick(1);
+        press_fire(&ports, Port::Left, true);
+        tia.tick(1);
+        press_fire(&ports, Port::Left, false);
+        assert_eq!(tia.read(cmn::read_regs::INPT4), 0x00);
+        assert_eq!(tia.read(cmn::read_regs::INPT5), bits::BIT_D7);
+        tia.tick(10);
+        assert_eq!(tia.read(cmn::read_regs::INPT4), 0x00);
+
+        tia.write(cmn::regs::VBLANK, 0x00);
+        tia.tick(1);
+        assert_eq!(tia.read(cmn::read_regs::INPT4), bits::BIT_D7);
+        tia.write(cmn::regs::VBLANK, bits::BIT_D6);
+        assert_eq!(tia.read(cmn::read_regs::INPT4), bits::BIT_D7);
+    }
+
+    #[test]
+    fn paddle_pots_charge_after_dump() {
+        let ports = ControllerPorts::default().rc_refcell();
+        let tv = InMemoryTV::new_testable(0, 0, testable_config());
+        let mut tia =
+            InMemoryTIA::new_with_ports(LineState::Low.rc_cell(), tv.rc_refcell(), ports.clone());
+        let state = PaddlesState {
+            position: [2.0 / POT_MAX_SCANLINES as f32, 0.0],
+            fire: [false, false],
+        };
+        ports
+            .borrow_mut()
+            .set_input(Port::Left, ControllerInput::Paddles(state));
+
+        tia.write(cmn::regs::VBLANK, bits::BIT_D7);
+        tia.tick(1000);
+        (cmn::read_regs::INPT0..=cmn::read_regs::INPT3)
+            .for_each(|addr| assert_eq!(tia.read(addr), 0x00));
+
+        tia.write(cmn::regs::VBLANK, 0x00);
+        tia.tick(1);
+        assert_eq!(tia.read(cmn::read_regs::INPT1), bits::BIT_D7);
+        tia.tick(2 * PIXELS_PER_SCANLINE - 1);
+        assert_eq!(tia.read(cmn::read_regs::INPT0), 0x00);
+        tia.tick(1);
+        assert_eq!(tia.read(cmn::read_regs::INPT0), bits::BIT_D7);
+        tia.tick(1000);
+        assert_eq!(tia.read(cmn::read_regs::INPT2), 0x00);
+        assert_eq!(tia.read(cmn::read_regs::INPT3), 0x00);
+
+        tia.write(cmn::regs::VBLANK, bits::BIT_D7);
+        tia.tick(1);
+        assert_eq!(tia.read(cmn::read_regs::INPT0), 0x00);
+    }
+
+    const HI: u8 = bits::BIT_D7;
+
+    /// `[INPTx pin 9, INPTx pin 5, INPT4/5]` of `port`, after the RIOT drove SWCHA.
+    #[test_case(Port::Left, "keypad 3", 0xF0, 0xE0, [HI, HI, 0x00]; "Keypad right column")]
+    #[test_case(Port::Left, "keypad 3", 0xF0, 0xD0, [HI, HI, HI]; "Keypad other row")]
+    #[test_case(Port::Right, "keypad 0", 0x0F, 0x07, [HI, 0x00, HI]; "Keypad middle column")]
+    #[test_case(Port::Right, "genesis up+c", 0x00, 0x00, [0x00, 0x00, HI]; "Genesis C pressed")]
+    #[test_case(Port::Right, "genesis fire", 0x00, 0x00, [0x00, HI, 0x00]; "Genesis B pressed")]
+    fn column_inputs(port: Port, input: &str, ddr: u8, out: u8, exp: [u8; 3]) {
+        let ports = ControllerPorts::default().rc_refcell();
+        let tv = InMemoryTV::new_testable(0, 0, testable_config());
+        let tia =
+            InMemoryTIA::new_with_ports(LineState::Low.rc_cell(), tv.rc_refcell(), ports.clone());
+        ports.borrow_mut().set_input(port, input.parse().unwrap());
+        ports.borrow_mut().drive_swcha(out, ddr);
+
+        let (pots, fire) = match port {
+            Port::Left => (cmn::read_regs::INPT0, cmn::read_regs::INPT4),
+            Port::Right => (cmn::read_regs::INPT2, cmn::read_regs::INPT5),
+        };
+        assert_eq!([tia.read(pots), tia.read(pots + 1), tia.read(fire)], exp);
+    }
+
+    fn solid_display_config() -> TVConfig {
+        TVConfig::new(5, 3, 2, [0x00; 128])
     }
 
     #[test_case(0, 0; "Dormant during HBLANK - 0")]
@@ -391,7 +1064,7 @@ mod tests {
     #[test_case(228+69, 1; "Wrap around - 4")]
     fn playern_hpos_counter_no_reset(color_clks_done: usize, expected: usize) {
         let tv_cfg = cmn::ntsc_tv_config();
-        let tv = cmn::NtscTV::new_testable(3, 0, tv_cfg);
+        let tv = InMemoryTV::new_testable(3, 0, tv_cfg);
         let mut tia = InMemoryTIA::new(LineState::Low.rc_cell(), tv.rc_refcell());
 
         tia.tick(color_clks_done);
@@ -432,7 +1105,7 @@ mod tests {
     /// NOTE: These tests cases have been hand checked with stella + online sources discussing course aligning player sprites.
     fn player0_hpos_counter_with_reset(color_clks_done: usize, expected: usize) {
         let rdy = LineState::Low.rc_cell();
-        let tv = cmn::NtscTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
         let tia = InMemoryTIA::new(rdy.clone(), tv.rc_refcell()).rc_refcell();
 
         tia.borrow_mut().write(cmn::regs::RESP0, 0x00);
@@ -464,24 +1137,319 @@ mod tests {
         expected: usize,
     ) {
         let rdy = LineState::Low.rc_cell();
-        let tv = cmn::NtscTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
         let tia = InMemoryTIA::new(rdy.clone(), tv.rc_refcell()).rc_refcell();
 
         tia.borrow_mut().tick(color_clks_done);
         tia.borrow_mut().write(cmn::regs::RESP0 + player_id, 0x00);
-        wsync(tia.clone(), rdy);
+        wsync(tia.clone(), rdy.clone());
         tia.borrow_mut().write(cmn::regs::HMP0 + player_id, hmp0);
         tia.borrow_mut().write(cmn::regs::HMOVE, 0x00);
+        wsync(tia.clone(), rdy);
         assert_eq!(tia.borrow_mut().player_hpos_counters()[player_id], expected);
     }
 
-    fn wsync(tia: Rc<RefCell<dyn TIA>>, rdy: Rc<Cell<LineState>>) {
-        tia.borrow_mut().write(cmn::regs::WSYNC, 0x00);
-        while rdy.get() == LineState::Low {
-            tia.borrow_mut().tick(1)
-        }
+    #[test_case(0, 50*3, 0b_0000_0000, 74; "Stay in place - 0")]
+    #[test_case(1, 50*3, 0b_0000_0000, 74; "Stay in place - 1")]
+    #[test_case(0, 50*3, 0b_0111_0000, 81; "Move right - 0")]
+    #[test_case(1, 50*3, 0b_1000_0000, 66; "Move left - 1")]
+    fn missile_hpos_counter_with_hmove(
+        missile_id: usize,
+        color_clks_done: usize,
+        hmm: u8,
+        expected: usize,
+    ) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let tia = InMemoryTIA::new(rdy.clone(), tv.rc_refcell()).rc_refcell();
+
+        tia.borrow_mut().tick(color_clks_done);
+        tia.borrow_mut().write(cmn::regs::RESM0 + missile_id, 0x00);
+        wsync(tia.clone(), rdy.clone());
+        tia.borrow_mut().write(cmn::regs::HMM0 + missile_id, hmm);
+        tia.borrow_mut().write(cmn::regs::HMOVE, 0x00);
+        wsync(tia.clone(), rdy);
+        assert_eq!(
+            tia.borrow_mut().missile_hpos_counters()[missile_id],
+            expected
+        );
     }
-}
+
+    #[test_case(1, 0x70, 7; "Early - all clocks")]
+    #[test_case(1, 0x80, 152; "Early - no clocks")]
+    #[test_case(1, 0x00, 0; "Early - stay in place")]
+    #[test_case(100, 0x70, 0; "Mid line - no clocks in HBLANK")]
+    #[test_case(215, 0x70, 14; "Late - clocks in next HBLANK")]
+    #[test_case(215, 0x00, 7; "Late - stay in place moves")]
+    fn hmove_moves_by_clocks_in_hblank(hmove_at: usize, hmbl: u8, delta: usize) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let tia = InMemoryTIA::new(rdy.clone(), tv.rc_refcell()).rc_refcell();
+
+        tia.borrow_mut().tick(50 * 3);
+        tia.borrow_mut().write(cmn::regs::RESBL, 0x00);
+        wsync(tia.clone(), rdy.clone());
+        let start = *tia.borrow_mut().ball_hpos_counter();
+        tia.borrow_mut().write(cmn::regs::HMBL, hmbl);
+        tia.borrow_mut().tick(hmove_at - 1);
+        tia.borrow_mut().write(cmn::regs::HMOVE, 0x00);
+        wsync(tia.clone(), rdy.clone());
+        wsync(tia.clone(), rdy);
+
+        assert_eq!(*tia.borrow_mut().ball_hpos_counter(), (start + delta) % 160);
+    }
+
+    #[test_case(1, &[68, 75], 76; "Early HMOVE extends HBLANK")]
+    #[test_case(100, &[], 68; "Mid line HMOVE does not")]
+    fn hmove_blanks_extended_hblank(hmove_at: usize, black: &[usize], first_bk: usize) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config()).rc_refcell();
+        let tia = InMemoryTIA::new(rdy.clone(), tv.clone()).rc_refcell();
+
+        tia.borrow_mut().write(cmn::regs::COLUBK, 0x10);
+        tia.borrow_mut().tick(hmove_at);
+        tia.borrow_mut().write(cmn::regs::HMOVE, 0x00);
+        wsync(tia.clone(), rdy);
+
+        let line = tv.borrow().buffer()[3].clone();
+        black
+            .iter()
+            .for_each(|&x| assert_eq!(line[x], 0x00, "pixel {x}"));
+        assert_eq!(line[first_bk - 1], 0x00);
+        assert_eq!(line[first_bk], 0x10);
+    }
+
+    #[test_case(0x00, 3; "Single")]
+    #[test_case(0x05, 6; "Double")]
+    #[test_case(0x07, 10; "Quad")]
+    fn missile_locked_to_player_centre(nusiz: u8, offset: usize) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let tia = InMemoryTIA::new(rdy.clone(), tv.rc_refcell()).rc_refcell();
+
+        tia.borrow_mut().tick(50 * 3);
+        tia.borrow_mut().write(cmn::regs::RESP1, 0x00);
+        tia.borrow_mut().write(cmn::regs::NUSIZ1, nusiz);
+        tia.borrow_mut().write(cmn::regs::RESMP1, bits::BIT_D1);
+        wsync(tia.clone(), rdy.clone());
+        tia.borrow_mut().write(cmn::regs::RESMP1, 0x00);
+        wsync(tia.clone(), rdy);
+
+        let p1 = tia.borrow_mut().player_hpos_counters()[1];
+        assert_eq!(
+            tia.borrow_mut().missile_hpos_counters()[1],
+            (p1 + 160 - offset) % 160
+        );
+    }
+
+    #[test_case(0x00, bits::BIT_D1, true; "No delay - enabled")]
+    #[test_case(0x00, 0x00, false; "No delay - disabled")]
+    #[test_case(bits::BIT_D0, bits::BIT_D1, false; "Delayed - old disabled")]
+    #[test_case(bits::BIT_D0, 0x00, true; "Delayed - old enabled")]
+    fn ball_enable_with_vdelbl(vdelbl: u8, enabl: u8, expected: bool) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let mut tia = InMemoryTIA::new(rdy, tv.rc_refcell());
+
+        tia.write(cmn::regs::ENABL, enabl ^ bits::BIT_D1);
+        tia.write(cmn::regs::GRP1, 0x00);
+        tia.write(cmn::regs::ENABL, enabl);
+        tia.write(cmn::regs::VDELBL, vdelbl);
+        tia.tick(1);
+
+        assert_eq!(tia.ball_enabled(), expected);
+    }
+
+    #[test_case(0, &[(0, 0x01), (1, 0x10), (0, 0x0F)], 0x0F, 0x01; "Player 0 old copied on GRP1 write")]
+    #[test_case(1, &[(1, 0x10), (0, 0x0F), (1, 0xF0)], 0xF0, 0x10; "Player 1 old copied on GRP0 write")]
+    fn player_graphics_with_vdelp(id: usize, writes: &[(usize, u8)], new: u8, old: u8) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let mut tia = InMemoryTIA::new(rdy, tv.rc_refcell());
+
+        writes
+            .iter()
+            .for_each(|&(x, val)| tia.write(cmn::regs::GRP0 + x, val));
+        tia.tick(1);
+        assert_eq!(tia.player_graphics(id), new);
+
+        tia.write(cmn::regs::VDELP0 + id, bits::BIT_D0);
+        assert_eq!(tia.player_graphics(id), old);
+    }
+
+    #[test_case(cmn::read_regs::CXPPMM)]
+    #[test_case(0x17)]
+    #[test_case(0x37)]
+    fn overlapping_players_latch_until_cxclr(addr: usize) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let mut tia = InMemoryTIA::new(rdy, tv.rc_refcell());
+
+        tia.write(cmn::regs::RESP0, 0x00);
+        tia.write(cmn::regs::RESP1, 0x00);
+        tia.write(cmn::regs::GRP0, 0xFF);
+        tia.write(cmn::regs::GRP1, 0x01);
+        assert_eq!(tia.read(addr), 0x00);
+
+        tia.tick(cmn::NTSC_PIXELS_PER_SCANLINE);
+        assert_eq!(tia.read(addr), bits::BIT_D7);
+        assert_eq!(tia.read(cmn::read_regs::CXM0P), 0x00);
+
+        tia.write(cmn::regs::CXCLR, 0x00);
+        assert_eq!(tia.read(addr), 0x00);
+    }
+
+    #[test_case(0x00, 0x10, 0x20; "Player above playfield")]
+    #[test_case(bits::BIT_D2, 0x20, 0x20; "Playfield above player")]
+    #[test_case(bits::BIT_D1, 0x10, 0x10; "Score playfield in player colour")]
+    fn player_over_playfield_by_priority(ctrlpf: u8, exp: u8, exp_pf: u8) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config()).rc_refcell();
+        let mut tia = InMemoryTIA::new(rdy, tv.clone());
+
+        tia.write(cmn::regs::RESP0, 0x00);
+        tia.write(cmn::regs::GRP0, 0xFF);
+        tia.write(cmn::regs::COLUP0, 0x10);
+        tia.write(cmn::regs::PF0, 0xF0);
+        tia.write(cmn::regs::COLUPF, 0x20);
+        tia.write(cmn::regs::CTRLPF, ctrlpf);
+        tia.tick(2 * cmn::NTSC_PIXELS_PER_SCANLINE);
+
+        let hblank = cmn::ntsc_tv_config().hblank_pixels();
+        assert_eq!(tv.borrow().buffer()[4][hblank + 5], exp);
+        assert_eq!(tv.borrow().buffer()[4][hblank + 12], exp_pf);
+    }
+
+    #[test]
+    fn playfield_write_waits_for_pixel_boundary() {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config()).rc_refcell();
+        let mut tia = InMemoryTIA::new(rdy, tv.clone());
+
+        tia.write(cmn::regs::COLUPF, 0x20);
+        tia.write(cmn::regs::PF2, 0xFF);
+        tia.tick(145);
+        tia.write(cmn::regs::PF2, 0x00);
+        tia.tick(cmn::NTSC_PIXELS_PER_SCANLINE - 145);
+
+        let line = tv.borrow().buffer()[3].clone();
+        assert_eq!(line[144..148], [0x20; 4]);
+        assert_eq!(line[196..200], [0x00; 4]);
+    }
+
+    #[test]
+    fn rsync_shortens_scanline() {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config()).rc_refcell();
+        let mut tia = InMemoryTIA::new(rdy.clone(), tv.clone());
+
+        tia.write(cmn::regs::COLUBK, 0x10);
+        tia.tick(100);
+        tia.write(cmn::regs::RSYNC, 0x00);
+        assert_eq!(
+            tia.hsync_counter(),
+            cmn::NTSC_PIXELS_PER_SCANLINE - RSYNC_CLOCKS
+        );
+
+        tia.write(cmn::regs::WSYNC, 0x00);
+        tia.tick(RSYNC_CLOCKS);
+        assert_eq!(rdy.get(), LineState::Low);
+        tia.tick(1);
+        assert_eq!(rdy.get(), LineState::High);
+        assert_eq!(tia.hsync_counter(), 1);
+        tia.tick(cmn::ntsc_tv_config().hblank_pixels());
+
+        let line = tv.borrow().buffer()[3].clone();
+        assert_eq!(line[99], 0x10);
+        assert!(line[100..225].iter().all(|&x| x == 0x00));
+        assert_eq!(line[225..], [0x10; RSYNC_CLOCKS]);
+        let next_line = tv.borrow().buffer()[4].clone();
+        assert_eq!(next_line[67..69], [0x00, 0x10]);
+    }
+
+    #[test]
+    fn rsync_at_scanline_end_is_ignored() {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let mut tia = InMemoryTIA::new(rdy, tv.rc_refcell());
+
+        tia.tick(cmn::NTSC_PIXELS_PER_SCANLINE - 1);
+        tia.write(cmn::regs::RSYNC, 0x00);
+        assert_eq!(tia.hsync_counter(), cmn::NTSC_PIXELS_PER_SCANLINE - 1);
+        tia.tick(1);
+        assert_eq!(tia.hsync_counter(), 0);
+    }
+
+    /// Only WSYNC & RSYNC act on the horizontal counter or RDY.
+    #[test_case(cmn::regs::WSYNC, 110, LineState::Low)]
+    #[test_case(cmn::regs::RSYNC, 7, LineState::High)]
+    #[test_case(cmn::regs::RESP0, 110, LineState::High)]
+    #[test_case(cmn::regs::RESP1, 110, LineState::High)]
+    #[test_case(cmn::regs::RESM0, 110, LineState::High)]
+    #[test_case(cmn::regs::RESM1, 110, LineState::High)]
+    #[test_case(cmn::regs::RESBL, 110, LineState::High)]
+    #[test_case(cmn::regs::HMOVE, 110, LineState::High)]
+    #[test_case(cmn::regs::HMCLR, 110, LineState::High)]
+    #[test_case(cmn::regs::CXCLR, 110, LineState::High)]
+    fn strobe_effects_on_hsync_counter_and_rdy(addr: usize, hsync: usize, exp: LineState) {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(3, 0, cmn::ntsc_tv_config());
+        let mut tia = InMemoryTIA::new(rdy.clone(), tv.rc_refcell());
+
+        tia.tick(100);
+        assert_eq!(rdy.get(), LineState::High);
+        tia.write(addr, 0x00);
+        tia.tick(10);
+
+        assert_eq!(tia.hsync_counter(), hsync);
+        assert_eq!(rdy.get(), exp);
+    }
+
+    #[test]
+    fn audio_clocked_twice_per_scanline() {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(0, 0, cmn::ntsc_tv_config()).rc_refcell();
+        let mut tia = InMemoryTIA::new(rdy.clone(), tv.clone());
+
+        tia.write(cmn::regs::AUDC0, 0x04);
+        tia.write(cmn::regs::AUDV0, 0x0F);
+        tia.write(cmn::regs::AUDC1, 0x00);
+        tia.write(cmn::regs::AUDV1, 0x05);
+        tia.tick(3 * cmn::NTSC_PIXELS_PER_SCANLINE);
+        let audio = tia.drain_audio();
+
+        assert_eq!(audio.len(), 3 * aud::CLOCKS_PER_SCANLINE);
+        assert_eq!(
+            audio[..4],
+            [[0x0F, 0x05], [0x00, 0x05], [0x0F, 0x05], [0x00, 0x05]]
+        );
+        assert!(tia.drain_audio().is_empty());
+    }
+
+    #[test]
+    fn audio_queue_drops_oldest_samples() {
+        let rdy = LineState::Low.rc_cell();
+        let tv = InMemoryTV::new_testable(0, 0, cmn::ntsc_tv_config()).rc_refcell();
+        let mut tia = InMemoryTIA::new(rdy.clone(), tv.clone());
+
+        tia.write(cmn::regs::AUDV0, 0x01);
+        tia.tick(aud::MAX_QUEUED_SAMPLES * cmn::NTSC_PIXELS_PER_SCANLINE / 2);
+        tia.write(cmn::regs::AUDV0, 0x02);
+        tia.tick(cmn::NTSC_PIXELS_PER_SCANLINE);
+        let audio = tia.drain_audio();
+
+        assert_eq!(audio.len(), aud::MAX_QUEUED_SAMPLES);
+        assert_eq!(audio[0], [0x01, 0x00]);
+        assert_eq!(audio[audio.len() - 2..], [[0x02, 0x00]; 2]);
+    }
+
+    fn wsync(tia: Rc<RefCell<dyn TIA>>, rdy: Rc<Cell<LineState>>) {
+        tia.borrow_mut().write(cmn::regs::WSYNC, 0x00);
+        while rdy.get() == LineState::Low {
+            tia.borrow_mut().tick(1)
+        }
+    }
+}
 
 mod pf {
     use super::*;
@@ -511,10 +1479,14 @@ mod pf {
         (cmn::regs::PF2, bits::BIT_D7),
     ];
 
-    pub fn get_color<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>(
+    pub fn is_first_half(clk: usize, tv_cfg: &TVConfig) -> bool {
+        (clk - tv_cfg.hblank_pixels()) / (tv_cfg.visible_pixels() / 2) == 0
+    }
+
+    pub fn get_color(
         clk: usize,
         registers: &[u8; cmn::TIA_MAX_ADDRESS + 1],
-        tv_cfg: &TVConfig<SCANLINES, PIXELS_PER_SCANLINE>,
+        tv_cfg: &TVConfig,
     ) -> Option<u8> {
         if registers[cmn::regs::PF0] == 0x00
             && registers[cmn::regs::PF1] == 0x00
@@ -526,7 +1498,7 @@ mod pf {
         let half_screen = tv_cfg.visible_pixels() / 2;
         let pixels_per_playfield_pixel = half_screen / PLAYFIELD_WIDTH;
         let pixel = (clk - tv_cfg.hblank_pixels()) % half_screen;
-        let first_half = (clk - tv_cfg.hblank_pixels()) / half_screen == 0;
+        let first_half = is_first_half(clk, tv_cfg);
 
         let mut map_index = pixel / pixels_per_playfield_pixel;
         if !first_half && bits::tst_bits(registers[cmn::regs::CTRLPF], bits::BIT_D0) {
@@ -535,7 +1507,8 @@ mod pf {
 
         let reg_info = PLAYFIELD_MAP[map_index];
         if bits::tst_bits(registers[reg_info.0], reg_info.1) {
-            if bits::tst_bits(registers[cmn::regs::CTRLPF], bits::BIT_D1) {
+            // NOTE: Playfield priority (D2) overrides score mode (D1).
+            if registers[cmn::regs::CTRLPF] & (bits::BIT_D2 | bits::BIT_D1) == bits::BIT_D1 {
                 if first_half {
                     Some(registers[cmn::regs::COLUP0])
                 } else {
@@ -553,14 +1526,15 @@ mod pf {
     mod tests {
         use super::*;
         use alloc::vec;
-        use alloc::vec::*;
         use test_case::test_case;
 
-        type TestableTVConfig = TVConfig<3, { 2 * PLAYFIELD_WIDTH + 1 }>;
+        fn testable_config() -> TVConfig {
+            TVConfig::new(3, 2 * PLAYFIELD_WIDTH + 1, 2 * PLAYFIELD_WIDTH, [0x00; 128])
+        }
 
         #[test]
         fn test_no_pf() {
-            let cfg = TestableTVConfig::default();
+            let cfg = testable_config();
             let regs: [u8; cmn::TIA_MAX_ADDRESS + 1] = [0x00; cmn::TIA_MAX_ADDRESS + 1];
             let x = get_color(68, &regs, &cfg);
 
@@ -570,7 +1544,7 @@ mod pf {
         #[test_case(0xAA, (0xF0, 0xFF, 0xFF), [Some(0xAA); PLAYFIELD_WIDTH]; "Full")]
         #[test_case(0x1A, (0xA0, 0x55, 0xAA), [None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A)]; "Alternates")]
         fn test_pf_patterns(col_pf: u8, pf: (u8, u8, u8), display: [Option<u8>; PLAYFIELD_WIDTH]) {
-            let cfg = TestableTVConfig::default();
+            let cfg = testable_config();
             let mut regs: [u8; cmn::TIA_MAX_ADDRESS + 1] = [0x00; cmn::TIA_MAX_ADDRESS + 1];
             regs[cmn::regs::COLUPF] = col_pf;
             regs[cmn::regs::PF0] = pf.0;
@@ -602,12 +1576,17 @@ mod pf {
             vec![None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A), None, Some(0x2A)],
             vec![Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None, Some(0x3A), None];
             "Mirrored + Player colors")]
+        #[test_case(
+            bits::BIT_D2 | bits::BIT_D1,
+            vec![None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A)],
+            vec![None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A), None, Some(0x1A)];
+            "Priority + PF color")]
         fn test_pf_colors(
             ctrl_pf: u8,
             display_left: Vec<Option<u8>>,
             display_right: Vec<Option<u8>>,
         ) {
-            let cfg = TestableTVConfig::default();
+            let cfg = testable_config();
             let mut regs: [u8; cmn::TIA_MAX_ADDRESS + 1] = [0x00; cmn::TIA_MAX_ADDRESS + 1];
             regs[cmn::regs::CTRLPF] = ctrl_pf;
             regs[cmn::regs::COLUPF] = 0x1A;
@@ -629,6 +1608,9 @@ mod pf {
 mod grp {
     use super::*;
 
+    /// Pixels from a RESPx strobe to the first pixel of the player.
+    pub const START_DELAY: usize = 5;
+
     // Player registers: https://youtu.be/GObPgosXPPs?list=PLbPt2qKXQzJ8-P3Qe9lDPtxwFSdbDbcvW&t=534
     // COLUPn          => color
     // GRPn
@@ -638,6 +1620,35 @@ mod grp {
     // HMCLR -s-       => clears hmotion for p,m,b
     // HMOVE -s-       => apply fine tuning set in hmotion
 
+    // NUSIZn -----xxx  => copies & stretching:
+    //   000 one copy            100 two copies - wide
+    //   001 two copies - close  101 double size
+    //   010 two copies - medium 110 three copies - medium
+    //   011 three copies - close 111 quad size
+
+    /// Pixels from the start of the player to each of its copies, per NUSIZ D2-D0.
+    /// Missiles are copied the same way.
+    pub static COPIES: &[&[usize]; 8] = &[
+        &[0],
+        &[0, 16],
+        &[0, 32],
+        &[0, 16, 32],
+        &[0, 64],
+        &[0],
+        &[0, 32, 64],
+        &[0],
+    ];
+
+    /// Colour clocks each player pixel lasts for, per NUSIZ D2-D0.
+    #[inline]
+    pub fn scale(nusizn: u8) -> usize {
+        match nusizn & 0b111 {
+            0b101 => 2,
+            0b111 => 4,
+            _ => 1,
+        }
+    }
+
     static POSITION_MASK: &[u8; 8] = &[
         bits::BIT_D0,
         bits::BIT_D1,
@@ -652,14 +1663,25 @@ mod grp {
     /// Refer:
     /// - https://forums.atariage.com/topic/208473-resp0-while-pixel-position-is-negativelow/#comment-2692375
     /// - https://forums.atariage.com/topic/271085-when-do-i-use-resp0-command-to-put-sprite-on-left-of-screen/#comment-3871580
-    pub fn get_color(playern_hpos_counter: usize, grpn: u8, colupn: u8, refpn: u8) -> Option<u8> {
-        if playern_hpos_counter > 7 {
-            return None;
-        }
-
-        let mut mask_index = 7 - playern_hpos_counter;
+    /// - https://www.atarihq.com/danb/files/TIA_HW_Notes.txt, stretched players start a pixel late.
+    pub fn get_color(
+        playern_hpos_counter: usize,
+        grpn: u8,
+        colupn: u8,
+        refpn: u8,
+        nusizn: u8,
+    ) -> Option<u8> {
+        let scale = scale(nusizn);
+        let delay = if scale > 1 { 1 } else { 0 };
+        let pixel = COPIES[(nusizn & 0b111) as usize]
+            .iter()
+            .filter_map(|&offset| playern_hpos_counter.checked_sub(offset + delay))
+            .map(|x| x / scale)
+            .find(|&x| x < 8)?;
+
+        let mut mask_index = 7 - pixel;
         if bits::tst_bits(refpn, bits::BIT_D3) {
-            mask_index = playern_hpos_counter;
+            mask_index = pixel;
         }
 
         let mask = POSITION_MASK[mask_index];
@@ -674,7 +1696,6 @@ mod grp {
     mod tests {
         use super::*;
         use alloc::vec;
-        use alloc::vec::*;
         use test_case::test_case;
 
         #[test_case(0, false)]
@@ -686,7 +1707,7 @@ mod grp {
             let mut col: Vec<_> = (159..)
                 .take(10)
                 .map(|x| x % 160)
-                .map(|hpos| get_color(hpos, 0b_10101111, color, refpn))
+                .map(|hpos| get_color(hpos, 0b_10101111, color, refpn, 0x00))
                 .collect();
             if rev {
                 col.reverse();
@@ -708,5 +1729,673 @@ mod grp {
                 ]
             )
         }
+
+        #[test_case(0b000, &[0], 8)]
+        #[test_case(0b001, &[0, 16], 8)]
+        #[test_case(0b010, &[0, 32], 8)]
+        #[test_case(0b011, &[0, 16, 32], 8)]
+        #[test_case(0b100, &[0, 64], 8)]
+        #[test_case(0b101, &[1], 16)]
+        #[test_case(0b110, &[0, 32, 64], 8)]
+        #[test_case(0b111, &[1], 32)]
+        fn nusiz_copies_and_stretching(nusiz: u8, starts: &[usize], width: usize) {
+            let drawn: Vec<_> = (0..160)
+                .filter(|&x| get_color(x, 0xFF, 0x42, 0x00, nusiz).is_some())
+                .collect();
+
+            assert_eq!(
+                drawn,
+                starts
+                    .iter()
+                    .flat_map(|&x| x..x + width)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        #[test_case(0b101, &[1, 2, 5, 6])]
+        #[test_case(0b111, &[1, 2, 3, 4, 9, 10, 11, 12])]
+        fn stretched_player_repeats_each_pixel(nusiz: u8, expected: &[usize]) {
+            let drawn: Vec<_> = (0..40)
+                .filter(|&x| get_color(x, 0b_1010_0000, 0x42, 0x00, nusiz).is_some())
+                .collect();
+
+            assert_eq!(drawn, expected);
+        }
+    }
+}
+
+mod msl {
+    use super::*;
+
+    // Missile registers:
+    // ENAMn  BIT1      => enable
+    // NUSIZn xx--      => 1, 2, 4 or 8 px wide
+    // NUSIZn -----xxx  => copies as for players, a single copy when stretched
+    // RESMn  -s-       => start drawing
+    // HMMn   xxxx----  => fine tuning, as for players
+    // RESMPn BIT1      => hide & lock to the centre of player n
+
+    /// Pixels from a RESMx strobe to the first pixel of the missile.
+    pub const START_DELAY: usize = 4;
+
+    #[inline]
+    pub fn width(nusizn: u8) -> usize {
+        1 << ((nusizn >> 4) & 0b11)
+    }
+
+    /// Pixels from the start of the player to where RESMPx puts the missile.
+    pub fn centre_offset(nusizn: u8) -> usize {
+        match nusizn & 0b111 {
+            0b101 => 6,
+            0b111 => 10,
+            _ => 3,
+        }
+    }
+
+    pub fn get_color(
+        missilen_hpos_counter: usize,
+        enamn: u8,
+        resmpn: u8,
+        nusizn: u8,
+        colupn: u8,
+    ) -> Option<u8> {
+        if !bits::tst_bits(enamn, bits::BIT_D1) || bits::tst_bits(resmpn, bits::BIT_D1) {
+            return None;
+        }
+
+        grp::COPIES[(nusizn & 0b111) as usize]
+            .iter()
+            .filter_map(|&offset| missilen_hpos_counter.checked_sub(offset))
+            .any(|x| x < width(nusizn))
+            .then_some(colupn)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0b0000_0000, 1)]
+        #[test_case(0b0001_0000, 2)]
+        #[test_case(0b0010_0111, 4)]
+        #[test_case(0b0011_0101, 8)]
+        fn width_follows_nusiz(nusiz: u8, expected: usize) {
+            let drawn: Vec<_> = (0..160)
+                .filter(|&x| get_color(x, bits::BIT_D1, 0x00, nusiz, 0x42).is_some())
+                .collect();
+
+            assert_eq!(drawn, (0..expected).collect::<Vec<_>>());
+        }
+
+        #[test_case(0b0001_0011, &[0, 1, 16, 17, 32, 33])]
+        #[test_case(0b0000_0100, &[0, 64])]
+        #[test_case(0b0000_0110, &[0, 32, 64])]
+        fn copies_follow_nusiz(nusiz: u8, expected: &[usize]) {
+            let drawn: Vec<_> = (0..160)
+                .filter(|&x| get_color(x, bits::BIT_D1, 0x00, nusiz, 0x42).is_some())
+                .collect();
+
+            assert_eq!(drawn, expected);
+        }
+
+        #[test_case(0x00, 0x00; "Disabled")]
+        #[test_case(bits::BIT_D1, bits::BIT_D1; "Locked to player")]
+        fn hidden(enam: u8, resmp: u8) {
+            assert_eq!(get_color(0, enam, resmp, 0x30, 0x42), None);
+        }
+    }
+}
+
+mod bl {
+    // Ball registers:
+    // ENABL  BIT1      => enable
+    // CTRLPF xx----    => 1, 2, 4 or 8 px wide
+    // RESBL  -s-       => start drawing
+    // HMBL   xxxx----  => fine tuning, as for players
+    // VDELBL BIT0      => draw the ENABL copied on the last GRP1 write
+
+    /// Pixels from a RESBL strobe to the first pixel of the ball.
+    pub const START_DELAY: usize = 4;
+
+    pub fn get_color(
+        ball_hpos_counter: usize,
+        enabled: bool,
+        ctrlpf: u8,
+        colupf: u8,
+    ) -> Option<u8> {
+        let width = 1 << ((ctrlpf >> 4) & 0b11);
+        (enabled && ball_hpos_counter < width).then_some(colupf)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use alloc::vec::*;
+        use test_case::test_case;
+
+        #[test_case(0b0000_0000, 1)]
+        #[test_case(0b0001_0001, 2)]
+        #[test_case(0b0010_0010, 4)]
+        #[test_case(0b0011_0101, 8)]
+        fn width_follows_ctrlpf(ctrlpf: u8, expected: usize) {
+            let drawn: Vec<_> = (0..160)
+                .filter(|&x| get_color(x, true, ctrlpf, 0x42).is_some())
+                .collect();
+
+            assert_eq!(drawn, (0..expected).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn hidden_when_disabled() {
+            assert_eq!(get_color(0, false, 0x30, 0x42), None);
+        }
+    }
+}
+
+mod cx {
+    use super::*;
+
+    pub const P0: usize = 0;
+    pub const P1: usize = 1;
+    pub const M0: usize = 2;
+    pub const M1: usize = 3;
+    pub const BL: usize = 4;
+    pub const PF: usize = 5;
+    pub const OBJECTS: usize = 6;
+
+    /// CXM0P-CXPPMM.
+    pub const REGISTERS: usize = cmn::read_regs::CXPPMM + 1;
+
+    /// Collision register, bit & the two objects overlapping to set it.
+    static PAIRS: &[(usize, u8, usize, usize); 15] = &[
+        (cmn::read_regs::CXM0P, bits::BIT_D7, M0, P1),
+        (cmn::read_regs::CXM0P, bits::BIT_D6, M0, P0),
+        (cmn::read_regs::CXM1P, bits::BIT_D7, M1, P0),
+        (cmn::read_regs::CXM1P, bits::BIT_D6, M1, P1),
+        (cmn::read_regs::CXP0FB, bits::BIT_D7, P0, PF),
+        (cmn::read_regs::CXP0FB, bits::BIT_D6, P0, BL),
+        (cmn::read_regs::CXP1FB, bits::BIT_D7, P1, PF),
+        (cmn::read_regs::CXP1FB, bits::BIT_D6, P1, BL),
+        (cmn::read_regs::CXM0FB, bits::BIT_D7, M0, PF),
+        (cmn::read_regs::CXM0FB, bits::BIT_D6, M0, BL),
+        (cmn::read_regs::CXM1FB, bits::BIT_D7, M1, PF),
+        (cmn::read_regs::CXM1FB, bits::BIT_D6, M1, BL),
+        (cmn::read_regs::CXBLPF, bits::BIT_D7, BL, PF),
+        (cmn::read_regs::CXPPMM, bits::BIT_D7, P0, P1),
+        (cmn::read_regs::CXPPMM, bits::BIT_D6, M0, M1),
+    ];
+
+    /// Sets the latch of every pair of objects drawn on the same pixel.
+    pub fn latch(latches: &mut [u8; REGISTERS], objects: &[bool; OBJECTS]) {
+        PAIRS
+            .iter()
+            .filter(|&&(_, _, a, b)| objects[a] && objects[b])
+            .for_each(|&(reg, bit, _, _)| latches[reg] |= bit);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(&[P0, P1], cmn::read_regs::CXPPMM, bits::BIT_D7)]
+        #[test_case(&[M0, M1], cmn::read_regs::CXPPMM, bits::BIT_D6)]
+        #[test_case(&[M0, P1], cmn::read_regs::CXM0P, bits::BIT_D7)]
+        #[test_case(&[M1, P1], cmn::read_regs::CXM1P, bits::BIT_D6)]
+        #[test_case(&[P1, BL], cmn::read_regs::CXP1FB, bits::BIT_D6)]
+        #[test_case(&[BL, PF], cmn::read_regs::CXBLPF, bits::BIT_D7)]
+        fn latches_pair(drawn: &[usize], reg: usize, bit: u8) {
+            let mut objects = [false; OBJECTS];
+            drawn.iter().for_each(|&x| objects[x] = true);
+            let mut latches = [0x00; REGISTERS];
+
+            latch(&mut latches, &objects);
+
+            let mut expected = [0x00; REGISTERS];
+            expected[reg] = bit;
+            assert_eq!(latches, expected);
+        }
+
+        #[test]
+        fn all_objects_set_all_latches() {
+            let mut latches = [0x00; REGISTERS];
+
+            latch(&mut latches, &[true; OBJECTS]);
+
+            let mut expected = [bits::BIT_D7 | bits::BIT_D6; REGISTERS];
+            expected[cmn::read_regs::CXBLPF] = bits::BIT_D7;
+            assert_eq!(latches, expected);
+        }
+
+        #[test]
+        fn single_object_sets_nothing() {
+            let mut latches = [0x00; REGISTERS];
+
+            (0..OBJECTS).for_each(|x| {
+                let mut objects = [false; OBJECTS];
+                objects[x] = true;
+                latch(&mut latches, &objects);
+            });
+
+            assert_eq!(latches, [0x00; REGISTERS]);
+        }
+    }
+}
+
+mod pri {
+    use super::*;
+    use cx::{BL, M0, M1, OBJECTS, P0, P1, PF};
+
+    /// Objects from the highest to the lowest priority.
+    static NORMAL: &[usize; OBJECTS] = &[P0, M0, P1, M1, BL, PF];
+    /// CTRLPF D2: Playfield & ball above the players & missiles.
+    static PLAYFIELD_ABOVE: &[usize; OBJECTS] = &[PF, BL, P0, M0, P1, M1];
+    /// CTRLPF D1: Playfield takes the priority of the player whose colour it is drawn in.
+    static SCORE_LEFT: &[usize; OBJECTS] = &[P0, M0, PF, P1, M1, BL];
+    static SCORE_RIGHT: &[usize; OBJECTS] = &[P0, M0, P1, M1, PF, BL];
+
+    pub fn get_color(objects: &[Option<u8>; OBJECTS], ctrlpf: u8, first_half: bool) -> Option<u8> {
+        let order = if bits::tst_bits(ctrlpf, bits::BIT_D2) {
+            PLAYFIELD_ABOVE
+        } else if bits::tst_bits(ctrlpf, bits::BIT_D1) {
+            if first_half {
+                SCORE_LEFT
+            } else {
+                SCORE_RIGHT
+            }
+        } else {
+            NORMAL
+        };
+
+        order.iter().find_map(|&x| objects[x])
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use test_case::test_case;
+
+        const COLORS: [u8; OBJECTS] = [0x10, 0x20, 0x30, 0x40, 0x50, 0x60];
+
+        #[test_case(&[P0, P1, M0, M1, BL, PF], 0x00, true, Some(0x10); "P0 above all")]
+        #[test_case(&[M0, P1], 0x00, true, Some(0x30); "M0 above P1")]
+        #[test_case(&[M1, BL, PF], 0x00, true, Some(0x40); "M1 above BL & PF")]
+        #[test_case(&[BL, PF], 0x00, true, Some(0x50); "BL above PF")]
+        #[test_case(&[P0, BL], bits::BIT_D2, true, Some(0x50); "BL above P0")]
+        #[test_case(&[P0, M1, PF], bits::BIT_D2, true, Some(0x60); "PF above P0")]
+        #[test_case(&[M0, M1], bits::BIT_D2, true, Some(0x30); "M0 above M1")]
+        #[test_case(&[P1, PF], bits::BIT_D1, true, Some(0x60); "Score PF above P1 on left")]
+        #[test_case(&[P1, PF], bits::BIT_D1, false, Some(0x20); "Score P1 above PF on right")]
+        #[test_case(&[M0, PF], bits::BIT_D1, true, Some(0x30); "Score M0 above PF on left")]
+        #[test_case(&[BL, PF], bits::BIT_D1, false, Some(0x60); "Score PF above BL")]
+        #[test_case(&[P1, PF], bits::BIT_D2 | bits::BIT_D1, false, Some(0x60); "Priority over score")]
+        #[test_case(&[], bits::BIT_D2, true, None; "Nothing drawn")]
+        fn get_color_by_priority(drawn: &[usize], ctrlpf: u8, first_half: bool, exp: Option<u8>) {
+            let mut objects = [None; OBJECTS];
+            drawn.iter().for_each(|&x| objects[x] = Some(COLORS[x]));
+
+            assert_eq!(get_color(&objects, ctrlpf, first_half), exp);
+        }
+    }
+}
+
+mod hm {
+    use super::*;
+
+    /// Players, missiles & ball, as numbered by `cx`.
+    pub const OBJECTS: usize = cx::BL + 1;
+    /// Pixels HBLANK is extended by, when HMOVE is strobed during it.
+    pub const EXTENDED_HBLANK: usize = 8;
+    /// Color clocks per tick of the motion counter.
+    pub const CLOCK_PERIOD: usize = 4;
+    pub const MAX_CLOCKS: u8 = 0x0F;
+
+    /// Extra clocks an object gets from an HMOVE during HBLANK, for HMxx. Less the 8 normal
+    /// clocks lost to the extended HBLANK, that moves it by the signed HMxx nibble.
+    #[inline]
+    pub fn clocks(hmxx: u8) -> u8 {
+        (hmxx >> 4) ^ 0x08
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0x00, 8)]
+        #[test_case(0x70, 15)]
+        #[test_case(0x80, 0)]
+        #[test_case(0xF0, 7)]
+        #[test_case(0x3F, 11; "Low nibble ignored")]
+        fn extra_clocks(hmxx: u8, exp: u8) {
+            assert_eq!(clocks(hmxx), exp);
+            assert!(clocks(hmxx) <= MAX_CLOCKS);
+        }
+    }
+}
+
+mod dly {
+    use super::*;
+
+    /// Color clocks for a write to PF0-PF2 to reach the playfield...
+    const PF: usize = 2;
+    /// ...which only picks up a change at the start of its next 4 pixel wide pixel.
+    const PF_PIXEL: usize = 4;
+
+    /// Color clocks from a CPU write to a register till it takes effect, the rest take effect
+    /// at once.
+    ///
+    /// Refer: https://github.com/stella-emu/stella/blob/master/src/emucore/tia/TIA.cxx
+    pub fn clocks(addr: usize, hsync_counter: usize, hblank_pixels: usize) -> u8 {
+        match addr {
+            cmn::regs::PF0..=cmn::regs::PF2 => {
+                let phase = (hsync_counter + PF + PF_PIXEL - hblank_pixels % PF_PIXEL) % PF_PIXEL;
+                (PF + (PF_PIXEL - phase) % PF_PIXEL) as u8
+            }
+            cmn::regs::GRP0
+            | cmn::regs::GRP1
+            | cmn::regs::ENAM0
+            | cmn::regs::ENAM1
+            | cmn::regs::ENABL
+            | cmn::regs::REFP0
+            | cmn::regs::REFP1
+            | cmn::regs::VBLANK => 1,
+            cmn::regs::HMP0..=cmn::regs::HMBL | cmn::regs::HMCLR => 2,
+            cmn::regs::HMOVE => 6,
+            _ => 0,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(144, 4; "From a boundary")]
+        #[test_case(145, 3; "Waits for a boundary")]
+        #[test_case(146, 2; "Lands on a boundary")]
+        #[test_case(147, 5; "Misses a boundary")]
+        fn playfield_writes_land_on_pixel_boundaries(hsync_counter: usize, exp: u8) {
+            let clocks = clocks(cmn::regs::PF2, hsync_counter, 68);
+
+            assert_eq!(clocks, exp);
+            assert_eq!((hsync_counter + clocks as usize - 68) % PF_PIXEL, 0);
+        }
+
+        #[test_case(cmn::regs::COLUPF, 0)]
+        #[test_case(cmn::regs::RESP0, 0)]
+        #[test_case(cmn::regs::WSYNC, 0)]
+        #[test_case(cmn::regs::GRP1, 1)]
+        #[test_case(cmn::regs::HMCLR, 2)]
+        #[test_case(cmn::regs::HMOVE, 6)]
+        fn register_delays(addr: usize, exp: u8) {
+            assert_eq!(clocks(addr, 0, 68), exp);
+        }
+    }
+}
+
+mod aud {
+    use super::*;
+
+    /// Each channel is clocked twice per scanline, i.e. at ~31.4 kHz.
+    pub const CLOCKS_PER_SCANLINE: usize = 2;
+    /// Audio queued for the host is capped at a second, the oldest samples are dropped first.
+    pub const MAX_QUEUED_SAMPLES: usize = AUDIO_SAMPLE_RATE as usize;
+    /// Low 4 bits of the 5 bit poly clocking the "div 31" modes, met in 2 of its 31 states.
+    const DIV31_STATE: u16 = 0b0001;
+    const POLY4_BITS: u32 = 4;
+    const POLY5_BITS: u32 = 5;
+    const POLY9_BITS: u32 = 9;
+
+    /// What clocks the waveform generator, from AUDC D1-D0.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Clock {
+        Always,
+        /// Twice per period of the 5 bit poly.
+        Div31,
+        /// Whenever the 5 bit poly outputs a 1.
+        Poly5,
+    }
+
+    /// Waveform generator, from AUDC.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Wave {
+        /// Output held high, AUDV alone sets the level.
+        One,
+        Poly4,
+        /// Output toggled on every clock.
+        Div2,
+        Poly5,
+        Poly9,
+    }
+
+    /// | AUDC  | Waveform                      |
+    /// |-------|-------------------------------|
+    /// | 0, B  | Set to 1                      |
+    /// | 1     | 4 bit poly                    |
+    /// | 2     | div 15 → 4 bit poly           |
+    /// | 3     | 5 bit poly → 4 bit poly       |
+    /// | 4, 5  | div 2, pure tone              |
+    /// | 6, A  | div 31, pure tone             |
+    /// | 7     | 5 bit poly → div 2            |
+    /// | 8     | 9 bit poly, white noise       |
+    /// | 9     | 5 bit poly                    |
+    /// | C, D  | div 6, pure tone              |
+    /// | E     | div 93, pure tone             |
+    /// | F     | 5 bit poly div 6              |
+    ///
+    /// Refer: https://www.randomterrain.com/atari-2600-memories-music-and-sound.html
+    fn mode(audc: u8) -> (Clock, Wave) {
+        let clock = match audc & 0b0011 {
+            0b10 => Clock::Div31,
+            0b11 => Clock::Poly5,
+            _ => Clock::Always,
+        };
+        let wave = match audc {
+            0x00 | 0x0B => Wave::One,
+            0x01..=0x03 => Wave::Poly4,
+            0x08 => Wave::Poly9,
+            0x09 => Wave::Poly5,
+            _ => Wave::Div2,
+        };
+
+        (clock, wave)
+    }
+
+    /// AUDC C-F run everything off a further divide by 3.
+    #[inline]
+    fn is_div3(audc: u8) -> bool {
+        audc & 0b1100 == 0b1100
+    }
+
+    /// Shifts a maximal length LFSR of `bits` bits right, feeding back bit 0 ^ bit `tap`.
+    #[inline]
+    fn shift(poly: u16, bits: u32, tap: u32) -> u16 {
+        let feedback = (poly ^ (poly >> tap)) & 1;
+        (poly >> 1) | (feedback << (bits - 1))
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Channel {
+        /// Audio clocks since the waveform was last clocked, which it is every AUDF + 1.
+        divider: u8,
+        div3: u8,
+        poly4: u16,
+        poly5: u16,
+        poly9: u16,
+        output: bool,
+    }
+
+    impl Default for Channel {
+        fn default() -> Self {
+            Self {
+                divider: 0,
+                div3: 0,
+                poly4: (1 << POLY4_BITS) - 1,
+                poly5: (1 << POLY5_BITS) - 1,
+                poly9: (1 << POLY9_BITS) - 1,
+                output: false,
+            }
+        }
+    }
+
+    impl Channel {
+        /// One audio clock. Returns the channel level, AUDV while the waveform is high.
+        pub fn tick(&mut self, audc: u8, audf: u8, audv: u8) -> u8 {
+            // NOTE: AUDF lowered below the divider restarts the count right away.
+            if self.divider >= audf {
+                self.divider = 0;
+                self.step(audc);
+            } else {
+                self.divider += 1;
+            }
+
+            if self.output {
+                audv
+            } else {
+                0
+            }
+        }
+
+        fn step(&mut self, audc: u8) {
+            if is_div3(audc) {
+                self.div3 = (self.div3 + 1) % 3;
+                if self.div3 != 0 {
+                    return;
+                }
+            }
+
+            let (clock, wave) = mode(audc);
+            let clocked = match clock {
+                Clock::Always => true,
+                Clock::Div31 => self.poly5 & 0x0F == DIV31_STATE,
+                Clock::Poly5 => self.poly5 & 1 != 0,
+            };
+            self.poly5 = shift(self.poly5, POLY5_BITS, 2);
+            self.poly9 = shift(self.poly9, POLY9_BITS, 4);
+            if clocked {
+                self.poly4 = shift(self.poly4, POLY4_BITS, 1);
+            }
+
+            self.output = match wave {
+                Wave::One => true,
+                Wave::Poly4 => self.poly4 & 1 != 0,
+                Wave::Div2 => self.output ^ clocked,
+                Wave::Poly5 => self.poly5 & 1 != 0,
+                Wave::Poly9 => self.poly9 & 1 != 0,
+            };
+        }
+    }
+
+    impl SaveState for Channel {
+        fn save_state(&self, w: &mut StateWriter) {
+            w.put_u8(self.divider);
+            w.put_u8(self.div3);
+            w.put_u8(self.poly4 as u8);
+            w.put_u8(self.poly5 as u8);
+            w.put_u16(self.poly9);
+            w.put_bool(self.output);
+        }
+
+        fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+            self.divider = r.get_u8()?;
+            self.div3 = r.get_u8()?;
+            self.poly4 = r.get_u8()? as u16;
+            self.poly5 = r.get_u8()? as u16;
+            self.poly9 = r.get_u16()?;
+            self.output = r.get_bool()?;
+            // NOTE: An all zeros LFSR would never leave that state.
+            let valid = |poly: u16, bits: u32| poly != 0 && poly < 1 << bits;
+            if self.div3 >= 3
+                || !valid(self.poly4, POLY4_BITS)
+                || !valid(self.poly5, POLY5_BITS)
+                || !valid(self.poly9, POLY9_BITS)
+            {
+                return Err(r.corrupt());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use test_case::test_case;
+
+        /// Shortest period of the channel output, looking at 4 periods worth of clocks.
+        fn period(audc: u8, audf: u8) -> usize {
+            const MAX_PERIOD: usize = 511 * 32;
+            let mut channel = Channel::default();
+            // NOTE: Skip past the start, before the LFSRs & dividers line up.
+            (0..MAX_PERIOD).for_each(|_| _ = channel.tick(audc, audf, 1));
+            let out: Vec<u8> = (0..4 * MAX_PERIOD)
+                .map(|_| channel.tick(audc, audf, 1))
+                .collect();
+
+            (1..=MAX_PERIOD)
+                .find(|&p| (p..out.len()).all(|x| out[x] == out[x - p]))
+                .unwrap()
+        }
+
+        #[test_case(POLY4_BITS, 1, 15)]
+        #[test_case(POLY5_BITS, 2, 31)]
+        #[test_case(POLY9_BITS, 4, 511)]
+        fn polys_are_maximal_length(bits: u32, tap: u32, exp: usize) {
+            let start = (1 << bits) - 1;
+            let mut poly = shift(start, bits, tap);
+            let mut n = 1;
+            while poly != start {
+                assert_ne!(poly, 0);
+                poly = shift(poly, bits, tap);
+                n += 1;
+            }
+
+            assert_eq!(n, exp);
+        }
+
+        #[test_case(0x01, 15)]
+        #[test_case(0x02, 465)]
+        #[test_case(0x03, 465)]
+        #[test_case(0x04, 2)]
+        #[test_case(0x05, 2)]
+        #[test_case(0x06, 31)]
+        #[test_case(0x07, 31)]
+        #[test_case(0x08, 511)]
+        #[test_case(0x09, 31)]
+        #[test_case(0x0A, 31)]
+        #[test_case(0x0C, 6)]
+        #[test_case(0x0D, 6)]
+        #[test_case(0x0E, 93)]
+        #[test_case(0x0F, 93)]
+        fn waveform_periods(audc: u8, exp: usize) {
+            assert_eq!(period(audc, 0), exp);
+        }
+
+        #[test_case(0x04, 0x00, 2)]
+        #[test_case(0x04, 0x01, 4)]
+        #[test_case(0x04, 0x1F, 64)]
+        #[test_case(0x0C, 0x04, 30)]
+        #[test_case(0x01, 0x02, 45)]
+        fn audf_divides_frequency(audc: u8, audf: u8, exp: usize) {
+            assert_eq!(period(audc, audf), exp);
+        }
+
+        #[test_case(0x00)]
+        #[test_case(0x0B)]
+        fn set_to_one_outputs_volume(audc: u8) {
+            let mut channel = Channel::default();
+            (0..4).for_each(|_| _ = channel.tick(audc, 0x03, 0x09));
+
+            assert!((0..100).all(|_| channel.tick(audc, 0x03, 0x09) == 0x09));
+        }
+
+        #[test]
+        fn pure_tone_toggles_between_silence_and_volume() {
+            let mut channel = Channel::default();
+            let out: Vec<u8> = (0..6).map(|_| channel.tick(0x04, 0x00, 0x0F)).collect();
+
+            assert_eq!(out, [0x0F, 0x00, 0x0F, 0x00, 0x0F, 0x00]);
+        }
     }
 }
@@ -4,4 +4,4 @@ mod tv;
 
 pub use cmn::*;
 pub use core::{InMemoryTIA, TIA};
-pub use tv::{InMemoryTV, TVConfig, TV};
+pub use tv::{InMemoryTV, TVConfig, MAX_SCANLINES, TV};
@@ -1,7 +1,27 @@
+use crate::state::{StateError, StateReader, StateWriter};
+use alloc::{vec, vec::Vec};
 use core::fmt::Debug;
 
-pub trait TV<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
-    fn config(&self) -> &TVConfig<SCANLINES, PIXELS_PER_SCANLINE>;
+/// Scanlines after this many are not drawn, should a program never start a new frame.
+pub const MAX_SCANLINES: usize = 400;
+
+pub trait TV {
+    fn config(&self) -> &TVConfig;
+
+    /// Switch to another standard, e.g. once it has been detected.
+    ///
+    /// NOTE: The TIA keeps the horizontal timing of the config it was created with.
+    fn set_config(&mut self, config: TVConfig);
+
+    /// Colors to display the buffer with, as per the console's TV type switch.
+    #[inline]
+    fn palette(&self) -> &[u32; 128] {
+        if self.black_and_white() {
+            self.config().bw_color_map()
+        } else {
+            self.config().color_map()
+        }
+    }
 
     /// Render current pixel with the given color.
     fn render_pixel(&mut self, color: u8) {
@@ -16,7 +36,7 @@ pub trait TV<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
 
     #[inline]
     fn render_pixel_core(&mut self, color: u8) {
-        if self.vsync_on() || self.current_scanline() >= self.config().scanlines() {
+        if self.vsync_on() || self.current_scanline() >= MAX_SCANLINES {
             return;
         }
 
@@ -39,6 +59,42 @@ pub trait TV<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
         self.post_vsync();
     }
 
+    /// Beam position, frame bookkeeping, config & B&W setting. Buffer contents are not part of the
+    /// state.
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_u64(self.frame_counter());
+        w.put_bool(self.vsync_on());
+        w.put_u16(self.current_scanline() as u16);
+        w.put_u16(self.current_pixel() as u16);
+        self.config().save_state(w);
+        w.put_bool(self.black_and_white());
+    }
+
+    /// Blobs older than v15 keep the current config & B&W setting.
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        let frame_counter = r.get_u64()?;
+        let vsync_on = r.get_bool()?;
+        let scanline = r.get_u16()? as usize;
+        let pixel = r.get_u16()? as usize;
+        if r.version() >= 15 {
+            let config = TVConfig::load_state(r)?;
+            let black_and_white = r.get_bool()?;
+            if config != *self.config() {
+                self.set_config(config);
+            }
+            self.set_black_and_white(black_and_white);
+        }
+        if pixel >= self.config().pixels_per_scanline() {
+            return Err(r.corrupt());
+        }
+        self.set_frame_counter(frame_counter);
+        self.set_vsync_on(vsync_on);
+        self.set_current_scanline(scanline);
+        self.set_current_pixel(pixel);
+
+        Ok(())
+    }
+
     fn current_scanline(&self) -> usize;
 
     fn set_current_scanline(&mut self, scanline: usize);
@@ -49,7 +105,7 @@ pub trait TV<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
 
     fn write_buffer(&mut self, color: u8);
 
-    fn post_vsync(&self);
+    fn post_vsync(&mut self);
 
     fn frame_counter(&self) -> u64;
 
@@ -58,47 +114,48 @@ pub trait TV<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
     fn vsync_on(&self) -> bool;
 
     fn set_vsync_on(&mut self, on: bool);
+
+    fn black_and_white(&self) -> bool;
+
+    fn set_black_and_white(&mut self, on: bool);
 }
 
 #[derive(Debug)]
-pub struct InMemoryTV<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
+pub struct InMemoryTV {
     /// Number of times VSYNC has been called.
     frame_counter: u64,
     vsync_on: bool,
+    black_and_white: bool,
     curr_scanline: usize,
     curr_pixel: usize,
-    config: TVConfig<SCANLINES, PIXELS_PER_SCANLINE>,
-    buffer: [[u8; PIXELS_PER_SCANLINE]; SCANLINES],
+    config: TVConfig,
+    /// As many scanlines as the config has, more if the last frame was taller.
+    buffer: Vec<Vec<u8>>,
     /// Total duration the for rendering all frames so far.
     duration: u64,
 }
 
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
-    InMemoryTV<SCANLINES, PIXELS_PER_SCANLINE>
-{
-    pub fn new(config: TVConfig<SCANLINES, PIXELS_PER_SCANLINE>) -> Self {
+impl InMemoryTV {
+    pub fn new(config: TVConfig) -> Self {
         Self::new_testable(0, 0, config)
     }
 
-    pub fn new_testable(
-        scanline: usize,
-        pixel: usize,
-        config: TVConfig<SCANLINES, PIXELS_PER_SCANLINE>,
-    ) -> Self {
+    pub fn new_testable(scanline: usize, pixel: usize, config: TVConfig) -> Self {
         Self {
             frame_counter: 0,
             vsync_on: false,
+            black_and_white: false,
             curr_scanline: scanline,
             curr_pixel: pixel,
+            buffer: vec![vec![0x00; config.pixels_per_scanline()]; config.scanlines()],
             config,
-            buffer: [[0x00; PIXELS_PER_SCANLINE]; SCANLINES],
             duration: 0,
         }
     }
 
     #[inline]
-    pub fn buffer(&self) -> [[u8; PIXELS_PER_SCANLINE]; SCANLINES] {
-        self.buffer
+    pub fn buffer(&self) -> Vec<Vec<u8>> {
+        self.buffer.clone()
     }
 
     #[inline]
@@ -107,16 +164,24 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
     }
 }
 
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> TV<SCANLINES, PIXELS_PER_SCANLINE>
-    for InMemoryTV<SCANLINES, PIXELS_PER_SCANLINE>
-{
+impl TV for InMemoryTV {
     #[inline]
-    fn config(&self) -> &TVConfig<SCANLINES, PIXELS_PER_SCANLINE> {
+    fn config(&self) -> &TVConfig {
         &self.config
     }
 
+    fn set_config(&mut self, config: TVConfig) {
+        self.buffer = vec![vec![0x00; config.pixels_per_scanline()]; config.scanlines()];
+        self.curr_pixel %= config.pixels_per_scanline();
+        self.config = config;
+    }
+
+    /// Scanlines past the nominal height are dropped once the next frame starts drawing, they are
+    /// drawn again if the program keeps producing tall frames.
     #[inline]
-    fn post_vsync(&self) {}
+    fn post_vsync(&mut self) {
+        self.buffer.truncate(self.config.scanlines());
+    }
 
     #[inline]
     fn current_scanline(&self) -> usize {
@@ -140,7 +205,11 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> TV<SCANLINES, PIX
 
     #[inline]
     fn write_buffer(&mut self, color: u8) {
-        self.buffer[self.current_scanline()][self.current_pixel()] = color;
+        if self.curr_scanline >= self.buffer.len() {
+            let row = vec![0x00; self.config.pixels_per_scanline()];
+            self.buffer.resize(self.curr_scanline + 1, row);
+        }
+        self.buffer[self.curr_scanline][self.curr_pixel] = color;
     }
 
     #[inline]
@@ -162,18 +231,20 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> TV<SCANLINES, PIX
     fn set_vsync_on(&mut self, on: bool) {
         self.vsync_on = on;
     }
-}
 
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> Default
-    for TVConfig<SCANLINES, PIXELS_PER_SCANLINE>
-{
-    fn default() -> Self {
-        TVConfig::<SCANLINES, PIXELS_PER_SCANLINE>::new(PIXELS_PER_SCANLINE - 1, [0x00u32; 128])
+    #[inline]
+    fn black_and_white(&self) -> bool {
+        self.black_and_white
+    }
+
+    #[inline]
+    fn set_black_and_white(&mut self, on: bool) {
+        self.black_and_white = on;
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct TVConfig<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TVConfig {
     // Rows.
     scanlines: usize,
     // Columns.
@@ -182,18 +253,23 @@ pub struct TVConfig<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize> {
     visible_pixels: usize,
     // colors
     color_map: [u32; 128],
+    bw_color_map: [u32; 128],
 }
 
-impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
-    TVConfig<SCANLINES, PIXELS_PER_SCANLINE>
-{
-    pub fn new(draw_pixels: usize, color_map: [u32; 128]) -> Self {
+impl TVConfig {
+    pub fn new(
+        scanlines: usize,
+        pixels_per_scanline: usize,
+        draw_pixels: usize,
+        color_map: [u32; 128],
+    ) -> Self {
         let ret = Self {
-            scanlines: SCANLINES,
-            pixels_per_scanline: PIXELS_PER_SCANLINE,
-            hblank_pixels: PIXELS_PER_SCANLINE - draw_pixels,
+            scanlines,
+            pixels_per_scanline,
+            hblank_pixels: pixels_per_scanline.saturating_sub(draw_pixels),
             visible_pixels: draw_pixels,
             color_map,
+            bw_color_map: color_map.map(luma),
         };
 
         if ret.pixels_per_scanline() != (ret.hblank_pixels() + ret.visible_pixels()) {
@@ -208,6 +284,13 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
         &self.color_map
     }
 
+    /// What a B&W set shows for each color.
+    #[inline]
+    pub fn bw_color_map(&self) -> &[u32; 128] {
+        &self.bw_color_map
+    }
+
+    /// Nominal frame height. Programs may draw more or fewer, see [`MAX_SCANLINES`].
     #[inline]
     pub fn scanlines(&self) -> usize {
         self.scanlines
@@ -227,6 +310,43 @@ impl<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>
     pub fn visible_pixels(&self) -> usize {
         self.visible_pixels
     }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.put_u16(self.scanlines as u16);
+        w.put_u16(self.pixels_per_scanline as u16);
+        w.put_u16(self.visible_pixels as u16);
+        self.color_map.iter().for_each(|&x| w.put_u32(x));
+    }
+
+    fn load_state(r: &mut StateReader) -> Result<Self, StateError> {
+        let scanlines = r.get_u16()? as usize;
+        let pixels_per_scanline = r.get_u16()? as usize;
+        let visible_pixels = r.get_u16()? as usize;
+        let mut color_map = [0; 128];
+        for x in color_map.iter_mut() {
+            *x = r.get_u32()?;
+        }
+        if scanlines == 0 || pixels_per_scanline == 0 || visible_pixels > pixels_per_scanline {
+            return Err(r.corrupt());
+        }
+
+        Ok(Self::new(
+            scanlines,
+            pixels_per_scanline,
+            visible_pixels,
+            color_map,
+        ))
+    }
+}
+
+/// Grey of the same brightness as the `0xRRGGBBAA` color.
+///
+/// Refer: https://en.wikipedia.org/wiki/Luma_(video)#Rec._601_luma_versus_Rec._709_luma_coefficients
+fn luma(rgba: u32) -> u32 {
+    let [r, g, b, a] = rgba.to_be_bytes().map(|x| x as u32);
+    let y = ((299 * r + 587 * g + 114 * b + 500) / 1000) as u8;
+
+    u32::from_be_bytes([y, y, y, a as u8])
 }
 
 #[cfg(test)]
@@ -246,16 +366,21 @@ mod tests {
     ///  1│x  │  
     ///  2│x  │  
     ///   └───┘            
-    type TestableTVConfig = TVConfig<SCANLINES, PIXELS_PER_SCANLINE>;
-
-    type TestableTV = InMemoryTV<SCANLINES, PIXELS_PER_SCANLINE>;
+    fn testable_config() -> TVConfig {
+        TVConfig::new(
+            SCANLINES,
+            PIXELS_PER_SCANLINE,
+            PIXELS_PER_SCANLINE - 1,
+            [0x00; 128],
+        )
+    }
 
     #[test_case(1, 1)]
     #[test_case(1, 2)]
     #[test_case(2, 1)]
     #[test_case(2, 2)]
     fn render_pixel_on_draw_areas(scanline: usize, pixel: usize) {
-        let mut tv = TestableTV::new_testable(scanline, pixel, TestableTVConfig::default());
+        let mut tv = InMemoryTV::new_testable(scanline, pixel, testable_config());
 
         tv.render_pixel(0x01);
 
@@ -267,7 +392,7 @@ mod tests {
 
     #[test]
     fn increment_pixel() {
-        let mut tv = TestableTV::new_testable(1, 1, TestableTVConfig::default());
+        let mut tv = InMemoryTV::new_testable(1, 1, testable_config());
         tv.render_pixel(0x01);
         tv.render_pixel(0x02);
 
@@ -276,7 +401,7 @@ mod tests {
 
     #[test]
     fn vsync_resets_scanline() {
-        let mut tv = TestableTV::new_testable(2, 1, TestableTVConfig::default());
+        let mut tv = InMemoryTV::new_testable(2, 1, testable_config());
 
         tv.vsync_start();
         [0x01, 0x02, 0x03].iter().for_each(|&x| tv.render_pixel(x));
@@ -287,4 +412,59 @@ mod tests {
         assert_eq!(tv.buffer()[1], [0x00, 0x04, 0x00]);
         assert_eq!(tv.frame_counter(), 1);
     }
+
+    #[test]
+    fn buffer_grows_for_taller_frames() {
+        let mut tv = InMemoryTV::new_testable(SCANLINES + 1, 2, testable_config());
+
+        tv.render_pixel(0x01);
+        assert_eq!(tv.buffer().len(), SCANLINES + 2);
+        assert_eq!(tv.buffer()[SCANLINES + 1], [0x00, 0x00, 0x01]);
+
+        tv.set_current_scanline(MAX_SCANLINES);
+        tv.render_pixel(0x02);
+        assert_eq!(tv.buffer().len(), SCANLINES + 2);
+
+        tv.vsync_start();
+        assert_eq!(tv.buffer().len(), SCANLINES + 2);
+        tv.vsync_end();
+        assert_eq!(tv.buffer().len(), SCANLINES);
+    }
+
+    #[test]
+    fn set_config_resizes_buffer() {
+        let mut tv = InMemoryTV::new_testable(1, 1, testable_config());
+        tv.render_pixel(0x01);
+
+        tv.set_config(TVConfig::new(5, 2, 1, [0x00; 128]));
+        assert_eq!(tv.buffer(), vec![vec![0x00; 2]; 5]);
+        assert_eq!(tv.current_pixel(), 0);
+        assert_eq!(tv.config().scanlines(), 5);
+    }
+
+    #[test_case(0x000000FF, 0x000000FF; "Black")]
+    #[test_case(0xFFFFFF80, 0xFFFFFF80; "White")]
+    #[test_case(0xFF0000FF, 0x4C4C4CFF; "Red")]
+    #[test_case(0x00FF00FF, 0x969696FF; "Green")]
+    #[test_case(0x0000FFFF, 0x1D1D1DFF; "Blue")]
+    fn luma_keeps_brightness(rgba: u32, exp: u32) {
+        assert_eq!(luma(rgba), exp);
+    }
+
+    #[test]
+    fn palette_follows_black_and_white() {
+        let mut color_map = [0x000000FF; 128];
+        color_map[0x43] = 0xFF0000FF;
+        let mut tv = InMemoryTV::new(TVConfig::new(
+            SCANLINES,
+            PIXELS_PER_SCANLINE,
+            PIXELS_PER_SCANLINE - 1,
+            color_map,
+        ));
+        assert_eq!(tv.palette()[0x43], 0xFF0000FF);
+
+        tv.set_black_and_white(true);
+        assert_eq!(tv.palette()[0x43], 0x4C4C4CFF);
+        assert_eq!(tv.palette()[0x00], 0x000000FF);
+    }
 }
@@ -0,0 +1,194 @@
+//! Tells 50Hz programs from 60Hz ones by the number of scanlines between VSYNCs, as there is
+//! nothing in a ROM that says which TV it was made for.
+//!
+//! - The frames right after power-on are skipped, programs often produce odd ones while
+//!   initializing.
+//! - The median of the next frames decides, so a stray long or short frame does not.
+//! - 50Hz programs are shown as PAL and 60Hz ones as NTSC. PAL60, NTSC50 & SECAM cannot be told
+//!   apart by timing, see [`crate::Atari::set_tv_standard`].
+use crate::tia::{self, TVStandard};
+use alloc::vec::Vec;
+
+/// Frames ignored before counting starts.
+const SKIP_FRAMES: u64 = 2;
+/// Frames whose scanlines are counted.
+const SAMPLE_FRAMES: usize = 8;
+/// Halfway between NTSC & PAL frames.
+const MIN_PAL_SCANLINES: usize = (tia::NTSC_SCANLINES + tia::PAL_SCANLINES) / 2;
+
+#[derive(Debug, Clone)]
+pub struct TVDetector {
+    pixels_per_scanline: usize,
+    /// Frame counter when detection started.
+    start: u64,
+    /// Frame being counted.
+    frame: u64,
+    clocks: usize,
+    /// Scanlines of each complete frame counted so far.
+    scanlines: Vec<usize>,
+    standard: Option<TVStandard>,
+}
+
+impl TVDetector {
+    pub fn new(frame: u64, pixels_per_scanline: usize) -> Self {
+        Self {
+            pixels_per_scanline,
+            start: frame,
+            frame,
+            clocks: 0,
+            scanlines: Vec::with_capacity(SAMPLE_FRAMES),
+            standard: None,
+        }
+    }
+
+    /// The decision, once enough frames have been seen.
+    #[inline]
+    pub fn standard(&self) -> Option<TVStandard> {
+        self.standard
+    }
+
+    /// Scanlines of the frames counted so far, oldest first.
+    #[inline]
+    pub fn scanlines(&self) -> &[usize] {
+        &self.scanlines
+    }
+
+    /// Called for every color clock while the TV shows `frame`. Returns the standard on the clock
+    /// it is decided.
+    pub fn tick(&mut self, frame: u64) -> Option<TVStandard> {
+        if self.standard.is_some() {
+            return None;
+        }
+
+        if frame != self.frame {
+            if self.frame >= self.start + SKIP_FRAMES {
+                self.scanlines
+                    .push(self.clocks.div_ceil(self.pixels_per_scanline));
+            }
+            self.frame = frame;
+            self.clocks = 0;
+            if self.scanlines.len() == SAMPLE_FRAMES {
+                self.standard = Some(self.decide());
+                return self.standard;
+            }
+        }
+        self.clocks += 1;
+
+        None
+    }
+
+    /// Start over from `frame`, dropping any decision, e.g. after loading a state. The state
+    /// brings back the TV config it was taken with, which may predate the decision.
+    pub fn restart(&mut self, frame: u64) {
+        *self = Self::new(frame, self.pixels_per_scanline);
+    }
+
+    fn decide(&self) -> TVStandard {
+        let mut scanlines = self.scanlines.clone();
+        scanlines.sort_unstable();
+        if scanlines[scanlines.len() / 2] >= MIN_PAL_SCANLINES {
+            TVStandard::Pal
+        } else {
+            TVStandard::Ntsc
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const PIXELS_PER_SCANLINE: usize = 228;
+
+    /// Runs frames of the given heights, returning the frame the decision came on, if any.
+    fn run(detector: &mut TVDetector, frames: &[usize]) -> Option<(usize, TVStandard)> {
+        let mut decision = None;
+        let mut frame = detector.frame;
+        for (i, &scanlines) in frames.iter().enumerate() {
+            for _ in 0..scanlines * PIXELS_PER_SCANLINE {
+                if let Some(x) = detector.tick(frame) {
+                    decision = decision.or(Some((i, x)));
+                }
+            }
+            frame += 1;
+        }
+        // Starting the next frame completes the last one.
+        if let Some(x) = detector.tick(frame) {
+            decision = decision.or(Some((frames.len(), x)));
+        }
+
+        decision
+    }
+
+    #[test_case(262, TVStandard::Ntsc; "NTSC")]
+    #[test_case(312, TVStandard::Pal; "PAL")]
+    #[test_case(280, TVStandard::Ntsc; "Tall NTSC")]
+    #[test_case(295, TVStandard::Pal; "Short PAL")]
+    fn decides_by_scanlines(scanlines: usize, exp: TVStandard) {
+        let mut detector = TVDetector::new(0, PIXELS_PER_SCANLINE);
+
+        let decision = run(&mut detector, &[scanlines; 10]);
+
+        assert_eq!(decision, Some((10, exp)));
+        assert_eq!(detector.standard(), Some(exp));
+        assert_eq!(detector.scanlines(), &[scanlines; SAMPLE_FRAMES]);
+    }
+
+    #[test]
+    fn skips_power_on_frames() {
+        let mut detector = TVDetector::new(0, PIXELS_PER_SCANLINE);
+
+        run(&mut detector, &[100, 1000, 312, 312]);
+
+        assert_eq!(detector.scanlines(), &[312, 312]);
+        assert_eq!(detector.standard(), None);
+    }
+
+    #[test]
+    fn stray_frames_are_outvoted() {
+        let mut detector = TVDetector::new(0, PIXELS_PER_SCANLINE);
+
+        run(
+            &mut detector,
+            &[312, 312, 312, 262, 312, 312, 600, 312, 312, 312],
+        );
+
+        assert_eq!(detector.standard(), Some(TVStandard::Pal));
+    }
+
+    #[test]
+    fn decision_is_final() {
+        let mut detector = TVDetector::new(0, PIXELS_PER_SCANLINE);
+        run(&mut detector, &[262; 10]);
+
+        assert_eq!(run(&mut detector, &[312; 10]), None);
+        assert_eq!(detector.standard(), Some(TVStandard::Ntsc));
+        assert_eq!(detector.scanlines(), &[262; SAMPLE_FRAMES]);
+    }
+
+    #[test]
+    fn restart_drops_decision() {
+        let mut detector = TVDetector::new(0, PIXELS_PER_SCANLINE);
+        run(&mut detector, &[262; 10]);
+
+        detector.restart(100);
+        assert_eq!(detector.standard(), None);
+        assert!(detector.scanlines().is_empty());
+        run(&mut detector, &[312; 10]);
+
+        assert_eq!(detector.standard(), Some(TVStandard::Pal));
+    }
+
+    #[test]
+    fn restart_drops_partial_counts() {
+        let mut detector = TVDetector::new(0, PIXELS_PER_SCANLINE);
+        run(&mut detector, &[262; 6]);
+
+        detector.restart(50);
+        assert!(detector.scanlines().is_empty());
+        run(&mut detector, &[312; 10]);
+
+        assert_eq!(detector.standard(), Some(TVStandard::Pal));
+    }
+}
@@ -0,0 +1,183 @@
+pub mod common;
+use rustella::{
+    cmn::{LoHi, RefExtensions},
+    state, tia,
+    tia::TV,
+    Atari,
+};
+use std::{cell::RefCell, rc::Rc};
+
+fn new_atari() -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    (Atari::new(tv.clone()), tv)
+}
+
+#[test]
+fn continue_after_load_is_bit_identical() {
+    common::setup_logger();
+    let rom = common::read_rom("collect/collect-02-Timer.bin");
+
+    let (mut atari, tv) = new_atari();
+    atari.load_rom(0xF800u16, &rom);
+    atari.run_for(30000);
+    // NOTE: Land in the middle of an instruction.
+    atari.tick(7);
+    let saved = atari.save_state();
+    atari.run_for(54143);
+
+    let (mut atari2, tv2) = new_atari();
+    atari2.load_rom(0xF800u16, &rom);
+    atari2.load_state(&saved).unwrap();
+    atari2.run_for(54143);
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+    assert_eq!(atari2.cpu_state().cycles(), atari.cpu_state().cycles());
+    assert_eq!(tv2.borrow().frame_counter(), tv.borrow().frame_counter());
+    assert_eq!(tv2.borrow().buffer(), tv.borrow().buffer());
+}
+
+/// Rewrites a current state as format version 13, i.e. without the TIA audio channels that version
+/// 14 appended to the end of the TIA section, nor the TV config, B&W setting, console switches &
+/// controller devices of version 15.
+fn downgrade_to_v13(state: &[u8]) -> Vec<u8> {
+    const CHANNELS_LEN: usize = 2 * 7;
+    const TV_CONFIG_LEN: usize = 3 * 2 + 128 * 4 + 1;
+    let mut ret = [&state[..4], &13u16.to_le_bytes()].concat();
+    let mut rest = &state[6..];
+    while !rest.is_empty() {
+        let tag = &rest[..4];
+        let len = u32::from_le_bytes(rest[4..8].try_into().unwrap()) as usize;
+        let mut payload = &rest[8..8 + len];
+        rest = &rest[8 + len..];
+        if tag == b"SWCH" || tag == b"CTRL" {
+            continue;
+        }
+        if tag == b"TIA " {
+            payload = &payload[..len - CHANNELS_LEN];
+        }
+        if tag == b"TV  " {
+            payload = &payload[..len - TV_CONFIG_LEN];
+        }
+        ret.extend_from_slice(tag);
+        ret.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        ret.extend_from_slice(payload);
+    }
+
+    ret
+}
+
+/// The audio channels of an older state start from power-on, nothing else may differ.
+#[test]
+fn continue_after_loading_older_version_is_bit_identical() {
+    common::setup_logger();
+    let rom = common::read_rom("collect/collect-02-Timer.bin");
+
+    let (mut atari, tv) = new_atari();
+    atari.load_rom(0xF800u16, &rom);
+    atari.run_for(30000);
+    atari.tick(7);
+    let v13 = downgrade_to_v13(&atari.save_state());
+    atari.run_for(54143);
+
+    let (mut atari2, tv2) = new_atari();
+    atari2.load_state(&v13).unwrap();
+    atari2.run_for(54143);
+
+    assert_eq!(
+        downgrade_to_v13(&atari2.save_state()),
+        downgrade_to_v13(&atari.save_state())
+    );
+    assert_eq!(atari2.cpu_state().cycles(), atari.cpu_state().cycles());
+    assert_eq!(atari2.cpu_state().pc(), atari.cpu_state().pc());
+    assert_eq!(tv2.borrow().frame_counter(), tv.borrow().frame_counter());
+    assert_eq!(tv2.borrow().buffer(), tv.borrow().buffer());
+}
+
+/// NOTE: The fixture was written by format version 1 after 30000 instructions. Back then each call
+/// to [`Atari::tick`] restarted the CPU / TIA clock phase, so single clock ticks ran the CPU on
+/// every color clock and the history up to that point differs from a fresh run. What the file
+/// holds is restored exactly, once a few frames are run the picture agrees.
+/// The cartridge is part of the state, so no ROM is loaded before restoring it.
+#[test]
+fn continue_after_loading_v1_state() {
+    common::setup_logger();
+    let v1 = common::read_rom("states/collect-02-Timer-v1.state");
+    assert_eq!(&v1[4..6], &1u16.to_le_bytes());
+
+    let (mut atari, tv) = new_atari();
+    atari.load_rom(0xF800u16, &common::read_rom("collect/collect-02-Timer.bin"));
+    (0..5).for_each(|_| assert!(atari.run_frame()));
+
+    let (mut atari2, tv2) = new_atari();
+    atari2.load_state(&v1).unwrap();
+    let cpu = atari2.cpu_state();
+    assert_eq!(cpu.pc(), LoHi(0x3C, 0xF8));
+    assert_eq!(cpu.instructions(), 30000);
+    assert_eq!(cpu.cycles(), 84262);
+    assert_eq!(tv2.borrow().frame_counter(), 34);
+    (0..5).for_each(|_| assert!(atari2.run_frame()));
+
+    assert_eq!(tv2.borrow().frame_counter(), 39);
+    assert_eq!(tv2.borrow().buffer(), tv.borrow().buffer());
+    assert_eq!(atari2.frame_checksum().video, atari.frame_checksum().video);
+}
+
+/// The state brings its TV along, whatever the loading machine was built with.
+#[test]
+fn pal_state_loads_into_ntsc_machine() {
+    common::setup_logger();
+    let rom = common::read_rom("collect/collect-02-Timer.bin");
+    let pal = || tia::InMemoryTV::new(tia::pal_tv_config()).rc_refcell();
+
+    let tv = pal();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF800u16, &rom);
+    atari.set_switches(rustella::ctrl::ConsoleSwitches {
+        color: false,
+        left_difficulty: rustella::ctrl::Difficulty::A,
+        ..Default::default()
+    });
+    atari.run_for(30000);
+    atari.tick(7);
+    let saved = atari.save_state();
+    atari.run_for(54143);
+
+    let (mut atari2, tv2) = new_atari();
+    atari2.load_state(&saved).unwrap();
+    assert_eq!(atari2.tv_standard(), Some(tia::TVStandard::Pal));
+    assert_eq!(atari2.cpu_hz(), tia::PAL_CPU_HZ);
+    assert!(tv2.borrow().black_and_white());
+    assert_eq!(atari2.switches(), atari.switches());
+    atari2.run_for(54143);
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+    assert_eq!(tv2.borrow().buffer(), tv.borrow().buffer());
+    assert_eq!(tv2.borrow().palette(), tv.borrow().palette());
+    assert_eq!(tv2.borrow().buffer().len(), tia::PAL_SCANLINES);
+}
+
+#[test]
+fn state_is_compact() {
+    let (mut atari, _) = new_atari();
+    atari.load_rom(0xF800u16, &common::read_rom("collect/collect-02-Timer.bin"));
+    atari.run_for(30000);
+
+    let saved = atari.save_state();
+    assert!(saved.len() < 4 * 1024, "State size {}", saved.len());
+}
+
+#[test]
+fn load_rejects_bad_states() {
+    let (mut atari, _) = new_atari();
+    let saved = atari.save_state();
+
+    assert_eq!(
+        atari.load_state(&saved[..saved.len() - 1]),
+        Err(state::StateError::UnexpectedEnd)
+    );
+    assert_eq!(
+        atari.load_state(&[saved.as_slice(), &[0x00]].concat()),
+        Err(state::StateError::Corrupt(*b"HEAD"))
+    );
+    assert_eq!(atari.load_state(b"NOPE"), Err(state::StateError::BadMagic));
+}
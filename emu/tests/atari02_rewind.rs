@@ -0,0 +1,91 @@
+pub mod common;
+use rustella::{cmn::RefExtensions, rewind::RewindConfig, tia, Atari};
+use std::collections::HashMap;
+
+fn new_atari() -> Atari {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv);
+    atari.load_rom(0xF800u16, &common::read_rom("collect/collect-02-Timer.bin"));
+    atari
+}
+
+/// Runs till `frame`, collecting the state at the start of every frame.
+fn run_till_frame(atari: &mut Atari, frame: u64, states: &mut HashMap<u64, Vec<u8>>) {
+    while atari.frame_counter() < frame {
+        let prev = atari.frame_counter();
+        atari.tick(1);
+        if atari.frame_counter() != prev {
+            states.insert(atari.frame_counter(), atari.save_state());
+        }
+    }
+}
+
+#[test]
+fn rewind_restores_frame_start() {
+    common::setup_logger();
+    let mut states = HashMap::new();
+
+    let mut atari = new_atari();
+    atari.enable_rewind(RewindConfig {
+        interval_frames: 5,
+        budget_bytes: 64 * 1024,
+    });
+    run_till_frame(&mut atari, 32, &mut states);
+
+    assert_eq!(atari.rewind(10), Ok(Some(20)));
+    assert_eq!(atari.frame_counter(), 20);
+    assert_eq!(atari.save_state(), states[&20]);
+
+    assert_eq!(atari.rewind(3), Ok(Some(15)));
+    assert_eq!(atari.save_state(), states[&15]);
+    assert_eq!(atari.rewind_buffer().unwrap().frame_range(), Some((0, 15)));
+}
+
+#[test]
+fn continue_after_rewind_is_bit_identical() {
+    common::setup_logger();
+    let mut states = HashMap::new();
+
+    let mut atari = new_atari();
+    atari.enable_rewind(RewindConfig {
+        interval_frames: 1,
+        budget_bytes: 64 * 1024,
+    });
+    run_till_frame(&mut atari, 30, &mut states);
+    assert_eq!(atari.rewind(12), Ok(Some(18)));
+    run_till_frame(&mut atari, 30, &mut states);
+
+    let mut reference = new_atari();
+    let mut ref_states = HashMap::new();
+    run_till_frame(&mut reference, 30, &mut ref_states);
+
+    assert_eq!(atari.save_state(), reference.save_state());
+    assert_eq!(states[&25], ref_states[&25]);
+}
+
+#[test]
+fn rewind_respects_memory_budget() {
+    common::setup_logger();
+    let mut atari = new_atari();
+    atari.enable_rewind(RewindConfig {
+        interval_frames: 1,
+        budget_bytes: 4 * 1024,
+    });
+    run_till_frame(&mut atari, 60, &mut HashMap::new());
+
+    let rb = atari.rewind_buffer().unwrap();
+    assert!(rb.memory_used() <= 4 * 1024);
+    let (oldest, newest) = rb.frame_range().unwrap();
+    assert_eq!(newest, 60);
+    assert!(oldest > 0 && oldest < 60);
+
+    assert_eq!(atari.rewind(1000), Ok(Some(oldest)));
+}
+
+#[test]
+fn rewind_when_disabled() {
+    let mut atari = new_atari();
+    atari.tick(100);
+
+    assert_eq!(atari.rewind(1), Ok(None));
+}
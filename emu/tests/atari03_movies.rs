@@ -0,0 +1,259 @@
+pub mod common;
+use rustella::{
+    cmn::RefExtensions,
+    ctrl::{ConsoleSwitches, ControllerInput, Difficulty, JoystickState, Port},
+    movie::{InputEvent, Movie, MovieError, MovieInput, MoviePlayer, MovieRecorder, MovieStart},
+    power_on::PowerOnConfig,
+    tia, Atari,
+};
+use std::{fmt, str::FromStr};
+
+const ROM: &str = "collect/collect-03-ScoreAndTimerDisplay.bin";
+
+fn new_atari() -> Atari {
+    new_atari_with(ROM, PowerOnConfig::default())
+}
+
+fn new_atari_with(rom: &str, power_on: PowerOnConfig) -> Atari {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new_with_power_on(tv, power_on);
+    atari.load_rom(0xF800u16, &common::read_rom(rom));
+    atari
+}
+
+/// Stalls the machine for some color clocks. Shifts everything after it, like a real input would.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stall(usize);
+
+impl fmt::Display for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stall {}", self.0)
+    }
+}
+
+impl FromStr for Stall {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("stall ")
+            .and_then(|x| x.parse().ok())
+            .map(Stall)
+            .ok_or(())
+    }
+}
+
+impl MovieInput for Stall {
+    fn apply(&self, atari: &mut Atari) {
+        atari.tick(self.0);
+    }
+}
+
+fn record(atari: &mut Atari, mut recorder: MovieRecorder<Stall>, frames: u64) -> Movie<Stall> {
+    while recorder.frame() < frames {
+        if recorder.frame() % 7 == 3 {
+            recorder.input(atari, Stall(recorder.frame() as usize * 11));
+        }
+        assert!(recorder.run_frame(atari));
+    }
+
+    recorder.finish()
+}
+
+#[test]
+fn playback_from_power_on_reproduces_recording() {
+    common::setup_logger();
+    let mut atari = new_atari();
+    let recorder = MovieRecorder::power_on(&atari);
+    let movie = record(&mut atari, recorder, 30);
+    let text = movie.to_text();
+
+    let mut atari2 = new_atari();
+    let movie2 = Movie::<Stall>::from_text(&text).unwrap();
+    assert_eq!(movie2, movie);
+    let mut player = MoviePlayer::new(movie2, &mut atari2).unwrap();
+    player.play(&mut atari2).unwrap();
+
+    assert!(player.is_finished());
+    assert_eq!(player.frame(), 30);
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
+
+#[test]
+fn playback_from_state_reproduces_recording() {
+    common::setup_logger();
+    let mut atari = new_atari();
+    atari.run_for(30000);
+    atari.tick(5);
+    let recorder = MovieRecorder::from_state(&atari);
+    let movie = record(&mut atari, recorder, 20);
+    assert!(matches!(movie.start, MovieStart::State(_)));
+
+    // NOTE: The cartridge is part of the state.
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari2 = Atari::new(tv);
+    let movie2 = Movie::<Stall>::from_text(&movie.to_text()).unwrap();
+    MoviePlayer::new(movie2, &mut atari2)
+        .unwrap()
+        .play(&mut atari2)
+        .unwrap();
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
+
+/// The state does not hold the host's inputs, the movie opens with them.
+#[test]
+fn playback_from_state_restores_host_inputs() {
+    common::setup_logger();
+    let mut atari = new_atari();
+    let stick = ControllerInput::Joystick(JoystickState {
+        up: true,
+        fire: true,
+        ..Default::default()
+    });
+    let switches = ConsoleSwitches {
+        left_difficulty: Difficulty::A,
+        ..Default::default()
+    };
+    atari.set_input(Port::Left, stick);
+    atari.set_switches(switches);
+    atari.run_for(30000);
+    let mut recorder = MovieRecorder::<InputEvent>::from_state(&atari);
+    (0..3).for_each(|_| assert!(recorder.run_frame(&mut atari)));
+    let movie = recorder.finish();
+    assert_eq!(
+        movie.inputs,
+        [
+            (0, InputEvent::Switches(switches)),
+            (0, InputEvent::Controller(Port::Left, stick)),
+            (
+                0,
+                InputEvent::Controller(Port::Right, atari.input(Port::Right).unwrap())
+            ),
+        ]
+    );
+
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari2 = Atari::new(tv);
+    let movie2 = Movie::<InputEvent>::from_text(&movie.to_text()).unwrap();
+    MoviePlayer::new(movie2, &mut atari2)
+        .unwrap()
+        .play(&mut atari2)
+        .unwrap();
+
+    assert_eq!(atari2.input(Port::Left), Some(stick));
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
+
+/// The program spins on `JMP $F000` without ever generating VSYNC.
+#[test]
+fn frames_not_started_in_time_are_reported() {
+    let mut rom = vec![0xEA; 0x1000];
+    rom[..3].copy_from_slice(&[0x4C, 0x00, 0xF0]);
+    rom[0xFFC..0xFFE].copy_from_slice(&[0x00, 0xF0]);
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv);
+    atari.load_rom(0xF000u16, &rom);
+    let mut recorder = MovieRecorder::<Stall>::from_state(&atari);
+    assert!(!recorder.run_frame(&mut atari));
+    let movie = recorder.finish();
+    assert_eq!(movie.len(), 1);
+
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari2 = Atari::new(tv);
+    let mut player = MoviePlayer::new(movie, &mut atari2).unwrap();
+
+    assert_eq!(player.step(&mut atari2), Err(MovieError::NoFrame(0)));
+}
+
+#[test]
+fn playback_reports_first_desync() {
+    common::setup_logger();
+    let mut atari = new_atari();
+    let recorder = MovieRecorder::power_on(&atari);
+    let mut movie = record(&mut atari, recorder, 30);
+    assert_eq!(movie.inputs[1], (10, Stall(110)));
+    // NOTE: The program counts frames in RAM, running one more shifts it.
+    movie.inputs[1].1 = Stall(110 + tia::NTSC_SCANLINES * tia::NTSC_PIXELS_PER_SCANLINE);
+
+    let mut atari2 = new_atari();
+    let mut player = MoviePlayer::new(movie.clone(), &mut atari2).unwrap();
+    let err = player.play(&mut atari2).unwrap_err();
+
+    let MovieError::Desync {
+        frame,
+        expected,
+        found,
+    } = err
+    else {
+        panic!("Expected a desync, found {err:?}.");
+    };
+    assert_eq!(frame, 10);
+    assert_eq!(expected, movie.checksums[10]);
+    assert_ne!(found, expected);
+    assert_eq!(player.frame(), 11);
+}
+
+#[test]
+fn playback_detects_tampered_checksum() {
+    let mut atari = new_atari();
+    let recorder = MovieRecorder::power_on(&atari);
+    let movie = record(&mut atari, recorder, 5);
+    let cs = movie.checksums[2].to_string();
+    let text = movie
+        .to_text()
+        .replace(&format!("frame 2 {cs}"), "frame 2 00000000 00000000");
+
+    let mut atari2 = new_atari();
+    let movie2 = Movie::<Stall>::from_text(&text).unwrap();
+    let err = MoviePlayer::new(movie2, &mut atari2)
+        .unwrap()
+        .play(&mut atari2);
+
+    assert!(matches!(err, Err(MovieError::Desync { frame: 2, .. })));
+}
+
+#[test]
+fn power_on_movie_needs_fresh_machine() {
+    let mut atari = new_atari();
+    let recorder = MovieRecorder::power_on(&atari);
+    let movie = record(&mut atari, recorder, 1);
+
+    let result = MoviePlayer::new(movie, &mut atari);
+    assert!(matches!(result, Err(MovieError::NotAtPowerOn)));
+}
+
+#[test]
+fn power_on_movie_needs_same_cartridge() {
+    let mut atari = new_atari();
+    let recorder = MovieRecorder::power_on(&atari);
+    let movie = record(&mut atari, recorder, 1);
+
+    let mut atari2 = new_atari_with("collect/collect-02-Timer.bin", PowerOnConfig::default());
+    let result = MoviePlayer::new(movie, &mut atari2);
+
+    assert_eq!(
+        result.err(),
+        Some(MovieError::RomMismatch {
+            expected: atari.rom_checksum(),
+            found: atari2.rom_checksum(),
+        })
+    );
+}
+
+#[test]
+fn power_on_movie_needs_same_power_on_contents() {
+    let mut atari = new_atari_with(ROM, PowerOnConfig::random(1));
+    let recorder = MovieRecorder::power_on(&atari);
+    let movie = record(&mut atari, recorder, 1);
+    let movie = Movie::<Stall>::from_text(&movie.to_text()).unwrap();
+
+    let mut atari2 = new_atari_with(ROM, PowerOnConfig::random(2));
+    let result = MoviePlayer::new(movie.clone(), &mut atari2);
+    assert_eq!(result.err(), Some(MovieError::PowerOnMismatch));
+
+    let mut atari3 = new_atari_with(ROM, PowerOnConfig::random(1));
+    MoviePlayer::new(movie, &mut atari3)
+        .unwrap()
+        .play(&mut atari3)
+        .unwrap();
+}
@@ -0,0 +1,49 @@
+pub mod common;
+use rustella::{cmn::RefExtensions, power_on::PowerOnConfig, tia, Atari};
+use test_case::test_case;
+
+fn new_atari(power_on: PowerOnConfig) -> Atari {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new_with_power_on(tv, power_on);
+    atari.load_rom(0xF800u16, &common::read_rom("collect/collect-02-Timer.bin"));
+    atari
+}
+
+#[test]
+fn same_seed_is_deterministic() {
+    common::setup_logger();
+    let mut atari = new_atari(PowerOnConfig::random(1234));
+    let mut atari2 = new_atari(PowerOnConfig::random(1234));
+    assert_eq!(atari2.save_state(), atari.save_state());
+
+    atari.run_for(10000);
+    atari2.run_for(10000);
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
+
+#[test]
+fn seeds_change_power_on_state() {
+    let atari = new_atari(PowerOnConfig::random(1));
+    let atari2 = new_atari(PowerOnConfig::random(2));
+    let zero = new_atari(PowerOnConfig::zero());
+
+    assert_ne!(atari.frame_checksum().ram, atari2.frame_checksum().ram);
+    assert_ne!(atari.cpu_state().a(), atari2.cpu_state().a());
+    assert_eq!(zero.cpu_state().a(), 0x00);
+}
+
+/// NOTE: The program starts with CLEAN_START, so nothing from power-on survives the first frame.
+#[test_case(PowerOnConfig::random(0); "Seed 0")]
+#[test_case(PowerOnConfig::random(0xFFFF_FFFF_FFFF_FFFF); "Seed max")]
+#[test_case(PowerOnConfig::default(); "Default")]
+fn initialized_program_ignores_power_on_state(power_on: PowerOnConfig) {
+    common::setup_logger();
+    let mut atari = new_atari(power_on);
+    let mut zero = new_atari(PowerOnConfig::zero());
+
+    (0..5).for_each(|_| {
+        atari.run_frame();
+        zero.run_frame();
+    });
+    assert_eq!(atari.frame_checksum(), zero.frame_checksum());
+}
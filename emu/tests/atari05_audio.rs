@@ -0,0 +1,136 @@
+pub mod common;
+use rustella::{
+    audio::{AudioConfig, AudioFrame},
+    cmn::RefExtensions,
+    power_on::PowerOnConfig,
+    tia, Atari,
+};
+use test_case::test_case;
+
+/// Ball bouncing around, with a tone for a frame on every bounce.
+fn new_atari(sample_rate: u32) -> Atari {
+    new_atari_on(tia::ntsc_tv_config(), sample_rate)
+}
+
+fn new_atari_on(cfg: tia::TVConfig, sample_rate: u32) -> Atari {
+    let tv = tia::InMemoryTV::new(cfg).rc_refcell();
+    let mut atari = Atari::new_with_power_on(tv, PowerOnConfig::random(2600));
+    atari.load_rom(
+        0xF000,
+        &common::read_rom("8blit/8blit-s03e02-Ex1-Sound 1 tone 1 channel.bin"),
+    );
+    atari.enable_audio(AudioConfig { sample_rate });
+    atari
+}
+
+fn run_frames(atari: &mut Atari, frames: usize) -> Vec<AudioFrame> {
+    (0..frames).for_each(|_| assert!(atari.run_frame()));
+    atari.drain_audio_frames()
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[test_case(44_100)]
+#[test_case(48_000)]
+fn frame_sample_counts_follow_frame_counter(sample_rate: u32) {
+    common::setup_logger();
+    let mut atari = new_atari(sample_rate);
+
+    let frames = run_frames(&mut atari, 60);
+
+    let numbers: Vec<_> = frames.iter().map(|x| x.frame).collect();
+    assert_eq!(numbers, (0..60).collect::<Vec<_>>());
+    assert_eq!(atari.frame_counter(), 60);
+    // NOTE: A NTSC frame is 262 scanlines, of two native samples each.
+    let exp = 524.0 * sample_rate as f64 / rustella::tia::AUDIO_SAMPLE_RATE as f64;
+    frames
+        .iter()
+        .skip(1)
+        .for_each(|x| assert!((x.samples.len() as f64 - exp).abs() <= 1.0));
+    let total: usize = frames.iter().skip(1).map(|x| x.samples.len()).sum();
+    assert!((total as f64 - 59.0 * exp).abs() <= 1.0);
+}
+
+/// The program runs 262 scanline frames on any TV, a PAL console just runs them slower.
+#[test_case(tia::pal_tv_config(), tia::PAL_CPU_HZ; "PAL")]
+#[test_case(tia::secam_tv_config(), tia::PAL_CPU_HZ; "SECAM")]
+#[test_case(tia::ntsc50_tv_config(), tia::NTSC_CPU_HZ; "NTSC50")]
+fn frame_sample_counts_follow_cpu_clock(cfg: tia::TVConfig, cpu_hz: u32) {
+    common::setup_logger();
+    let mut atari = new_atari_on(cfg, 48_000);
+    assert_eq!(atari.cpu_hz(), cpu_hz);
+
+    let frames = run_frames(&mut atari, 60);
+
+    let native_rate = cpu_hz as f64 / tia::CPU_CYCLES_PER_AUDIO_SAMPLE as f64;
+    let exp = 524.0 * 48_000.0 / native_rate;
+    let total: usize = frames.iter().skip(1).map(|x| x.samples.len()).sum();
+    assert!((total as f64 - 59.0 * exp).abs() <= 1.0);
+}
+
+#[test]
+fn set_tv_standard_changes_cpu_clock() {
+    common::setup_logger();
+    let mut atari = new_atari(48_000);
+    run_frames(&mut atari, 10);
+
+    atari.set_tv_standard(tia::TVStandard::Pal60);
+    let frames = run_frames(&mut atari, 60);
+
+    assert_eq!(atari.cpu_hz(), tia::PAL_CPU_HZ);
+    let native_rate = tia::PAL_CPU_HZ as f64 / tia::CPU_CYCLES_PER_AUDIO_SAMPLE as f64;
+    let total: usize = frames.iter().skip(1).map(|x| x.samples.len()).sum();
+    assert!((total as f64 - 59.0 * 524.0 * 48_000.0 / native_rate).abs() <= 1.0);
+}
+
+/// The ROM plays the tone at AUDV0 4 for a frame per bounce, see tia09_sound.rs.
+///
+/// NOTE: The tone runs into the vertical blank of the next frame, which is quieter.
+#[test]
+fn bounces_are_heard() {
+    common::setup_logger();
+    let mut atari = new_atari(48_000);
+
+    let frames = run_frames(&mut atari, 50);
+
+    let loud: Vec<_> = frames
+        .iter()
+        .filter(|x| rms(&x.samples) > 0.1)
+        .map(|x| x.frame)
+        .collect();
+    assert_eq!(loud[..4], [8, 21, 34, 41]);
+    assert!(frames
+        .iter()
+        .all(|x| x.samples.iter().all(|x| x.abs() <= 1.0)));
+}
+
+#[test]
+fn load_state_keeps_audio_in_sync() {
+    common::setup_logger();
+    let mut atari = new_atari(48_000);
+    run_frames(&mut atari, 5);
+    let state = atari.save_state();
+
+    run_frames(&mut atari, 5);
+    atari.load_state(&state).unwrap();
+    let frames = run_frames(&mut atari, 3);
+
+    assert_eq!(atari.frame_counter(), 8);
+    assert_eq!(
+        frames.iter().map(|x| x.frame).collect::<Vec<_>>(),
+        [5, 6, 7]
+    );
+}
+
+#[test]
+fn disabled_audio_produces_nothing() {
+    common::setup_logger();
+    let mut atari = new_atari(48_000);
+    atari.disable_audio();
+
+    assert!(run_frames(&mut atari, 2).is_empty());
+    assert!(atari.audio_config().is_none());
+    assert!(!atari.drain_tia_audio().is_empty());
+}
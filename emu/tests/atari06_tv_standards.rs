@@ -0,0 +1,179 @@
+pub mod common;
+use rustella::{
+    cmn::RefExtensions,
+    tia::{self, TVStandard, TV},
+    Atari,
+};
+use std::{cell::RefCell, rc::Rc};
+
+const ROM: &str = "8blit/8blit-s03e04-Regions-final.bin";
+
+fn new_atari(cfg: tia::TVConfig) -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(cfg).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF000u16, &common::read_rom(ROM));
+    (atari, tv)
+}
+
+/// The program knows nothing of the TV, so it runs the same on all of them.
+#[test]
+fn program_ignores_tv_standard() {
+    common::setup_logger();
+    let (mut ntsc, _) = new_atari(tia::ntsc_tv_config());
+    let (mut pal60, _) = new_atari(tia::pal60_tv_config());
+    let (mut pal, _) = new_atari(tia::pal_tv_config());
+    let (mut secam, _) = new_atari(tia::secam_tv_config());
+
+    for _ in 0..10 {
+        assert!(ntsc.run_frame());
+        assert!(pal60.run_frame());
+        assert!(pal.run_frame());
+        assert!(secam.run_frame());
+    }
+
+    assert_eq!(pal60.frame_checksum(), ntsc.frame_checksum());
+    assert_eq!(pal.frame_checksum(), ntsc.frame_checksum());
+    assert_eq!(secam.frame_checksum(), ntsc.frame_checksum());
+    assert_eq!(pal.cpu_state().cycles(), ntsc.cpu_state().cycles());
+}
+
+#[test]
+fn ntsc50_leaves_extra_scanlines_blank() {
+    let (mut atari, tv) = new_atari(tia::ntsc50_tv_config());
+    (0..10).for_each(|_| assert!(atari.run_frame()));
+
+    let buffer = tv.borrow().buffer();
+    assert_eq!(buffer.len(), tia::PAL_SCANLINES);
+    assert!(buffer[..tia::NTSC_SCANLINES]
+        .iter()
+        .flatten()
+        .any(|&x| x != 0));
+    assert!(buffer[tia::NTSC_SCANLINES..]
+        .iter()
+        .flatten()
+        .all(|&x| x == 0));
+}
+
+/// The frames don't fit a TV set up for 200 scanlines, so its buffer grows to hold them.
+#[test]
+fn buffer_fits_taller_frames() {
+    let cfg = tia::ntsc_tv_config();
+    let short = tia::TVConfig::new(
+        200,
+        cfg.pixels_per_scanline(),
+        cfg.visible_pixels(),
+        *cfg.color_map(),
+    );
+    let (mut atari, tv) = new_atari(short);
+    let (mut ntsc, ntsc_tv) = new_atari(cfg);
+    for _ in 0..10 {
+        assert!(atari.run_frame());
+        assert!(ntsc.run_frame());
+    }
+
+    let buffer = tv.borrow().buffer();
+    assert!(buffer.len() > 200);
+    assert_eq!(buffer, ntsc_tv.borrow().buffer()[..buffer.len()]);
+}
+
+#[test]
+fn palette_follows_tv_standard() {
+    let (_, pal) = new_atari(tia::pal_tv_config());
+    let (_, secam) = new_atari(tia::secam_tv_config());
+    let (_, ntsc50) = new_atari(tia::ntsc50_tv_config());
+
+    assert_eq!(ntsc50.borrow().palette(), tia::ntsc_tv_config().color_map());
+    assert_ne!(pal.borrow().palette(), ntsc50.borrow().palette());
+    assert_ne!(secam.borrow().palette(), pal.borrow().palette());
+
+    secam.borrow_mut().set_black_and_white(true);
+    assert_eq!(secam.borrow().palette()[0x07], 0xFFFFFFFF);
+}
+
+/// The NTSC build draws an 88 double scanline kernel, so its frames are 3 + 37 + 176 + 30 scanlines.
+#[test]
+fn ntsc_program_is_detected_on_pal_tv() {
+    let (mut atari, tv) = new_atari(tia::pal_tv_config());
+    atari.enable_tv_detection();
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Pal));
+    assert_eq!(atari.cpu_hz(), tia::PAL_CPU_HZ);
+    assert_eq!(
+        atari.audio_sample_rate(),
+        TVStandard::Pal.audio_sample_rate()
+    );
+
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+
+    let detector = atari.tv_detector().unwrap();
+    assert_eq!(detector.standard(), Some(TVStandard::Ntsc));
+    assert_eq!(detector.scanlines(), [246; 8]);
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Ntsc));
+    assert_eq!(tv.borrow().palette(), tia::ntsc_tv_config().color_map());
+    assert_eq!(tv.borrow().buffer().len(), tia::NTSC_SCANLINES);
+    assert_eq!(atari.cpu_hz(), tia::NTSC_CPU_HZ);
+    assert_eq!(atari.audio_sample_rate(), tia::AUDIO_SAMPLE_RATE);
+}
+
+#[test]
+fn host_overrides_detection() {
+    let (mut atari, _) = new_atari(tia::ntsc_tv_config());
+    atari.enable_tv_detection();
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Ntsc));
+
+    atari.set_tv_standard(TVStandard::Pal60);
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+
+    assert!(atari.tv_detector().is_none());
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Pal60));
+}
+
+#[test]
+fn override_before_decision_sticks() {
+    let (mut atari, _) = new_atari(tia::ntsc_tv_config());
+    atari.enable_tv_detection();
+    (0..3).for_each(|_| assert!(atari.run_frame()));
+
+    atari.set_tv_standard(TVStandard::Ntsc50);
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Ntsc50));
+}
+
+/// The state taken before the switch brings back the PAL TV, detection then decides again.
+#[test]
+fn load_state_from_before_decision_detects_again() {
+    let (mut atari, tv) = new_atari(tia::pal_tv_config());
+    atari.enable_tv_detection();
+    (0..2).for_each(|_| assert!(atari.run_frame()));
+    let state = atari.save_state();
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Ntsc));
+
+    atari.load_state(&state).unwrap();
+
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Pal));
+    assert_eq!(atari.cpu_hz(), tia::PAL_CPU_HZ);
+    assert_eq!(tv.borrow().buffer().len(), tia::PAL_SCANLINES);
+    assert_eq!(atari.tv_detector().unwrap().standard(), None);
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Ntsc));
+    assert_eq!(atari.cpu_hz(), tia::NTSC_CPU_HZ);
+}
+
+#[test]
+fn load_state_restarts_detection() {
+    let (mut atari, _) = new_atari(tia::pal_tv_config());
+    (0..5).for_each(|_| assert!(atari.run_frame()));
+    let state = atari.save_state();
+    atari.enable_tv_detection();
+    (0..5).for_each(|_| assert!(atari.run_frame()));
+
+    atari.load_state(&state).unwrap();
+
+    let detector = atari.tv_detector().unwrap();
+    assert!(detector.scanlines().is_empty());
+    assert_eq!(detector.standard(), None);
+    (0..12).for_each(|_| assert!(atari.run_frame()));
+    assert_eq!(atari.tv_standard(), Some(TVStandard::Ntsc));
+}
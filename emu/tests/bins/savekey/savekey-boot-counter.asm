@@ -0,0 +1,159 @@
+;===============================================================================
+; SaveKey boot counter
+;
+; Reads the byte at EEPROM $0040, writes it back incremented and shows it as
+; the background color. Then draws blank frames forever. An erased EEPROM
+; holds $FF, so the first boot counts 0.
+;
+; The SaveKey goes in the right port, SDA on pin 3 (SWCHA D2) and SCL on
+; pin 4 (SWCHA D3). SCL is an output driven through SWCHA. The SDA output
+; latch stays low, SDA is pulled low by making it an output in SWACNT and
+; released by making it an input.
+;
+; Assemble with: dasm savekey-boot-counter.asm -f3 -osavekey-boot-counter.bin
+;===============================================================================
+
+        processor 6502
+
+VSYNC   = $00
+WSYNC   = $02
+COLUBK  = $09
+SWCHA   = $0280
+SWACNT  = $0281
+
+SDA     = %00000100
+SCL     = %00001000
+CONTROL = %10100000
+ADDRESS = $40
+
+Count   = $80
+Shift   = $81
+
+        org $F800
+
+Reset
+        sei
+        cld
+        ldx #$FF
+        txs
+        lda #SCL
+        sta SWCHA
+        sta SWACNT
+
+        ; Random read of ADDRESS.
+        jsr Start
+        lda #CONTROL
+        jsr WriteByte
+        lda #0
+        jsr WriteByte
+        lda #ADDRESS
+        jsr WriteByte
+        jsr Start
+        lda #CONTROL|1
+        jsr WriteByte
+        jsr ReadByte
+        sta Count
+        jsr Stop
+        inc Count
+
+        ; Byte write of the new count.
+        jsr Start
+        lda #CONTROL
+        jsr WriteByte
+        lda #0
+        jsr WriteByte
+        lda #ADDRESS
+        jsr WriteByte
+        lda Count
+        jsr WriteByte
+        jsr Stop
+
+        lda Count
+        sta COLUBK
+
+Frame
+        lda #2
+        sta VSYNC
+        sta WSYNC
+        sta WSYNC
+        sta WSYNC
+        lda #0
+        sta VSYNC
+        ldx #130
+Lines
+        sta WSYNC
+        sta WSYNC
+        dex
+        bne Lines
+        jmp Frame
+
+; SDA falls while SCL is high. Leaves SCL low.
+Start
+        lda #SCL
+        sta SWACNT
+        sta SWCHA
+        lda #SCL|SDA
+        sta SWACNT
+        lda #0
+        sta SWCHA
+        rts
+
+; SDA rises while SCL is high.
+Stop
+        lda #SCL|SDA
+        sta SWACNT
+        lda #SCL
+        sta SWCHA
+        sta SWACNT
+        rts
+
+; Sends A, most significant bit first, then clocks the acknowledge.
+WriteByte
+        sta Shift
+        ldy #8
+WriteBit
+        asl Shift
+        lda #SCL
+        bcs WriteOne
+        ora #SDA
+WriteOne
+        sta SWACNT
+        lda #SCL
+        sta SWCHA
+        lda #0
+        sta SWCHA
+        dey
+        bne WriteBit
+        lda #SCL
+        sta SWACNT
+        sta SWCHA
+        lda #0
+        sta SWCHA
+        rts
+
+; Receives a byte into A, then NAKs it, i.e. it is the last one read.
+ReadByte
+        lda #SCL
+        sta SWACNT
+        ldy #8
+ReadBit
+        lda #SCL
+        sta SWCHA
+        lda SWCHA
+        and #SDA
+        cmp #SDA
+        rol Shift
+        lda #0
+        sta SWCHA
+        dey
+        bne ReadBit
+        lda #SCL
+        sta SWCHA
+        lda #0
+        sta SWCHA
+        lda Shift
+        rts
+
+        org $FFFC
+        .word Reset
+        .word Reset
@@ -5,9 +5,7 @@ pub fn setup_logger() {
     log::set_max_level(log::LevelFilter::Debug);
 }
 
-pub fn serialize_tv_buffer<const SCANLINES: usize, const PIXELS_PER_SCANLINE: usize>(
-    buffer: &[[u8; PIXELS_PER_SCANLINE]; SCANLINES],
-) -> Vec<String> {
+pub fn serialize_tv_buffer(buffer: &[Vec<u8>]) -> Vec<String> {
     buffer
         .iter()
         .enumerate()
@@ -31,3 +29,20 @@ pub fn read_rom(relative_path: &str) -> Vec<u8> {
 
     fs::read(bin_path).unwrap()
 }
+
+/// One line per frame that made a sound: for each channel the loudest level and how often the
+/// level changed, i.e. roughly the pitch.
+pub fn serialize_audio_frames(frames: &[Vec<[u8; 2]>]) -> Vec<String> {
+    frames
+        .iter()
+        .enumerate()
+        .filter(|(_, audio)| audio.iter().flatten().any(|&x| x != 0))
+        .map(|(frame, audio)| {
+            (0..2).fold(format!("{frame:03} =>"), |acc, ch| {
+                let peak = audio.iter().map(|x| x[ch]).max().unwrap_or(0);
+                let edges = audio.windows(2).filter(|x| x[0][ch] != x[1][ch]).count();
+                acc + &format!(" │ ch{ch} {peak:X} x {edges:3}")
+            })
+        })
+        .collect()
+}
@@ -1,13 +1,25 @@
 pub mod common;
-use rustella::{cmn, cmn::RefExtensions, cpu::*, riot};
+use rustella::{
+    cmn,
+    cmn::RefExtensions,
+    cpu::*,
+    power_on::{PowerOnFill, DEFAULT_PATTERN},
+    riot,
+};
 
 /// Test suite from https://codegolf.stackexchange.com/q/12844.
 #[test]
 fn hcm_6502_allsuitea_tests_main() {
     common::setup_logger();
     let buffer = common::read_rom("hcm_6502_AllSuiteA.bin");
-    let mut mem =
-        riot::Memory::new_with_rom(&buffer, 0x4000.into(), riot::mm_6502, None, None, true);
+    let mut mem = riot::Memory::new_with_rom(
+        &buffer,
+        0x4000.into(),
+        riot::mm_6502,
+        None,
+        None,
+        PowerOnFill::Pattern(DEFAULT_PATTERN),
+    );
     let rdy = cmn::LineState::High.rc_cell();
     let mut cpu = NMOS6502::new(rdy.clone(), &mem);
 
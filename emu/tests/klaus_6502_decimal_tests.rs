@@ -0,0 +1,37 @@
+pub mod common;
+use rustella::{
+    cmn,
+    cmn::RefExtensions,
+    cpu,
+    power_on::{PowerOnFill, DEFAULT_PATTERN},
+    riot,
+};
+
+/// Decimal mode test from https://github.com/Klaus2m5/6502_65C02_functional_tests, by Bruce Clark,
+/// built for the NMOS 6502 with the A, N, V, Z & C checks all on.
+#[test]
+fn klaus_6502_decimal_tests_main() {
+    common::setup_logger();
+    let buffer = common::read_rom("klaus_6502_decimal_test.bin");
+    let mut mem = riot::Memory::new_with_rom(
+        &buffer,
+        0x0000.into(),
+        riot::mm_6502,
+        None,
+        None,
+        PowerOnFill::Pattern(DEFAULT_PATTERN),
+    );
+    let rdy = cmn::LineState::High.rc_cell();
+    let mut cpu = cpu::NMOS6502::new(rdy.clone(), &mem);
+    cpu.set_pc(cmn::LoHi(0x00, 0x02));
+
+    // NOTE: DONE, where the test ends with or without errors.
+    while cpu.pc() != cmn::LoHi(0x4B, 0x02) && cpu.cycles() < 100_000_000 {
+        cpu.tick(&mut mem);
+    }
+    assert_eq!(cpu.pc(), cmn::LoHi(0x4B, 0x02), "CPU: {cpu:?}");
+    cpu.tick(&mut mem); // NOTE: Lets the store to ERROR before DONE land.
+
+    assert_eq!(mem.get(cmn::LoHi(0x0B, 0x00), 0), 0x00, "CPU: {cpu:?}"); // NOTE: ERROR.
+    assert_eq!(cpu.y(), 0xFF, "CPU: {cpu:?}");
+}
@@ -1,15 +1,25 @@
 pub mod common;
-use rustella::{cmn, cmn::RefExtensions, cpu, riot};
-
-/* NOTE: This is not complete yet. ADC/SBC bin & dec part of the tests are not done. */
+use rustella::{
+    cmn,
+    cmn::RefExtensions,
+    cpu,
+    power_on::{PowerOnFill, DEFAULT_PATTERN},
+    riot,
+};
 
 /// Test suite from https://github.com/Klaus2m5/6502_65C02_functional_tests.
 #[test]
 fn klaus_6502_65c02_functional_tests_main() {
     common::setup_logger();
     let buffer = common::read_rom("klaus_6502_functional_test.bin");
-    let mut mem =
-        riot::Memory::new_with_rom(&buffer, 0x0000.into(), riot::mm_6502, None, None, true);
+    let mut mem = riot::Memory::new_with_rom(
+        &buffer,
+        0x0000.into(),
+        riot::mm_6502,
+        None,
+        None,
+        PowerOnFill::Pattern(DEFAULT_PATTERN),
+    );
     let rdy = cmn::LineState::High.rc_cell();
     let mut cpu = cpu::NMOS6502::new(rdy.clone(), &mem);
     cpu.set_pc(cmn::LoHi(0x00, 0x04));
@@ -30,3 +40,35 @@ fn klaus_6502_65c02_functional_tests_main() {
     assert_eq!(cpu.psr(), 0x49);
     assert_eq!(cpu.s(), 0xFF);
 }
+
+/// Same suite as above, run through the ADC/SBC bin & dec part to its success trap.
+#[test]
+fn klaus_6502_65c02_functional_tests_to_success() {
+    common::setup_logger();
+    let buffer = common::read_rom("klaus_6502_functional_test.bin");
+    let mut mem = riot::Memory::new_with_rom(
+        &buffer,
+        0x0000.into(),
+        riot::mm_6502,
+        None,
+        None,
+        PowerOnFill::Pattern(DEFAULT_PATTERN),
+    );
+    let rdy = cmn::LineState::High.rc_cell();
+    let mut cpu = cpu::NMOS6502::new(rdy.clone(), &mem);
+    cpu.set_pc(cmn::LoHi(0x00, 0x04));
+
+    // NOTE: The success trap, a failed test traps before it.
+    while cpu.pc() != cmn::LoHi(0x69, 0x34) && cpu.cycles() < 100_000_000 {
+        cpu.tick(&mut mem);
+    }
+
+    assert_eq!(mem.get(cmn::LoHi(0x00, 0x02), 0), 0x2B, "CPU: {cpu:?}"); // NOTE: This indicates the number of tests ran.
+    assert_eq!(cpu.cycles(), 96241363);
+    assert_eq!(cpu.pc(), cmn::LoHi(0x69, 0x34));
+    assert_eq!(cpu.a(), 0xF0);
+    assert_eq!(cpu.x(), 0x0E);
+    assert_eq!(cpu.y(), 0xFF);
+    assert_eq!(cpu.psr(), 0xC1);
+    assert_eq!(cpu.s(), 0xFF);
+}
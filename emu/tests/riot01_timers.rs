@@ -1,23 +1,70 @@
 pub mod common;
 use insta::*;
-use rustella::{cmn, cmn::RefExtensions, tia, tia::TV, NtscAtari};
+use rustella::{cmn, cmn::RefExtensions, tia, tia::TV, Atari};
 
 /// Test suite from https://forums.atariage.com/blogs/entry/11112-step-2-timers/
 #[test]
 fn spiceware_collect_2_timers() {
     common::setup_logger();
 
-    let tv = tia::InMemoryTV::<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>::new(
-        tia::ntsc_tv_config(),
-    )
-    .rc_refcell();
-    let mut atari = NtscAtari::new(tv.clone());
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
     atari.load_rom(0xF800u16, &common::read_rom("collect/collect-02-Timer.bin"));
 
     atari.run_for(54143);
 
     assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
-    assert_eq!(tv.borrow().frame_counter(), 62);
-    assert_eq!(atari.cpu_state().cycles(), 152355);
-    assert_eq!(atari.cpu_state().pc(), cmn::LoHi(0x4F, 0xF8));
+    assert_eq!(tv.borrow().frame_counter(), 54);
+    assert_eq!(atari.cpu_state().cycles(), 155989);
+    assert_eq!(atari.cpu_state().pc(), cmn::LoHi(0x40, 0xF8));
+}
+
+/// The CPU runs on every third color clock, however the clocks are split across calls to tick.
+#[test]
+fn clock_phase_survives_short_ticks() {
+    common::setup_logger();
+    let rom = common::read_rom("collect/collect-02-Timer.bin");
+    let new_atari = || {
+        let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+        let mut atari = Atari::new(tv);
+        atari.load_rom(0xF800u16, &rom);
+        atari
+    };
+
+    let mut atari = new_atari();
+    atari.tick(100_000);
+    let mut atari2 = new_atari();
+    let mut clocks = 0;
+    for x in [1, 2, 4, 5, 7].into_iter().cycle() {
+        if clocks + x > 100_000 {
+            break;
+        }
+        atari2.tick(x);
+        clocks += x;
+    }
+    atari2.tick(100_000 - clocks);
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
+
+/// NOTE: VSYNC, VBLANK & overscan are timed with TIM64T, so any drift in the timer shows up here.
+#[test]
+fn spiceware_collect_2_frames_are_262_scanlines() {
+    common::setup_logger();
+
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF800u16, &common::read_rom("collect/collect-02-Timer.bin"));
+    (0..3).for_each(|_| assert!(atari.run_frame()));
+
+    for _ in 0..10 {
+        let frame = atari.frame_counter();
+        let mut clocks = 0;
+        while atari.frame_counter() == frame {
+            atari.tick(1);
+            clocks += 1;
+        }
+
+        assert_eq!(clocks, tia::NTSC_SCANLINES * tia::NTSC_PIXELS_PER_SCANLINE);
+    }
 }
@@ -0,0 +1,76 @@
+pub mod common;
+use insta::*;
+use rustella::{
+    cmn::RefExtensions,
+    ctrl::{ControllerInput, JoystickState, Port},
+    movie::{InputEvent, Movie, MoviePlayer, MovieRecorder},
+    tia, Atari,
+};
+use std::{cell::RefCell, rc::Rc};
+
+fn new_atari() -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(
+        0xF800u16,
+        &common::read_rom("collect/collect-04-2LineKernel.bin"),
+    );
+    (atari, tv)
+}
+
+fn joystick(s: &str) -> ControllerInput {
+    ControllerInput::Joystick(s.parse::<JoystickState>().unwrap())
+}
+
+/// Test suite from https://forums.atariage.com/blogs/entry/11118-step-4-2-line-kernel/
+#[test]
+fn spiceware_collect_4_joysticks_move_players() {
+    common::setup_logger();
+    let (mut still, still_tv) = new_atari();
+    let (mut atari, tv) = new_atari();
+
+    (0..10).for_each(|_| {
+        still.run_frame();
+        atari.run_frame();
+    });
+    atari.set_input(Port::Left, joystick("right+down"));
+    atari.set_input(Port::Right, joystick("left+up+fire"));
+    (0..30).for_each(|_| {
+        still.run_frame();
+        atari.run_frame();
+    });
+
+    assert_ne!(tv.borrow().buffer(), still_tv.borrow().buffer());
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+}
+
+#[test]
+fn joystick_movie_playback() {
+    common::setup_logger();
+    let (mut atari, _) = new_atari();
+    let mut recorder = MovieRecorder::power_on(&atari);
+    for (frame, input) in [(5, "right"), (20, "down+fire"), (30, "-"), (35, "left")] {
+        while recorder.frame() < frame {
+            recorder.run_frame(&mut atari);
+        }
+        recorder.input(
+            &mut atari,
+            InputEvent::Controller(Port::Left, joystick(input)),
+        );
+    }
+    recorder.run_frame(&mut atari);
+    let text = recorder.finish().to_text();
+    assert!(
+        text.contains("input 20 left joystick down+fire\n"),
+        "{text}"
+    );
+
+    let (mut atari2, _) = new_atari();
+    let movie = Movie::<InputEvent>::from_text(&text).unwrap();
+    MoviePlayer::new(movie, &mut atari2)
+        .unwrap()
+        .play(&mut atari2)
+        .unwrap();
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
@@ -0,0 +1,106 @@
+pub mod common;
+use insta::*;
+use rustella::{
+    cmn::RefExtensions,
+    movie::{InputEvent, Movie, MoviePlayer, MovieRecorder},
+    tia::{self, TV},
+    Atari,
+};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+fn new_atari(rom: &str) -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF800u16, &common::read_rom(rom));
+    (atari, tv)
+}
+
+fn colors(tv: &tia::InMemoryTV) -> BTreeSet<u8> {
+    tv.buffer().iter().flatten().copied().collect()
+}
+
+fn run_frames(atari: &mut Atari, frames: usize) {
+    (0..frames).for_each(|_| assert!(atari.run_frame()));
+}
+
+/// Holds a switch down for a few frames, like a player would.
+fn press(atari: &mut Atari, set: fn(&mut Atari, bool)) {
+    set(atari, true);
+    run_frames(atari, 5);
+    set(atari, false);
+}
+
+/// Test suite from https://forums.atariage.com/blogs/entry/11124-step-8-select-and-reset-support/
+#[test]
+fn spiceware_collect_8_reset_starts_game() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari("collect/collect-08-SelectAndResetSupport.bin");
+
+    run_frames(&mut atari, 10);
+    press(&mut atari, Atari::set_reset);
+    run_frames(&mut atari, 30);
+
+    // NOTE: Colors cycle only while the game is over.
+    assert_eq!(
+        colors(&tv.borrow()),
+        BTreeSet::from([0x00, 0x46, 0x64, 0x86, 0xC6])
+    );
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+}
+
+#[test]
+fn spiceware_collect_8_black_and_white() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari("collect/collect-08-SelectAndResetSupport.bin");
+    run_frames(&mut atari, 10);
+    assert!(colors(&tv.borrow()).iter().any(|x| x & 0xF0 != 0));
+    assert_eq!(tv.borrow().palette(), tv.borrow().config().color_map());
+
+    atari.set_color(false);
+    run_frames(&mut atari, 10);
+
+    assert!(colors(&tv.borrow()).iter().all(|x| x & 0xF0 == 0));
+    assert_eq!(tv.borrow().palette(), tv.borrow().config().bw_color_map());
+}
+
+/// Test suite from https://forums.atariage.com/blogs/entry/11130-step-9-game-variations/
+#[test]
+fn spiceware_collect_9_select_changes_variation() {
+    common::setup_logger();
+    let (mut unchanged, unchanged_tv) = new_atari("collect/collect-09-GameVariations.bin");
+    let (mut atari, tv) = new_atari("collect/collect-09-GameVariations.bin");
+
+    run_frames(&mut unchanged, 45);
+    run_frames(&mut atari, 10);
+    press(&mut atari, Atari::set_select);
+    run_frames(&mut atari, 30);
+
+    assert_ne!(tv.borrow().buffer(), unchanged_tv.borrow().buffer());
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+}
+
+#[test]
+fn switches_movie_playback() {
+    common::setup_logger();
+    let (mut atari, _) = new_atari("collect/collect-09-GameVariations.bin");
+    let mut recorder = MovieRecorder::power_on(&atari);
+    for (frame, switches) in [(5, "select"), (10, "-"), (20, "reset+bw"), (25, "bw")] {
+        while recorder.frame() < frame {
+            assert!(recorder.run_frame(&mut atari));
+        }
+        recorder.input(&mut atari, InputEvent::Switches(switches.parse().unwrap()));
+    }
+    (0..10).for_each(|_| assert!(recorder.run_frame(&mut atari)));
+    let movie = recorder.finish();
+
+    let (mut atari2, _) = new_atari("collect/collect-09-GameVariations.bin");
+    let movie2 = Movie::<InputEvent>::from_text(&movie.to_text()).unwrap();
+    assert_eq!(movie2, movie);
+    MoviePlayer::new(movie2, &mut atari2)
+        .unwrap()
+        .play(&mut atari2)
+        .unwrap();
+
+    assert_eq!(atari2.switches(), atari.switches());
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
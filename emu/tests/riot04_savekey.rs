@@ -0,0 +1,87 @@
+pub mod common;
+use rustella::{
+    cmn::RefExtensions,
+    ctrl::{EepromStorage, FileStorage, MemoryStorage, Port, SaveKey, EEPROM_SIZE},
+    tia, Atari,
+};
+
+const ROM: &str = "savekey/savekey-boot-counter.bin";
+/// Where the ROM keeps its count.
+const ADDRESS: usize = 0x0040;
+
+/// Powers on with a SaveKey over `storage` in the right port and runs a few frames.
+fn boot(storage: impl EepromStorage + 'static) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv);
+    atari.plug_device(Port::Right, Box::new(SaveKey::new(Box::new(storage))));
+    atari.load_rom(0xF800u16, &common::read_rom(ROM));
+
+    (0..3).for_each(|_| assert!(atari.run_frame()));
+}
+
+#[test]
+fn program_counts_boots_in_memory() {
+    common::setup_logger();
+    let storage = MemoryStorage::default();
+
+    boot(storage.clone());
+    assert_eq!(storage.data()[ADDRESS], 0x00);
+    boot(storage.clone());
+    boot(storage.clone());
+
+    let data = storage.data();
+    assert_eq!(data.len(), EEPROM_SIZE);
+    assert_eq!(data[ADDRESS], 0x02);
+    assert!(data
+        .iter()
+        .enumerate()
+        .all(|(i, &x)| i == ADDRESS || x == 0xFF));
+}
+
+#[test]
+fn program_counts_boots_in_file() {
+    common::setup_logger();
+    let path = std::env::temp_dir().join("rustella-savekey-boot-counter.bin");
+    let _ = std::fs::remove_file(&path);
+
+    boot(FileStorage::new(&path));
+    assert_eq!(std::fs::read(&path).unwrap()[ADDRESS], 0x00);
+    boot(FileStorage::new(&path));
+
+    assert_eq!(std::fs::read(&path).unwrap()[ADDRESS], 0x01);
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// The state is taken halfway through the program's write, after its read.
+#[test]
+fn state_restores_savekey_mid_write() {
+    common::setup_logger();
+    let new_atari = || Atari::new(tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell());
+    let storage = MemoryStorage::default();
+    let mut atari = new_atari();
+    atari.plug_device(
+        Port::Right,
+        Box::new(SaveKey::new(Box::new(storage.clone()))),
+    );
+    atari.load_rom(0xF800u16, &common::read_rom(ROM));
+    atari.run_for(700);
+    let saved = atari.save_state();
+    assert!(storage.data().is_empty());
+    (0..3).for_each(|_| assert!(atari.run_frame()));
+
+    let mut atari2 = new_atari();
+    atari2.load_state(&saved).unwrap();
+    let storage3 = MemoryStorage::default();
+    let mut atari3 = new_atari();
+    atari3.plug_device(
+        Port::Right,
+        Box::new(SaveKey::new(Box::new(storage3.clone()))),
+    );
+    atari3.load_state(&saved).unwrap();
+    (0..3).for_each(|_| assert!(atari2.run_frame() && atari3.run_frame()));
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+    assert_eq!(atari3.save_state(), atari.save_state());
+    assert_eq!(storage3.data(), storage.data());
+    assert_eq!(storage3.data()[ADDRESS], 0x00);
+}
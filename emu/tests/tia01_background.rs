@@ -1,17 +1,14 @@
 pub mod common;
 use insta::*;
-use rustella::{cmn, cmn::RefExtensions, tia, tia::TV, NtscAtari};
+use rustella::{cmn, cmn::RefExtensions, tia, tia::TV, Atari};
 
 /// Test suite from https://forums.atariage.com/blogs/entry/11109-step-1-generate-a-stable-display/
 #[test]
 fn spiceware_collect_1_stable_display() {
     common::setup_logger();
 
-    let tv = tia::InMemoryTV::<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>::new(
-        tia::ntsc_tv_config(),
-    )
-    .rc_refcell();
-    let mut atari = NtscAtari::new(tv.clone());
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
     atari.load_rom(
         0xF800u16,
         &common::read_rom("collect/collect-01-StableDisplay.bin"),
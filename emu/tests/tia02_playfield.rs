@@ -1,17 +1,14 @@
 pub mod common;
 use insta::*;
-use rustella::{cmn, cmn::RefExtensions, tia, tia::TV, NtscAtari};
+use rustella::{cmn, cmn::RefExtensions, tia, tia::TV, Atari};
 
 /// Test suite from https://forums.atariage.com/blogs/entry/11118-step-3-score-timer-display/
 #[test]
 fn spiceware_collect_3_score_timer_display() {
     common::setup_logger();
 
-    let tv = tia::InMemoryTV::<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>::new(
-        tia::ntsc_tv_config(),
-    )
-    .rc_refcell();
-    let mut atari = NtscAtari::new(tv.clone());
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
     atari.load_rom(
         0xF800u16,
         &common::read_rom("collect/collect-03-ScoreAndTimerDisplay.bin"),
@@ -20,7 +17,29 @@ fn spiceware_collect_3_score_timer_display() {
     atari.run_for(65895);
 
     assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
-    assert_eq!(tv.borrow().frame_counter(), 62);
-    assert_eq!(atari.cpu_state().cycles(), 194162);
-    assert_eq!(atari.cpu_state().pc(), cmn::LoHi(0x9D, 0xF8));
+    assert_eq!(tv.borrow().frame_counter(), 55);
+    assert_eq!(atari.cpu_state().cycles(), 196714);
+    assert_eq!(atari.cpu_state().pc(), cmn::LoHi(0xB0, 0xF8));
+}
+
+/// Asymmetric playfield kernel, rewriting PF0-PF2 mid scanline for the right half. Writes that
+/// land inside a playfield pixel must not leave stray pixels.
+#[test]
+fn asymmetric_playfield() {
+    common::setup_logger();
+
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF000u16, &common::read_rom("asymmetric.bin"));
+
+    (0..3).for_each(|_| assert!(atari.run_frame()));
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 3);
+    let hblank = tia::ntsc_tv_config().hblank_pixels();
+    tv.borrow().buffer().iter().for_each(|line| {
+        line[hblank..]
+            .chunks(4)
+            .for_each(|pf_pixel| assert!(pf_pixel.iter().all(|&x| x == pf_pixel[0])))
+    });
 }
@@ -1,16 +1,13 @@
 pub mod common;
 use insta::*;
-use rustella::{cmn::RefExtensions, tia, tia::TV, NtscAtari};
+use rustella::{cmn::RefExtensions, tia, tia::TV, Atari};
 
 /// Test suite from https://www.youtube.com/watch?v=GObPgosXPPs&list=PLbPt2qKXQzJ8-P3Qe9lDPtxwFSdbDbcvW&index=5
 #[test]
 fn single_static_player() {
     common::setup_logger();
-    let tv = tia::InMemoryTV::<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>::new(
-        tia::ntsc_tv_config(),
-    )
-    .rc_refcell();
-    let mut atari = NtscAtari::new(tv.clone());
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
     atari.load_rom(
         0xF000u16,
         &common::read_rom("8blit/8blit-s01e06-Ex1-First-Sprite.bin"),
@@ -27,11 +24,8 @@ fn single_static_player() {
 #[test]
 fn dual_player_fine_move() {
     common::setup_logger();
-    let tv = tia::InMemoryTV::<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>::new(
-        tia::ntsc_tv_config(),
-    )
-    .rc_refcell();
-    let mut atari = NtscAtari::new(tv.clone());
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
     atari.load_rom(
         0xF000u16,
         &common::read_rom("8blit/8blit-s01e06-Ex4-Two Dimensional Sprite.bin"),
@@ -44,3 +38,22 @@ fn dual_player_fine_move() {
     assert_eq!(atari.cpu_state().cycles(), 401412);
     assert_eq!(atari.cpu_state().pc(), 0xF046.into());
 }
+
+/// Test suite from https://www.youtube.com/watch?v=lUJmDX1fd18
+///
+/// NOTE: Quad width players, each pixel lasting 4 colour clocks after a 1 pixel start delay.
+#[test]
+fn quad_width_players() {
+    common::setup_logger();
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(
+        0xF000u16,
+        &common::read_rom("8blit/8blit-s04e01-Purrballs.bin"),
+    );
+
+    (0..10).for_each(|_| assert!(atari.run_frame()));
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 10);
+}
@@ -0,0 +1,86 @@
+pub mod common;
+use insta::*;
+use rustella::{
+    cmn::RefExtensions,
+    ctrl::{ControllerInput, PaddlesState, Port},
+    movie::{InputEvent, Movie, MoviePlayer, MovieRecorder},
+    tia, Atari,
+};
+use std::{cell::RefCell, rc::Rc};
+
+fn new_atari(rom: &str) -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF000u16, &common::read_rom(rom));
+    (atari, tv)
+}
+
+fn paddles(s: &str) -> ControllerInput {
+    ControllerInput::Paddles(s.parse::<PaddlesState>().unwrap())
+}
+
+fn run_frames(atari: &mut Atari, frames: usize) {
+    (0..frames).for_each(|_| assert!(atari.run_frame()));
+}
+
+/// Test suite from https://youtu.be/CuFHfAzsTw0
+///
+/// NOTE: Shows, in hex, the last scanline each pot was still charging on. 0.1 & 0.25 read 0x25 & 0x5D,
+/// INPT1 being read later on the scanline than INPT0. Position 0.0 is charged before the first read and keeps the initial 0x00, 1.0 saturates at 0xB3.
+#[test]
+fn eightbit_paddle_values() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari("8blit/8blit-s04e02-PaddleValues.bin");
+
+    atari.set_input(Port::Left, paddles("0.1,0.25 fire-a"));
+    atari.set_input(Port::Right, paddles("0,1 fire-b"));
+    run_frames(&mut atari, 10);
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+}
+
+/// Test suite from https://youtu.be/CuFHfAzsTw0
+#[test]
+fn eightbit_purrballs_paddle_moves_player() {
+    common::setup_logger();
+    let (mut left, left_tv) = new_atari("8blit/8blit-s04e02-Paddle-Controllers-Purrballs.bin");
+    let (mut right, right_tv) = new_atari("8blit/8blit-s04e02-Paddle-Controllers-Purrballs.bin");
+
+    left.set_input(Port::Left, paddles("0.1,0.1 -"));
+    right.set_input(Port::Left, paddles("0.4,0.4 -"));
+    run_frames(&mut left, 30);
+    run_frames(&mut right, 30);
+
+    assert_ne!(left_tv.borrow().buffer(), right_tv.borrow().buffer());
+}
+
+#[test]
+fn paddles_movie_playback() {
+    common::setup_logger();
+    let (mut atari, _) = new_atari("8blit/8blit-s04e02-PaddleValues.bin");
+    let mut recorder = MovieRecorder::power_on(&atari);
+    for (frame, input) in [(2, "0.5,0.5 -"), (5, "0.75,0.2 fire-a"), (8, "0,1 -")] {
+        while recorder.frame() < frame {
+            recorder.run_frame(&mut atari);
+        }
+        recorder.input(
+            &mut atari,
+            InputEvent::Controller(Port::Right, paddles(input)),
+        );
+    }
+    recorder.run_frame(&mut atari);
+    let text = recorder.finish().to_text();
+    assert!(
+        text.contains("input 5 right paddles 0.75,0.2 fire-a\n"),
+        "{text}"
+    );
+
+    let (mut atari2, _) = new_atari("8blit/8blit-s04e02-PaddleValues.bin");
+    let movie = Movie::<InputEvent>::from_text(&text).unwrap();
+    MoviePlayer::new(movie, &mut atari2)
+        .unwrap()
+        .play(&mut atari2)
+        .unwrap();
+
+    assert_eq!(atari2.save_state(), atari.save_state());
+}
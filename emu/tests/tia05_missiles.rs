@@ -0,0 +1,41 @@
+pub mod common;
+use insta::*;
+use rustella::{cmn::RefExtensions, tia, tia::TV, Atari};
+use std::{cell::RefCell, rc::Rc};
+
+fn new_atari(rom: &str) -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(0xF000u16, &common::read_rom(rom));
+    (atari, tv)
+}
+
+fn run_frames(atari: &mut Atari, frames: usize) {
+    (0..frames).for_each(|_| assert!(atari.run_frame()));
+}
+
+/// Test suite from https://youtu.be/T-6WY-JdjFo
+#[test]
+fn eightbit_missiles_with_color() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari("8blit/8blit-s01e07-Ex6-Missiles with Color.bin");
+
+    run_frames(&mut atari, 10);
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 10);
+}
+
+/// Test suite from https://youtu.be/T-6WY-JdjFo
+///
+/// NOTE: Missiles are moved every frame and resized every few, so the snapshot pins the frame.
+#[test]
+fn eightbit_missiles_movement_and_sizes() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari("8blit/8blit-s01e07-Ex7-Missiles Movemen and Sizes.bin");
+
+    run_frames(&mut atari, 60);
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 60);
+}
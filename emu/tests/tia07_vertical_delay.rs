@@ -0,0 +1,44 @@
+pub mod common;
+use insta::*;
+use rustella::{cmn::RefExtensions, tia, tia::TV, Atari};
+use std::{cell::RefCell, rc::Rc};
+
+fn new_atari(org: u16, rom: &str) -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(org, &common::read_rom(rom));
+    (atari, tv)
+}
+
+fn run_frames(atari: &mut Atari, frames: usize) {
+    (0..frames).for_each(|_| assert!(atari.run_frame()));
+}
+
+/// Test suite from https://youtu.be/T-6WY-JdjFo
+///
+/// NOTE: GRP0 is written a scanline ahead and only shown on the GRP1 write, lining the cat up with the dog.
+#[test]
+fn eightbit_cat_and_dog_vertical_delay() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari(
+        0xF000,
+        "8blit/8blit-s01e07-Ex5-Cat and Dog Vertical Delay.bin",
+    );
+
+    run_frames(&mut atari, 10);
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 10);
+}
+
+/// Test suite from the Collect tutorial series on AtariAge, step 5 - automate vertical delay.
+#[test]
+fn spiceware_collect_5_automate_vertical_delay() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari(0xF800, "collect/collect-05-AutomateVerticalDelay.bin");
+
+    run_frames(&mut atari, 10);
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 10);
+}
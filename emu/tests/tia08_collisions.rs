@@ -0,0 +1,69 @@
+pub mod common;
+use insta::*;
+use rustella::{
+    cmn::RefExtensions,
+    ctrl::{ControllerInput, JoystickState, Port},
+    tia::{self, TV},
+    Atari,
+};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+fn new_atari(org: u16, rom: &str) -> (Atari, Rc<RefCell<tia::InMemoryTV>>) {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new(tv.clone());
+    atari.load_rom(org, &common::read_rom(rom));
+    (atari, tv)
+}
+
+fn run_frames(atari: &mut Atari, frames: usize) {
+    (0..frames).for_each(|_| assert!(atari.run_frame()));
+}
+
+/// Most common non black color on the screen.
+fn background(tv: &tia::InMemoryTV) -> u8 {
+    let mut counts = BTreeMap::new();
+    tv.buffer()
+        .iter()
+        .flatten()
+        .filter(|&&x| x != 0x00)
+        .for_each(|&x| *counts.entry(x).or_insert(0) += 1);
+    counts.into_iter().max_by_key(|&(_, n)| n).unwrap().0
+}
+
+/// Test suite from https://youtu.be/fi5n4tPL1PI
+///
+/// NOTE: The ball only changes bearing on CXBLPF, without it the ball leaves the playfield box.
+#[test]
+fn eightbit_collision_detection_bouncing_ball() {
+    common::setup_logger();
+    let (mut atari, tv) = new_atari(
+        0xF000,
+        "8blit/8blit-s02e05-Ex1-Collision Detection-Bouncing Ball.bin",
+    );
+
+    run_frames(&mut atari, 300);
+
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+    assert_eq!(tv.borrow().frame_counter(), 300);
+}
+
+/// Test suite from the Collect tutorial series on AtariAge, step 15 - collect mini.
+///
+/// NOTE: Touching the box latches CXPPMM, which moves the box and changes the background color.
+#[test]
+fn spiceware_collect_15_player_collects_box() {
+    common::setup_logger();
+    let (mut still, still_tv) = new_atari(0xF800, "collect/collect-15-CollectMini.bin");
+    let (mut atari, tv) = new_atari(0xF800, "collect/collect-15-CollectMini.bin");
+
+    atari.set_input(
+        Port::Left,
+        ControllerInput::Joystick("right".parse::<JoystickState>().unwrap()),
+    );
+    run_frames(&mut still, 90);
+    run_frames(&mut atari, 90);
+
+    assert_eq!(background(&still_tv.borrow()), 0x0C);
+    assert_eq!(background(&tv.borrow()), 0x1C);
+    assert_debug_snapshot!(common::serialize_tv_buffer(&tv.borrow().buffer()));
+}
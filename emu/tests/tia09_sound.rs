@@ -0,0 +1,89 @@
+pub mod common;
+use insta::*;
+use rustella::{
+    cmn::RefExtensions,
+    ctrl::{ControllerInput, JoystickState, Port},
+    power_on::PowerOnConfig,
+    tia, Atari,
+};
+
+fn new_atari(org: u16, rom: &str, power_on: PowerOnConfig) -> Atari {
+    let tv = tia::InMemoryTV::new(tia::ntsc_tv_config()).rc_refcell();
+    let mut atari = Atari::new_with_power_on(tv, power_on);
+    atari.load_rom(org, &common::read_rom(rom));
+    atari
+}
+
+/// TIA audio output of each frame.
+fn run_frames(atari: &mut Atari, frames: usize) -> Vec<Vec<[u8; 2]>> {
+    (0..frames)
+        .map(|_| {
+            assert!(atari.run_frame());
+            atari.drain_tia_audio()
+        })
+        .collect()
+}
+
+/// Test suite from https://youtu.be/NBE-rEzk4cs
+///
+/// NOTE: The ROMs seed their random numbers from INTIM, which needs to be running at power-on.
+/// A pure tone (AUDC 4, AUDF 4) plays for a frame every time the ball bounces.
+#[test]
+fn eightbit_sound_1_tone_1_channel() {
+    common::setup_logger();
+    let mut atari = new_atari(
+        0xF000,
+        "8blit/8blit-s03e02-Ex1-Sound 1 tone 1 channel.bin",
+        PowerOnConfig::random(2600),
+    );
+
+    let frames = run_frames(&mut atari, 200);
+
+    assert_debug_snapshot!(common::serialize_audio_frames(&frames));
+}
+
+/// Test suite from https://youtu.be/NBE-rEzk4cs
+///
+/// NOTE: Bounces play on channel 0, RESET plays a rumble on channel 1.
+#[test]
+fn eightbit_sound_multi_tone_2_channel() {
+    common::setup_logger();
+    let mut atari = new_atari(
+        0xF000,
+        "8blit/8blit-s03e02-Ex2-Sound multi tone 2 channel.bin",
+        PowerOnConfig::random(2600),
+    );
+
+    let mut frames = run_frames(&mut atari, 100);
+    atari.set_reset(true);
+    frames.extend(run_frames(&mut atari, 2));
+    atari.set_reset(false);
+    frames.extend(run_frames(&mut atari, 100));
+
+    assert_debug_snapshot!(common::serialize_audio_frames(&frames));
+}
+
+/// Test suite from the Collect tutorial series on AtariAge, step 13 - add sound effects.
+///
+/// NOTE: The game starts on RESET, walking into the playfield then plays the collide effect.
+#[test]
+fn spiceware_collect_13_add_sound_effects() {
+    common::setup_logger();
+    let mut atari = new_atari(
+        0xF800,
+        "collect/collect-13-AddSoundEffects.bin",
+        PowerOnConfig::default(),
+    );
+
+    run_frames(&mut atari, 10);
+    atari.set_reset(true);
+    run_frames(&mut atari, 2);
+    atari.set_reset(false);
+    atari.set_input(
+        Port::Left,
+        ControllerInput::Joystick("up".parse::<JoystickState>().unwrap()),
+    );
+    let frames = run_frames(&mut atari, 60);
+
+    assert_debug_snapshot!(common::serialize_audio_frames(&frames));
+}
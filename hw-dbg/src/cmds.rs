@@ -1,12 +1,24 @@
 use super::{color_term::VTerm, repl};
-use rustella::{bits, cmn::LoHi, cpu, riot};
+use rustella::{
+    bits,
+    cmn::LoHi,
+    cpu,
+    rewind::RewindBuffer,
+    riot,
+    state::{SaveState, StateReader, StateWriter},
+};
 use std::collections::HashSet;
 use std::path::PathBuf;
 
+/// There is no TV attached to the debugger, a frame is the CPU time of one NTSC frame whatever the
+/// program. Keep the `rewind` command help in step.
+const CPU_CYCLES_PER_FRAME: usize = 76 * 262;
+
 pub fn go(
     cpu: &mut cpu::NMOS6502,
     mem: &mut riot::Memory,
     break_points: &HashSet<LoHi>,
+    rewind: &mut RewindBuffer,
     count: u64,
 ) {
     let mut count = count;
@@ -18,6 +30,7 @@ pub fn go(
                 break;
             }
         }
+        capture_snapshot(cpu, mem, rewind);
         count -= 1;
 
         if count == 0 {
@@ -129,6 +142,60 @@ pub fn load(mem: &mut riot::Memory, start: u16, path: PathBuf) {
     mem.load(&bytes.unwrap(), start);
 }
 
+/// Only the CPU & memory make up the debugger's machine, unlike [`rustella::Atari::save_state`].
+pub fn capture_snapshot(cpu: &cpu::NMOS6502, mem: &riot::Memory, rewind: &mut RewindBuffer) {
+    let frame = frame(cpu);
+    if rewind.is_due(frame) {
+        let mut w = StateWriter::new();
+        w.section(b"CPU ", |w| cpu.save_state(w));
+        w.section(b"MEM ", |w| mem.save_state(w));
+        rewind.capture(frame, w.into_bytes());
+    }
+}
+
+pub fn rewind(
+    cpu: &mut cpu::NMOS6502,
+    mem: &mut riot::Memory,
+    bps: &HashSet<LoHi>,
+    rewind: &mut RewindBuffer,
+    frames: u64,
+) {
+    let (frame, state) = match rewind.rewind_to(frame(cpu).saturating_sub(frames)) {
+        Ok(Some(x)) => x,
+        Ok(None) => {
+            println!("Nothing to rewind to.");
+            return;
+        }
+        Err(e) => {
+            println!("Unable to rewind: {e:?}");
+            return;
+        }
+    };
+
+    let restored = StateReader::new(&state).and_then(|mut r| {
+        r.section(b"CPU ", |r| cpu.load_state(r))?;
+        r.section(b"MEM ", |r| mem.load_state(r))
+    });
+    if let Err(e) = restored {
+        println!("Unable to restore snapshot of frame {frame}: {e:?}");
+        return;
+    }
+
+    println!("Rewound to frame {frame}.");
+    registers(cpu, mem, bps);
+}
+
+pub fn rewind_budget(rewind: &mut RewindBuffer, kib: usize) {
+    rewind.set_budget_bytes(kib * 1024);
+    let (oldest, newest) = rewind.frame_range().unwrap_or_default();
+    println!(
+        "{} snapshots, frames {oldest}..={newest}, {} / {} bytes.",
+        rewind.len(),
+        rewind.memory_used(),
+        rewind.config().budget_bytes
+    );
+}
+
 pub fn change_break_points(break_points: &mut HashSet<LoHi>, op: repl::BreakPointOp, addr: u16) {
     let addr = LoHi::from(addr);
     match op {
@@ -197,6 +264,10 @@ fn bit_value(cpu: &cpu::NMOS6502, bit: cpu::PSR) -> String {
     }
 }
 
+fn frame(cpu: &cpu::NMOS6502) -> u64 {
+    (cpu.cycles() / CPU_CYCLES_PER_FRAME) as u64
+}
+
 fn clock_speed(cpu: &cpu::NMOS6502) -> f64 {
     if cpu.duration() != 0 {
         (cpu.cycles() as f64 * 1_000_000_000.0) / cpu.duration() as f64 / 1_000_000.0
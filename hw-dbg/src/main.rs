@@ -2,7 +2,7 @@ mod cmds;
 mod color_term;
 mod repl;
 
-use rustella::{cmn, cmn::RefExtensions, cpu, riot};
+use rustella::{cmn, cmn::RefExtensions, cpu, power_on, rewind, riot};
 use std::{collections::HashSet, fs, path::PathBuf};
 
 fn main() {
@@ -28,7 +28,7 @@ fn main() {
         riot::mm_6502,
         None,
         None,
-        false,
+        power_on::PowerOnFill::Zero,
     );
     //let buffer = fs::read("D:/bin/Stella-6.7.1/roms/air_raid.bin").unwrap();
     //let mut mem = mem::Memory::new_with_rom(&buffer, cmn::ROM_START_6507, mem::mm_6507, true);
@@ -36,12 +36,16 @@ fn main() {
     cpu.set_pc(cmn::LoHi(0x00, 0x04));
 
     let mut break_points = HashSet::new();
+    let mut rewind = rewind::RewindBuffer::new(rewind::RewindConfig::default());
+    cmds::capture_snapshot(&cpu, &mem, &mut rewind);
 
     cmds::registers(&cpu, &mem, &break_points);
     let cl = repl::cmd_line();
     cl.repl(|command| match command {
         repl::Commands::Quit => println!("Press Ctrl+C to exit."),
-        repl::Commands::Go { count } => cmds::go(&mut cpu, &mut mem, &break_points, count),
+        repl::Commands::Go { count } => {
+            cmds::go(&mut cpu, &mut mem, &break_points, &mut rewind, count)
+        }
         repl::Commands::Registers => cmds::registers(&cpu, &mem, &break_points),
         repl::Commands::SetRegisters { reg, val } => cmds::set_register(&mut cpu, reg, val),
         repl::Commands::Memory { start } => cmds::memory(&mem, start),
@@ -53,5 +57,9 @@ fn main() {
         repl::Commands::BreakPointChange { op, address } => {
             cmds::change_break_points(&mut break_points, op, address)
         }
+        repl::Commands::Rewind { frames } => {
+            cmds::rewind(&mut cpu, &mut mem, &break_points, &mut rewind, frames)
+        }
+        repl::Commands::RewindBudget { kib } => cmds::rewind_budget(&mut rewind, kib),
     });
 }
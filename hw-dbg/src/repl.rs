@@ -139,6 +139,31 @@ pub enum Commands {
             help = "Address of the break point.")]
         address: u16,
     },
+
+    #[command(
+        visible_aliases = [ "rw" ],
+        about = "Go back in time by number of frames. Restores the CPU & memory only.",
+        long_about = None)]
+    Rewind {
+        #[arg(
+            index = 1,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u64),
+            help = "Number of frames to go back. With no TV attached, a frame is 76 * 262 CPU cycles.")]
+        frames: u64,
+    },
+
+    #[command(
+        visible_aliases = [ "rwb" ],
+        about = "Set the memory budget of the rewind buffer.",
+        long_about = None)]
+    RewindBudget {
+        #[arg(
+            index = 1,
+            value_parser = clap::value_parser!(usize),
+            help = "Budget in KiB.")]
+        kib: usize,
+    },
 }
 
 pub fn cmd_line() -> ClapEditor<Commands> {
@@ -6,7 +6,9 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 thread_local! {
-    static ATARI: RefCell<NtscAtari> = panic!("initialized not called.");
+    static ATARI: RefCell<rustella::Atari> = panic!("initialized not called.");
+    static SAVE_KEY: RefCell<Option<ctrl::MemoryStorage>> = const { RefCell::new(None) };
+    static TV: RefCell<Option<Rc<RefCell<web_tv::WebTV>>>> = const { RefCell::new(None) };
 }
 
 macro_rules! console_log {
@@ -19,16 +21,6 @@ fn initialize() {
     console_log!("Initialized emu_wasm...");
 }
 
-#[allow(non_snake_case)]
-#[wasm_bindgen(js_name = ntscColorMap)]
-pub fn ntsc_color_map() -> js_sys::Uint32Array {
-    let cfg = tia::ntsc_tv_config();
-    let map = cfg.color_map();
-    let js_map = js_sys::Uint32Array::new_with_length(map.len() as u32);
-    js_map.copy_from(map);
-    js_map
-}
-
 #[derive(Default)]
 #[wasm_bindgen]
 pub struct Atari {}
@@ -38,10 +30,11 @@ pub struct Atari {}
 impl Atari {
     #[wasm_bindgen(constructor)]
     pub fn new(render_scanline_fn: JsValue) -> Self {
-        let tv = web_tv::NtscWebTV::new(render_scanline_fn);
-        let atari = NtscAtari::new(Rc::new(RefCell::new(tv)));
+        let tv = Rc::new(RefCell::new(web_tv::WebTV::new(render_scanline_fn)));
+        let atari = rustella::Atari::new(tv.clone());
 
         ATARI.set(atari);
+        TV.set(Some(tv));
         console_log!("Created new atari...");
 
         Self {}
@@ -56,6 +49,243 @@ impl Atari {
     pub fn tick(&self, loops: usize) {
         ATARI.with_borrow_mut(|a| a.tick(loops))
     }
+
+    #[wasm_bindgen(js_name = "enableRewind")]
+    pub fn enable_rewind(&self, interval_frames: u64, budget_bytes: usize) {
+        ATARI.with_borrow_mut(|a| {
+            a.enable_rewind(rewind::RewindConfig {
+                interval_frames,
+                budget_bytes,
+            })
+        });
+        console_log!("Rewind enabled: every {interval_frames} frames, {budget_bytes} bytes");
+    }
+
+    #[wasm_bindgen(js_name = "disableRewind")]
+    pub fn disable_rewind(&self) {
+        ATARI.with_borrow_mut(|a| a.disable_rewind())
+    }
+
+    #[wasm_bindgen(js_name = "setRewindBudget")]
+    pub fn set_rewind_budget(&self, budget_bytes: usize) {
+        ATARI.with_borrow_mut(|a| {
+            if let Some(r) = a.rewind_buffer() {
+                r.set_budget_bytes(budget_bytes)
+            }
+        })
+    }
+
+    /// Returns the frame rewound to, undefined if rewind is not enabled or failed.
+    pub fn rewind(&self, frames: u64) -> Option<u64> {
+        ATARI.with_borrow_mut(|a| {
+            a.rewind(frames)
+                .inspect_err(|e| console_log!("Unable to rewind: {e:?}"))
+                .ok()
+                .flatten()
+        })
+    }
+
+    /// `port` is 0 for left, 1 for right.
+    #[wasm_bindgen(js_name = "setJoystick")]
+    pub fn set_joystick(
+        &self,
+        port: u8,
+        up: bool,
+        down: bool,
+        left: bool,
+        right: bool,
+        fire: bool,
+    ) {
+        let state = ctrl::JoystickState {
+            up,
+            down,
+            left,
+            right,
+            fire,
+        };
+        ATARI
+            .with_borrow_mut(|a| a.set_input(to_port(port), ctrl::ControllerInput::Joystick(state)))
+    }
+
+    /// Knob positions range from 0.0 to 1.0. Plugs paddles into `port` if needed.
+    #[wasm_bindgen(js_name = "setPaddles")]
+    pub fn set_paddles(&self, port: u8, a: f32, b: f32, fire_a: bool, fire_b: bool) {
+        let state = ctrl::PaddlesState {
+            position: [a, b],
+            fire: [fire_a, fire_b],
+        };
+        ATARI.with_borrow_mut(|x| x.set_input(to_port(port), ctrl::ControllerInput::Paddles(state)))
+    }
+
+    /// Bit `n` of `pressed` for key `n`, keys row by row: 1 2 3, 4 5 6, 7 8 9, * 0 #.
+    #[wasm_bindgen(js_name = "setKeypad")]
+    pub fn set_keypad(&self, port: u8, pressed: u16) {
+        let state = ctrl::KeypadState {
+            pressed: std::array::from_fn(|x| (pressed >> x) & 1 == 1),
+        };
+        ATARI.with_borrow_mut(|a| a.set_input(to_port(port), ctrl::ControllerInput::Keypad(state)))
+    }
+
+    /// `rotation` counts steps clockwise, wrapping.
+    #[wasm_bindgen(js_name = "setDriving")]
+    pub fn set_driving(&self, port: u8, rotation: u8, fire: bool) {
+        let state = ctrl::DrivingState { rotation, fire };
+        ATARI.with_borrow_mut(|a| a.set_input(to_port(port), ctrl::ControllerInput::Driving(state)))
+    }
+
+    /// `fire` is button B.
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(js_name = "setGenesis")]
+    pub fn set_genesis(
+        &self,
+        port: u8,
+        up: bool,
+        down: bool,
+        left: bool,
+        right: bool,
+        fire: bool,
+        c: bool,
+    ) {
+        let stick = ctrl::JoystickState {
+            up,
+            down,
+            left,
+            right,
+            fire,
+        };
+        let state = ctrl::GenesisState { stick, c };
+        ATARI.with_borrow_mut(|a| a.set_input(to_port(port), ctrl::ControllerInput::Genesis(state)))
+    }
+
+    /// Plugs a SaveKey into `port`, starting from `data` saved by an earlier session if given.
+    #[wasm_bindgen(js_name = "plugSaveKey")]
+    pub fn plug_save_key(&self, port: u8, data: Option<Vec<u8>>) {
+        let storage = ctrl::MemoryStorage::new(data.unwrap_or_default());
+        let device = ctrl::SaveKey::new(Box::new(storage.clone()));
+        SAVE_KEY.set(Some(storage));
+        ATARI.with_borrow_mut(|a| a.plug_device(to_port(port), Box::new(device)))
+    }
+
+    /// Plugs an AtariVox into `port`, its SaveKey starting from `data` saved by an earlier session if given.
+    #[wasm_bindgen(js_name = "plugAtariVox")]
+    pub fn plug_atari_vox(&self, port: u8, data: Option<Vec<u8>>) {
+        let storage = ctrl::MemoryStorage::new(data.unwrap_or_default());
+        let device = ctrl::AtariVox::new(Box::new(storage.clone()));
+        SAVE_KEY.set(Some(storage));
+        ATARI.with_borrow_mut(|a| a.plug_device(to_port(port), Box::new(device)))
+    }
+
+    /// Console & device audio at `sample_rate` from now on, see `audio`. Replaces `deviceAudio`.
+    #[wasm_bindgen(js_name = "enableAudio")]
+    pub fn enable_audio(&self, sample_rate: u32) {
+        ATARI.with_borrow_mut(|a| a.enable_audio(audio::AudioConfig { sample_rate }));
+        console_log!("Audio enabled: {sample_rate} Hz");
+    }
+
+    /// Samples of the frames completed since the last call, back to back.
+    pub fn audio(&self) -> Vec<f32> {
+        ATARI.with_borrow_mut(|a| {
+            a.drain_audio_frames()
+                .into_iter()
+                .flat_map(|x| x.samples)
+                .collect()
+        })
+    }
+
+    /// Speech generated since the last call, at `deviceAudioSampleRate`.
+    #[wasm_bindgen(js_name = "deviceAudio")]
+    pub fn device_audio(&self) -> Vec<f32> {
+        ATARI.with_borrow_mut(|a| a.drain_device_audio())
+    }
+
+    /// In Hz, it changes with the TV standard.
+    #[wasm_bindgen(js_name = "deviceAudioSampleRate")]
+    pub fn device_audio_sample_rate(&self) -> u32 {
+        ATARI.with_borrow(|a| a.audio_sample_rate())
+    }
+
+    /// EEPROM contents as of the last completed write, undefined if nothing was written yet.
+    #[wasm_bindgen(js_name = "saveKeyData")]
+    pub fn save_key_data(&self) -> Option<Vec<u8>> {
+        SAVE_KEY.with_borrow(|x| x.as_ref().map(|x| x.data()).filter(|x| !x.is_empty()))
+    }
+
+    /// Difficulty switches are `true` for A.
+    #[wasm_bindgen(js_name = "setSwitches")]
+    pub fn set_switches(
+        &self,
+        reset: bool,
+        select: bool,
+        color: bool,
+        left_a: bool,
+        right_a: bool,
+    ) {
+        let difficulty = |a| {
+            if a {
+                ctrl::Difficulty::A
+            } else {
+                ctrl::Difficulty::B
+            }
+        };
+        let switches = ctrl::ConsoleSwitches {
+            reset,
+            select,
+            color,
+            left_difficulty: difficulty(left_a),
+            right_difficulty: difficulty(right_a),
+        };
+        ATARI.with_borrow_mut(|a| a.set_switches(switches))
+    }
+
+    #[wasm_bindgen(js_name = "frameCounter")]
+    pub fn frame_counter(&self) -> u64 {
+        ATARI.with_borrow(|a| a.frame_counter())
+    }
+
+    /// Switches the TV to the standard the frame timing matches, see `tvStandard`.
+    #[wasm_bindgen(js_name = "enableTvDetection")]
+    pub fn enable_tv_detection(&self) {
+        ATARI.with_borrow_mut(|a| a.enable_tv_detection())
+    }
+
+    /// One of ntsc, pal, secam, pal60 & ntsc50.
+    #[wasm_bindgen(js_name = "tvStandard")]
+    pub fn tv_standard(&self) -> Option<String> {
+        ATARI.with_borrow(|a| a.tv_standard().map(|x| standard_name(x).to_string()))
+    }
+
+    /// Overrides detection, e.g. for a program detected wrongly. See `tvStandard` for the names.
+    #[wasm_bindgen(js_name = "setTvStandard")]
+    pub fn set_tv_standard(&self, name: &str) {
+        match to_standard(name) {
+            Some(x) => ATARI.with_borrow_mut(|a| a.set_tv_standard(x)),
+            None => console_log!("Unknown TV standard {name}"),
+        }
+    }
+
+    /// Colors of the current standard, indexed by color / 2.
+    #[wasm_bindgen(js_name = "colorMap")]
+    pub fn color_map(&self) -> js_sys::Uint32Array {
+        TV.with_borrow(|tv| {
+            let tv = tv.as_ref().unwrap().borrow();
+            let map = tia::TV::palette(&*tv);
+            let js_map = js_sys::Uint32Array::new_with_length(map.len() as u32);
+            js_map.copy_from(map);
+            js_map
+        })
+    }
+
+    /// Height of the frames handed to the render callback.
+    #[wasm_bindgen(js_name = "tvScanlines")]
+    pub fn tv_scanlines(&self) -> usize {
+        TV.with_borrow(|tv| tia::TV::config(&*tv.as_ref().unwrap().borrow()).scanlines())
+    }
+
+    /// Width of the frames handed to the render callback.
+    #[wasm_bindgen(js_name = "tvPixelsPerScanline")]
+    pub fn tv_pixels_per_scanline(&self) -> usize {
+        TV.with_borrow(|tv| tia::TV::config(&*tv.as_ref().unwrap().borrow()).pixels_per_scanline())
+    }
 }
 
 #[wasm_bindgen]
@@ -76,3 +306,28 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// 0 for left, anything else for right.
+fn to_port(port: u8) -> ctrl::Port {
+    if port == 0 {
+        ctrl::Port::Left
+    } else {
+        ctrl::Port::Right
+    }
+}
+
+fn standard_name(standard: tia::TVStandard) -> &'static str {
+    match standard {
+        tia::TVStandard::Ntsc => "ntsc",
+        tia::TVStandard::Pal => "pal",
+        tia::TVStandard::Secam => "secam",
+        tia::TVStandard::Pal60 => "pal60",
+        tia::TVStandard::Ntsc50 => "ntsc50",
+    }
+}
+
+fn to_standard(name: &str) -> Option<tia::TVStandard> {
+    tia::TVStandard::ALL
+        .into_iter()
+        .find(|&x| standard_name(x) == name)
+}
@@ -2,38 +2,51 @@ use rustella::*;
 use wasm_bindgen::prelude::*;
 
 /// NOTE: Pretty much a copy of InMemoryTV, figure out a way to abstract it out.
-pub struct NtscWebTV {
-    config: tia::TVConfig<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }>,
+pub struct WebTV {
+    config: tia::TVConfig,
     /// Number of times VSYNC has been called.
+    frame_counter: u64,
     curr_scanline: usize,
     curr_pixel: usize,
     vsync_on: bool,
-    video_buffer: [u8; tia::NTSC_PIXELS_PER_SCANLINE * tia::NTSC_SCANLINES],
+    black_and_white: bool,
+    /// As many scanlines as the config has, more if the current frame is taller.
+    video_buffer: Vec<u8>,
     /// JS callback.
     render_frame_fn: JsValue,
 }
 
-impl NtscWebTV {
+impl WebTV {
     pub fn new(render_frame_fn: JsValue) -> Self {
         let config = tia::ntsc_tv_config();
 
         Self {
+            frame_counter: 0,
             curr_scanline: 0,
             curr_pixel: 0,
-            config,
             vsync_on: false,
-            video_buffer: [0x00; tia::NTSC_PIXELS_PER_SCANLINE * tia::NTSC_SCANLINES],
+            black_and_white: false,
+            video_buffer: vec![0x00; config.pixels_per_scanline() * config.scanlines()],
             render_frame_fn,
+            config,
         }
     }
 }
 
-impl tia::TV<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }> for NtscWebTV {
-    fn config(&self) -> &tia::TVConfig<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }> {
+impl tia::TV for WebTV {
+    fn config(&self) -> &tia::TVConfig {
         &self.config
     }
 
-    fn post_vsync(&self) {
+    fn set_config(&mut self, config: tia::TVConfig) {
+        self.video_buffer = vec![0x00; config.pixels_per_scanline() * config.scanlines()];
+        self.curr_pixel %= config.pixels_per_scanline();
+        self.config = config;
+    }
+
+    /// Scanlines past the nominal height are handed over with the frame, then dropped. They are
+    /// drawn again if the program keeps producing tall frames.
+    fn post_vsync(&mut self) {
         let js_pixel_arr = js_sys::Uint8Array::new_with_length(self.video_buffer.len() as u32);
         js_pixel_arr.copy_from(&self.video_buffer);
         self.render_frame_fn
@@ -41,6 +54,8 @@ impl tia::TV<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }> for Nts
             .unchecked_into::<js_sys::Function>()
             .call1(&JsValue::null(), &js_pixel_arr)
             .unwrap();
+        self.video_buffer
+            .truncate(self.config.pixels_per_scanline() * self.config.scanlines());
     }
 
     #[inline]
@@ -65,18 +80,24 @@ impl tia::TV<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }> for Nts
 
     #[inline]
     fn write_buffer(&mut self, color: u8) {
-        self.video_buffer
-            [tia::NTSC_PIXELS_PER_SCANLINE * self.current_scanline() + self.current_pixel()] =
-            color;
+        let pixels_per_scanline = self.config.pixels_per_scanline();
+        let offset = pixels_per_scanline * self.curr_scanline + self.curr_pixel;
+        if offset >= self.video_buffer.len() {
+            self.video_buffer
+                .resize(pixels_per_scanline * (self.curr_scanline + 1), 0x00);
+        }
+        self.video_buffer[offset] = color;
     }
 
     #[inline]
     fn frame_counter(&self) -> u64 {
-        0
+        self.frame_counter
     }
 
     #[inline]
-    fn set_frame_counter(&mut self, _frames: u64) {}
+    fn set_frame_counter(&mut self, frames: u64) {
+        self.frame_counter = frames
+    }
 
     #[inline]
     fn vsync_on(&self) -> bool {
@@ -87,4 +108,14 @@ impl tia::TV<{ tia::NTSC_SCANLINES }, { tia::NTSC_PIXELS_PER_SCANLINE }> for Nts
     fn set_vsync_on(&mut self, on: bool) {
         self.vsync_on = on;
     }
+
+    #[inline]
+    fn black_and_white(&self) -> bool {
+        self.black_and_white
+    }
+
+    #[inline]
+    fn set_black_and_white(&mut self, on: bool) {
+        self.black_and_white = on;
+    }
 }
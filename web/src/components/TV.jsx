@@ -3,21 +3,30 @@ import { useEffect, useRef, useState } from 'react'
 import { useSearchParams } from 'react-router-dom'
 import useSWR from 'swr'
 import humanizeString from 'humanize-string'
-import init, { ntscColorMap, Atari } from 'rustella-wasm'
+import init, { Atari } from 'rustella-wasm'
 import { fetcher, getStartAddress } from '../utils'
 import ROMS from '../roms'
 import RomUploader from './RomUploader'
 
 const TV_WIDTH = 228
 const TV_HEIGHT = 262
+const TV_STANDARDS = ['ntsc', 'pal', 'secam', 'pal60', 'ntsc50']
 
 const fillRect = (ctx, x, y, w, h, color) => {
   ctx.fillStyle = color
   ctx.fillRect(x, y, w, h)
 }
 
-const renderFrame = (setTotalFrames, colorMap, context) => (pixels) => {
-  const imgData = context.createImageData(TV_WIDTH, TV_HEIGHT)
+// NOTE: Called from within atari.tick, so the geometry & colors come from tvRef, which is kept
+// up to date between ticks by syncTv.
+const renderFrame = (setTotalFrames, tvRef, context) => (pixels) => {
+  const { width, colorMap } = tvRef.current
+  const height = pixels.length / width
+  // NOTE: Frames taller than the standard's come with extra scanlines.
+  if (context.canvas.height !== height) {
+    context.canvas.height = height
+  }
+  const imgData = context.createImageData(width, height)
 
   for (let i = 0; i < imgData.data.length; i += 4) {
     const color = colorMap[pixels[Math.floor(i / 4)] / 2]
@@ -28,17 +37,64 @@ const renderFrame = (setTotalFrames, colorMap, context) => (pixels) => {
   }
 
   context.putImageData(imgData, 0, 0)
-  fillRect(context, 0, 0, 68, 262, 'rgba(255, 255, 255, 0.2)')
+  fillRect(context, 0, 0, 68, height, 'rgba(255, 255, 255, 0.2)')
   setTotalFrames((x) => x + 1)
 }
 
+const JOYSTICK_KEYS = {
+  ArrowUp: 'up',
+  ArrowDown: 'down',
+  ArrowLeft: 'left',
+  ArrowRight: 'right',
+  Space: 'fire',
+}
+
+const handleJoystickKeys = (atari) => {
+  const state = {
+    up: false,
+    down: false,
+    left: false,
+    right: false,
+    fire: false,
+  }
+  const onKey = (pressed) => (e) => {
+    const key = JOYSTICK_KEYS[e.code]
+    if (!key) {
+      return
+    }
+
+    e.preventDefault()
+    state[key] = pressed
+    atari.setJoystick(
+      0,
+      state.up,
+      state.down,
+      state.left,
+      state.right,
+      state.fire
+    )
+  }
+  const onKeyDown = onKey(true)
+  const onKeyUp = onKey(false)
+  window.addEventListener('keydown', onKeyDown)
+  window.addEventListener('keyup', onKeyUp)
+
+  return () => {
+    window.removeEventListener('keydown', onKeyDown)
+    window.removeEventListener('keyup', onKeyUp)
+  }
+}
+
 const defaultUploadedRomInfo = { name: '', data: new Uint8Array() }
 
 const TV = () => {
   const [searchParams, setSearchParams] = useSearchParams()
   const canvasRef = useRef(null)
+  const atariRef = useRef(null)
+  const tvRef = useRef({ width: TV_WIDTH, height: TV_HEIGHT, colorMap: [] })
   const [wasmInitialized, setWasmInitialized] = useState(false)
-  const [colorMap, setColorMap] = useState([])
+  const [tvSize, setTvSize] = useState({ width: TV_WIDTH, height: TV_HEIGHT })
+  const [tvStandard, setTvStandard] = useState('auto')
   const [totalTime, setTotalTime] = useState(0)
   const [totalFrames, setTotalFrames] = useState(0)
   const [selectedStockRomId, setSelectedStockRomId] = useState(0)
@@ -48,11 +104,29 @@ const TV = () => {
     suspense: true,
   })
 
+  // Follows the TV to the standard it was switched to, by detection or by the user.
+  const syncTv = (atari) => {
+    const standard = atari.tvStandard()
+    const width = atari.tvPixelsPerScanline()
+    const height = atari.tvScanlines()
+    const tv = tvRef.current
+    if (
+      tv.colorMap.length &&
+      tv.standard === standard &&
+      tv.width === width &&
+      tv.height === height
+    ) {
+      return
+    }
+
+    tvRef.current = { standard, width, height, colorMap: atari.colorMap() }
+    setTvSize({ width, height, standard })
+  }
+
   useEffect(() => {
     ;(async () => {
       await init()
       setWasmInitialized(true)
-      setColorMap(ntscColorMap())
     })()
   }, [])
 
@@ -82,28 +156,34 @@ const TV = () => {
       : ROMS[selectedStockRomId].start_addr
 
     const atari = new Atari(
-      renderFrame(setTotalFrames, colorMap, canvasRef.current.getContext('2d'))
+      renderFrame(setTotalFrames, tvRef, canvasRef.current.getContext('2d'))
     )
     atari.loadROM(name, startAddr, new Uint8Array(romData))
+    if (tvStandard === 'auto') {
+      atari.enableTvDetection()
+    } else {
+      atari.setTvStandard(tvStandard)
+    }
+    syncTv(atari)
+    atariRef.current = atari
+    const removeJoystickKeys = handleJoystickKeys(atari)
 
     setTotalTime(0)
     setTotalFrames(0)
     const interval = setInterval(() => {
       const start = Date.now()
       atari.tick(20000)
+      syncTv(atari)
       setTotalTime((x) => x + Date.now() - start)
     }, 10)
 
     return () => {
       clearInterval(interval)
+      removeJoystickKeys()
     }
-  }, [
-    wasmInitialized,
-    colorMap,
-    selectedStockRomId,
-    stockRomData,
-    uploadedRomInfo,
-  ])
+    // NOTE: A change of standard is handed to the running atari, it does not restart it.
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [wasmInitialized, selectedStockRomId, stockRomData, uploadedRomInfo])
 
   const stockRomDropDownItems = (type, startValue) =>
     ROMS.filter((x) => x.type === type).map((r, i) => {
@@ -143,18 +223,43 @@ const TV = () => {
             setUploadedRomInfo(x)
           }}
         />
+        <select
+          className="mx-4"
+          value={tvStandard}
+          onChange={(e) => {
+            const standard = e.target.value
+            setTvStandard(standard)
+            const atari = atariRef.current
+            if (!atari) {
+              return
+            }
+            if (standard === 'auto') {
+              atari.enableTvDetection()
+            } else {
+              atari.setTvStandard(standard)
+            }
+            syncTv(atari)
+          }}
+        >
+          <option value="auto">Auto</option>
+          {TV_STANDARDS.map((x) => (
+            <option key={x} value={x}>
+              {x.toUpperCase()}
+            </option>
+          ))}
+        </select>
       </div>
       <canvas
         className="bg-black"
         style={{ transform: 'scale(2.0, 1.0)' }}
-        width={TV_WIDTH}
-        height={TV_HEIGHT}
+        width={tvSize.width}
+        height={tvSize.height}
         ref={canvasRef}
       />
       <figcaption className="mb-2 text-xs">
         {humanizeString(romName)}
       </figcaption>
-      <div>{`${String(Math.trunc((totalFrames * 1000) / totalTime)).padStart(3, '0')} fps`}</div>
+      <div>{`${String(Math.trunc((totalFrames * 1000) / totalTime)).padStart(3, '0')} fps ${(tvSize.standard ?? '').toUpperCase()}`}</div>
     </div>
   )
 }